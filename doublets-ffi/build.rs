@@ -0,0 +1,294 @@
+//! Generates `doublets.h` and `doublets.pc` from the `specialize_for` functions.
+//!
+//! Both files are written into `OUT_DIR` only and installed
+//! with the libraries by `cargo xtask ffi-install <prefix>`.
+
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use syn::{
+    Attribute, Expr, FnArg, GenericArgument, Item, ItemEnum, ItemFn, Lit, Meta, NestedMeta, Pat,
+    PathArguments, ReturnType, Type,
+};
+
+const SOURCES: [&str; 3] = ["src/lib.rs", "src/errors.rs", "src/iter.rs"];
+
+// specialized types with names by `csharp` convention
+const TYPES: [(&str, &str); 4] = [
+    ("u8", "Byte"),
+    ("u16", "UInt16"),
+    ("u32", "UInt32"),
+    ("u64", "UInt64"),
+];
+
+struct Specialized {
+    name: String,
+    func: ItemFn,
+}
+
+fn specialized(attr: &Attribute) -> Option<String> {
+    if !attr
+        .path
+        .segments
+        .iter()
+        .any(|seg| seg.ident == "specialize_for")
+    {
+        return None;
+    }
+    match attr.parse_meta().ok()? {
+        Meta::List(list) => list.nested.into_iter().find_map(|nested| match nested {
+            NestedMeta::Meta(Meta::NameValue(value)) if value.path.is_ident("name") => {
+                match value.lit {
+                    Lit::Str(name) => Some(name.value()),
+                    _ => None,
+                }
+            }
+            _ => None,
+        }),
+        _ => None,
+    }
+}
+
+fn parse(path: &Path) -> Vec<Item> {
+    let source = fs::read_to_string(path).expect("unable to read ffi source");
+    syn::parse_file(&source)
+        .expect("unable to parse ffi source")
+        .items
+}
+
+fn functions(items: &[Item]) -> Vec<Specialized> {
+    items
+        .iter()
+        .cloned()
+        .filter_map(|item| match item {
+            Item::Fn(func) => func
+                .attrs
+                .iter()
+                .find_map(specialized)
+                .map(|name| Specialized { name, func }),
+            _ => None,
+        })
+        .collect()
+}
+
+fn c_type(ty: &Type, generic: &str, (rust, prefix): (&str, &str)) -> String {
+    match ty {
+        Type::Ptr(ptr) => {
+            let elem = c_type(&ptr.elem, generic, (rust, prefix));
+            if ptr.const_token.is_some() {
+                format!("const {elem}*")
+            } else {
+                format!("{elem}*")
+            }
+        }
+        Type::Tuple(tuple) if tuple.elems.is_empty() => "void".to_string(),
+        Type::Path(path) => {
+            let seg = path.path.segments.last().expect("empty type path");
+            let ident = seg.ident.to_string();
+            let is_generic = matches!(&seg.arguments, PathArguments::AngleBracketed(args)
+                if args.args.iter().any(|arg| matches!(arg, GenericArgument::Type(_))));
+            match ident.as_str() {
+                _ if ident == generic => c_int(rust).to_string(),
+                _ if is_generic => format!("{prefix}{ident}"),
+                "c_char" => "char".to_string(),
+                "c_void" => "void".to_string(),
+                "bool" => "bool".to_string(),
+                "usize" => "size_t".to_string(),
+                "ErrorKind" => "DoubletsErrorKind".to_string(),
+                other => panic!("`{other}` is not supported by the header generator"),
+            }
+        }
+        _ => panic!("unexpected doublets-ffi type"),
+    }
+}
+
+fn c_int(rust: &str) -> &'static str {
+    match rust {
+        "u8" => "uint8_t",
+        "u16" => "uint16_t",
+        "u32" => "uint32_t",
+        "u64" => "uint64_t",
+        other => panic!("`{other}` is not supported by the header generator"),
+    }
+}
+
+fn declaration(func: &Specialized, ty: (&str, &str)) -> String {
+    let sig = &func.func.sig;
+    let generic = sig
+        .generics
+        .type_params()
+        .next()
+        .expect("specialized function must be generic")
+        .ident
+        .to_string();
+    let output = match &sig.output {
+        ReturnType::Default => "void".to_string(),
+        ReturnType::Type(_, output) => c_type(output, &generic, ty),
+    };
+    let inputs: Vec<_> = sig
+        .inputs
+        .iter()
+        .map(|arg| match arg {
+            FnArg::Typed(arg) => {
+                let name = arg_name(&arg.pat);
+                format!("{} {name}", c_type(&arg.ty, &generic, ty))
+            }
+            FnArg::Receiver(_) => panic!("function with `self` is not supported"),
+        })
+        .collect();
+    let inputs = if inputs.is_empty() {
+        "void".to_string()
+    } else {
+        inputs.join(", ")
+    };
+    format!("{output} {}({inputs});\n", func.name.replace('*', ty.1))
+}
+
+fn arg_name(pat: &Pat) -> String {
+    match pat {
+        // `this` is reserved in C++
+        Pat::Ident(ident) if ident.ident == "this" => "links".to_string(),
+        Pat::Ident(ident) => ident.ident.to_string(),
+        _ => panic!("unsupported argument pattern"),
+    }
+}
+
+fn types((rust, prefix): (&str, &str)) -> String {
+    let int = c_int(rust);
+    format!(
+        r"typedef struct {prefix}Link {{
+    {int} index;
+    {int} source;
+    {int} target;
+}} {prefix}Link;
+
+typedef struct {prefix}Range {{
+    {int} start;
+    {int} end;
+}} {prefix}Range;
+
+typedef struct {prefix}Constants {{
+    {int} index_part;
+    {int} source_part;
+    {int} target_part;
+    {int} null;
+    {int} continue_;
+    {int} break_;
+    {int} skip;
+    {int} any;
+    {int} itself;
+    {int} error;
+    {prefix}Range internal_range;
+    {prefix}Range external_range;
+    bool _opt_marker;
+}} {prefix}Constants;
+
+typedef struct {prefix}LastError {{
+    DoubletsErrorKind kind;
+    {prefix}Link link;
+    size_t count;
+}} {prefix}LastError;
+
+typedef {int} (*{prefix}EachCallback)({prefix}Link link);
+
+typedef {int} (*{prefix}CUDCallback)({prefix}Link before, {prefix}Link after);
+
+"
+    )
+}
+
+fn error_kind(items: &[Item]) -> String {
+    let kind = items
+        .iter()
+        .find_map(|item| match item {
+            Item::Enum(kind) if kind.ident == "ErrorKind" => Some(kind),
+            _ => None,
+        })
+        .expect("`ErrorKind` is not found");
+    let ItemEnum { variants, .. } = kind;
+    let variants: String = variants
+        .iter()
+        .map(|variant| match &variant.discriminant {
+            Some((_, Expr::Lit(lit))) => match &lit.lit {
+                Lit::Int(int) => format!("    DoubletsErrorKind_{} = {int},\n", variant.ident),
+                _ => panic!("`ErrorKind` discriminants must be integers"),
+            },
+            _ => panic!("`ErrorKind` discriminants must be explicit"),
+        })
+        .collect();
+    format!("typedef enum DoubletsErrorKind {{\n{variants}}} DoubletsErrorKind;\n\n")
+}
+
+fn header(items: &[Item]) -> String {
+    let mut out = String::from(
+        r#"/* Generated by doublets-ffi build script, do not edit. */
+
+#ifndef DOUBLETS_H
+#define DOUBLETS_H
+
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+"#,
+    );
+    out.push_str(&error_kind(items));
+    out.push_str("void init_fmt_logger(void);\n\n");
+    out.push_str("const char* Links_GetLastErrorMessage(void);\n\n");
+    let functions = functions(items);
+    for ty in TYPES {
+        out.push_str(&types(ty));
+        for func in &functions {
+            out.push_str(&declaration(func, ty));
+        }
+        out.push('\n');
+    }
+    out.push_str(
+        r"#ifdef __cplusplus
+}
+#endif
+
+#endif /* DOUBLETS_H */
+",
+    );
+    out
+}
+
+// relative to its location, so it is valid wherever `<prefix>/lib/pkgconfig` is installed
+fn pkg_config() -> String {
+    format!(
+        r"prefix=${{pcfiledir}}/../..
+includedir=${{prefix}}/include
+libdir=${{prefix}}/lib
+
+Name: doublets
+Description: {}
+Version: {}
+Cflags: -I${{includedir}}
+Libs: -L${{libdir}} -ldoublets_ffi
+Libs.private: -lpthread -ldl -lm
+",
+        env::var("CARGO_PKG_DESCRIPTION").expect("set by cargo"),
+        env::var("CARGO_PKG_VERSION").expect("set by cargo"),
+    )
+}
+
+fn main() {
+    let items: Vec<_> = SOURCES
+        .iter()
+        .flat_map(|source| {
+            println!("cargo:rerun-if-changed={source}");
+            parse(Path::new(source))
+        })
+        .collect();
+
+    let out_dir = PathBuf::from(env::var("OUT_DIR").expect("set by cargo"));
+    fs::write(out_dir.join("doublets.h"), header(&items)).expect("unable to write header");
+    fs::write(out_dir.join("doublets.pc"), pkg_config()).expect("unable to write pkg-config file");
+}
@@ -1,5 +1,3 @@
-#![feature(box_syntax)]
-
 use proc_macro::TokenStream;
 
 use darling::FromMeta;
@@ -159,7 +157,7 @@ pub fn specialize_for(args: TokenStream, input: TokenStream) -> TokenStream {
                 panic!("function with `self` is not supported")
             }
             FnArg::Typed(pat_type) => {
-                pat_type.ty = box ty_from_to(*(pat_type.ty).clone(), &generic_name, &ty);
+                pat_type.ty = Box::new(ty_from_to(*(pat_type.ty).clone(), &generic_name, &ty));
             }
         });
 
@@ -0,0 +1,175 @@
+use std::{
+    any::Any, cell::RefCell, error::Error as StdError, ffi::CString, fmt::Display, io, ptr::null,
+};
+
+use doublets::{data::LinkType, Error, Link};
+use libc::c_char;
+use log::error;
+
+use ffi_attributes as ffi;
+
+/// Kind of the last error happened in the current thread.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+#[repr(C)]
+pub enum ErrorKind {
+    None = 0,
+    NotExists = 1,
+    HasUsages = 2,
+    AlreadyExists = 3,
+    LimitReached = 4,
+    AllocFailed = 5,
+    Io = 6,
+    Other = 7,
+    ReadOnly = 8,
+}
+
+/// Last error with the offending link:
+/// - `NotExists` - index of the missing link
+/// - `HasUsages` - first usage, `count` is the number of all usages
+/// - `AlreadyExists` - source and target of the existing link
+/// - `LimitReached` - index of the limit
+#[repr(C)]
+pub struct LastError<T: LinkType> {
+    pub kind: ErrorKind,
+    pub link: Link<T>,
+    pub count: usize,
+}
+
+struct Stored {
+    kind: ErrorKind,
+    message: CString,
+    // `Error<T>` of the store type the error happened in
+    error: Option<Box<dyn Any>>,
+}
+
+thread_local! {
+    static LAST_ERROR: RefCell<Option<Stored>> = RefCell::new(None);
+}
+
+fn kind_of<T: LinkType>(error: &Error<T>) -> ErrorKind {
+    match error {
+        Error::NotExists(_) => ErrorKind::NotExists,
+        Error::HasUsages(_) => ErrorKind::HasUsages,
+        Error::AlreadyExists(_) => ErrorKind::AlreadyExists,
+        Error::LimitReached(_) => ErrorKind::LimitReached,
+        Error::AllocFailed(_) => ErrorKind::AllocFailed,
+        Error::ReadOnly => ErrorKind::ReadOnly,
+        Error::Other(_) => ErrorKind::Other,
+    }
+}
+
+fn store(kind: ErrorKind, message: impl Display, error: Option<Box<dyn Any>>) {
+    error!("{message}");
+    // interior nul bytes cannot be passed to C, so the message is cut on them
+    let mut message = message.to_string().into_bytes();
+    if let Some(nul) = message.iter().position(|&byte| byte == 0) {
+        message.truncate(nul);
+    }
+    let message = CString::new(message).expect("nul bytes are removed");
+    LAST_ERROR.with(|last| {
+        *last.borrow_mut() = Some(Stored {
+            kind,
+            message,
+            error,
+        })
+    });
+}
+
+pub(crate) fn clear_last_error() {
+    LAST_ERROR.with(|last| *last.borrow_mut() = None);
+}
+
+pub(crate) fn result_into_error<T: LinkType + 'static, R>(
+    result: Result<R, Error<T>>,
+    default: R,
+) -> R {
+    match result {
+        Ok(value) => {
+            clear_last_error();
+            value
+        }
+        Err(err) => {
+            store(kind_of(&err), err.to_string(), Some(Box::new(err)));
+            default
+        }
+    }
+}
+
+// errors of store constructors: wrapped links errors, I/O and path errors
+pub(crate) fn boxed_into_error<T: LinkType + 'static, R>(
+    result: Result<R, Box<dyn StdError>>,
+    default: R,
+) -> R {
+    match result {
+        Ok(value) => {
+            clear_last_error();
+            value
+        }
+        Err(err) => match err.downcast::<Error<T>>() {
+            Ok(err) => result_into_error(Err(*err), default),
+            Err(err) => {
+                let kind = if err.is::<io::Error>() {
+                    ErrorKind::Io
+                } else {
+                    ErrorKind::Other
+                };
+                store(kind, err, None);
+                default
+            }
+        },
+    }
+}
+
+fn offending<T: LinkType>(error: &Error<T>) -> (Link<T>, usize) {
+    let zero = T::funty(0);
+    match error {
+        Error::NotExists(index) | Error::LimitReached(index) => (Link::new(*index, zero, zero), 1),
+        Error::HasUsages(usages) => (
+            usages.first().cloned().unwrap_or_else(Link::nothing),
+            usages.len(),
+        ),
+        Error::AlreadyExists(doublet) => (Link::new(zero, doublet.source, doublet.target), 1),
+        Error::AllocFailed(_) | Error::ReadOnly | Error::Other(_) => (Link::nothing(), 0),
+    }
+}
+
+/// Returns the last error happened in the current thread.
+/// Every fallible function stores its error and clears it on success.
+/// The link is filled only if the error happened in a store of the same type.
+#[ffi::specialize_for(
+    types = "u8",
+    types = "u16",
+    types = "u32",
+    types = "u64",
+    convention = "csharp",
+    name = "*Links_GetLastError"
+)]
+unsafe fn get_last_error<T: LinkType + 'static>() -> LastError<T> {
+    LAST_ERROR.with(|last| {
+        let last = last.borrow();
+        let (link, count) = last
+            .as_ref()
+            .and_then(|stored| stored.error.as_ref())
+            .and_then(|error| error.downcast_ref::<Error<T>>())
+            .map_or((Link::nothing(), 0), offending);
+        LastError {
+            kind: last.as_ref().map_or(ErrorKind::None, |stored| stored.kind),
+            link,
+            count,
+        }
+    })
+}
+
+/// Returns the message of the last error happened in the current thread or null.
+/// The string is owned by the library and is valid until the next call
+/// of a fallible function in the same thread.
+/// The message does not depend on the store type, so it is not specialized.
+#[no_mangle]
+#[allow(non_snake_case)]
+pub extern "C" fn Links_GetLastErrorMessage() -> *const c_char {
+    LAST_ERROR.with(|last| {
+        last.borrow()
+            .as_ref()
+            .map_or(null(), |stored| stored.message.as_ptr())
+    })
+}
@@ -0,0 +1,116 @@
+use std::vec;
+
+use doublets::{
+    data::{LinkType, ToQuery},
+    Cursor, Doublets, DoubletsExt, Link,
+};
+
+use crate::{c_void, query_from_raw, unnull_or_error, WrappedLinks};
+use ffi_attributes as ffi;
+
+// links are read from the store by pages of this size
+const PAGE: usize = 64;
+
+struct LinksIter<T: LinkType> {
+    links: *mut c_void,
+    // the rest of the scan or `None` if it is finished
+    cursor: Option<Cursor<T>>,
+    // the target required from usages of the source if both are queried
+    target: Option<T>,
+    page: vec::IntoIter<Link<T>>,
+}
+
+impl<T: LinkType> LinksIter<T> {
+    unsafe fn new(links: *mut c_void, query: &[T]) -> Self {
+        let store: &mut WrappedLinks<T> = unnull_or_error(links);
+        let any = store.constants().any;
+        let mut iter = Self {
+            links,
+            cursor: Cursor::from_query(query, store.constants()),
+            target: None,
+            page: Vec::new().into_iter(),
+        };
+        match *query {
+            _ if iter.cursor.is_some() => {}
+            // usages of the source are filtered by the target
+            [index, source, target] if index == any => {
+                iter.cursor = Some(Cursor::usages_as_source(source));
+                iter.target = Some(target);
+            }
+            // other queries pin the index or match a value in any part, so they are collected
+            _ => iter.page = store.each_iter(query.to_query()),
+        }
+        iter
+    }
+
+    unsafe fn next(&mut self) -> Option<Link<T>> {
+        loop {
+            if let Some(link) = self.page.next() {
+                if self.target.map_or(true, |target| link.target == target) {
+                    return Some(link);
+                }
+                continue;
+            }
+            let store: &mut WrappedLinks<T> = unnull_or_error(self.links);
+            let (page, cursor) = store.page_by_cursor(self.cursor.as_ref()?, PAGE);
+            self.page = page.into_iter();
+            self.cursor = cursor;
+        }
+    }
+}
+
+/// Creates an iterator over links matching the `query`.
+///
+/// Links are read from the store page by page while the iterator is advanced,
+/// so it sees changes of the store made between calls and must not outlive it.
+/// The iterator must be freed by `*Links_IterFree`.
+#[ffi::specialize_for(
+    types = "u8",
+    types = "u16",
+    types = "u32",
+    types = "u64",
+    convention = "csharp",
+    name = "*Links_IterNew"
+)]
+unsafe fn iter_new<T: LinkType>(this: *mut c_void, query: *const T, len: usize) -> *mut c_void {
+    let query = query_from_raw(query, len);
+    let iter = Box::new(LinksIter::new(this, &query[..]));
+    Box::into_raw(iter).cast()
+}
+
+/// Writes the next link into `link` and returns `true`
+/// or returns `false` if the iterator is exhausted.
+#[ffi::specialize_for(
+    types = "u8",
+    types = "u16",
+    types = "u32",
+    types = "u64",
+    convention = "csharp",
+    name = "*Links_IterNext"
+)]
+unsafe fn iter_next<T: LinkType>(iter: *mut c_void, link: *mut Link<T>) -> bool {
+    let iter: &mut LinksIter<T> = unnull_or_error(iter);
+    let out: &mut Link<T> = unnull_or_error(link);
+    match iter.next() {
+        Some(next) => {
+            *out = next;
+            true
+        }
+        None => false,
+    }
+}
+
+/// Frees the iterator, null is ignored.
+#[ffi::specialize_for(
+    types = "u8",
+    types = "u16",
+    types = "u32",
+    types = "u64",
+    convention = "csharp",
+    name = "*Links_IterFree"
+)]
+unsafe fn iter_free<T: LinkType>(iter: *mut c_void) {
+    if !iter.is_null() {
+        drop(Box::from_raw(iter.cast::<LinksIter<T>>()));
+    }
+}
@@ -1,16 +1,4 @@
-#![feature(try_blocks)]
-#![feature(box_syntax)]
-#![feature(try_trait_v2)]
-
-use std::{
-    error::Error,
-    ffi::CStr,
-    fmt::Display,
-    fs::File,
-    mem,
-    ops::{RangeInclusive, Try},
-    ptr::{drop_in_place, null_mut},
-};
+use std::{error::Error, ffi::CStr, fs::File, ops::RangeInclusive, ptr::null_mut};
 
 use doublets::{
     data::{
@@ -27,15 +15,14 @@ use log::{error, warn};
 #[allow(non_camel_case_types)]
 type c_void = core::ffi::c_void;
 
-use doublets::{parts, unit, Doublets};
+use doublets::{parts, split, unit, Branch, Doublets};
+use errors::{boxed_into_error, result_into_error};
 use ffi_attributes as ffi;
 
-fn result_into_log<R, E: Display>(result: Result<R, E>, default: R) -> R {
-    result.unwrap_or_else(|e| {
-        error!("{e}");
-        default
-    })
-}
+mod errors;
+mod iter;
+
+pub use errors::{ErrorKind, LastError};
 
 unsafe fn query_from_raw<'a, T: LinkType>(query: *const T, len: usize) -> Query<'a, T> {
     // it not require `#[cfg(debug_assertions)]`,
@@ -51,6 +38,47 @@ unsafe fn query_from_raw<'a, T: LinkType>(query: *const T, len: usize) -> Query<
     }
 }
 
+unsafe fn map_file<U: Default>(path: *const c_char) -> Result<FileMapped<U>, Box<dyn Error>> {
+    let path = CStr::from_ptr(path).to_str()?;
+    let file = File::options()
+        .create(true)
+        .read(true)
+        .write(true)
+        .open(path)?;
+    Ok(FileMapped::new(file)?)
+}
+
+unsafe fn open_united<T: LinkType>(
+    path: *const c_char,
+    constants: Constants<T>,
+) -> Result<WrappedLinks<T>, Box<dyn Error>> {
+    let mem = map_file(path)?;
+    Ok(Box::new(UnitedLinks::<T>::with_constants(
+        mem,
+        constants.into(),
+    )?))
+}
+
+unsafe fn open_split<T: LinkType>(
+    data_path: *const c_char,
+    index_path: *const c_char,
+    constants: Constants<T>,
+) -> Result<WrappedLinks<T>, Box<dyn Error>> {
+    let data_mem = map_file(data_path)?;
+    let index_mem = map_file(index_path)?;
+    Ok(Box::new(SplitLinks::<T>::with_constants(
+        data_mem,
+        index_mem,
+        constants.into(),
+    )?))
+}
+
+// the handle is a thin pointer to the boxed `dyn Doublets<T>`,
+// so every entry point works with both united and split stores
+fn into_raw<T: LinkType>(links: WrappedLinks<T>) -> *mut c_void {
+    Box::into_raw(Box::new(links)).cast()
+}
+
 unsafe fn unnull_or_error<'a, P, R>(ptr: *mut P) -> &'a mut R {
     if ptr.is_null() {
         // todo: use std::Backtrace or crates/tracing
@@ -64,6 +92,9 @@ unsafe fn unnull_or_error<'a, P, R>(ptr: *mut P) -> &'a mut R {
 // TODO: remove ::mem:: in doublets crate
 type UnitedLinks<T> = unit::Store<T, FileMapped<parts::LinkPart<T>>>;
 
+type SplitLinks<T> =
+    split::Store<T, FileMapped<parts::DataPart<T>>, FileMapped<parts::IndexPart<T>>>;
+
 type WrappedLinks<T> = Box<dyn Doublets<T>>;
 
 type EachCallback<T> = extern "C" fn(Link<T>) -> T;
@@ -168,21 +199,8 @@ unsafe fn new_with_constants_united_links<T: LinkType>(
     path: *const c_char,
     constants: Constants<T>,
 ) -> *mut c_void {
-    let result: Result<_, Box<dyn Error>> = try {
-        let path = CStr::from_ptr(path).to_str()?;
-        let file = File::options()
-            .create(true)
-            .read(true)
-            .write(true)
-            .open(path)?;
-        let mem = FileMapped::new(file)?;
-        let mut links: Box<dyn Doublets<T>> =
-            box UnitedLinks::<T>::with_constants(mem, constants.into())?;
-        let ptr = links.as_mut() as *mut _ as *mut c_void;
-        mem::forget(links);
-        ptr
-    };
-    result_into_log(result, null_mut())
+    let result = open_united::<T>(path, constants).map(into_raw);
+    boxed_into_error::<T, _>(result, null_mut())
 }
 
 #[ffi::specialize_for(
@@ -195,7 +213,39 @@ unsafe fn new_with_constants_united_links<T: LinkType>(
 )]
 unsafe fn drop_united_links<T: LinkType>(this: *mut c_void) {
     let links: &mut WrappedLinks<T> = unnull_or_error(this);
-    drop_in_place(links);
+    drop(Box::from_raw(links));
+}
+
+#[ffi::specialize_for(
+    types = "u8",
+    types = "u16",
+    types = "u32",
+    types = "u64",
+    convention = "csharp",
+    name = "*SplitLinks_New"
+)]
+unsafe fn new_split_links<T: LinkType>(
+    data_path: *const c_char,
+    index_path: *const c_char,
+) -> *mut c_void {
+    new_with_constants_split_links::<T>(data_path, index_path, LinksConstants::external().into())
+}
+
+#[ffi::specialize_for(
+    types = "u8",
+    types = "u16",
+    types = "u32",
+    types = "u64",
+    convention = "csharp",
+    name = "*SplitLinks_NewWithConstants"
+)]
+unsafe fn new_with_constants_split_links<T: LinkType>(
+    data_path: *const c_char,
+    index_path: *const c_char,
+    constants: Constants<T>,
+) -> *mut c_void {
+    let result = open_split::<T>(data_path, index_path, constants).map(into_raw);
+    boxed_into_error::<T, _>(result, null_mut())
 }
 
 #[ffi::specialize_for(
@@ -239,7 +289,7 @@ unsafe fn create_united<T: LinkType>(
         };
         links.create_by_with(query, handler)
     };
-    result_into_log(
+    result_into_error(
         result.map(|flow| {
             if flow.branch().is_continue() {
                 continue_
@@ -262,7 +312,7 @@ unsafe fn create_united<T: LinkType>(
 unsafe fn smart_create_united<T: LinkType>(this: *mut c_void) -> T {
     let links: &mut WrappedLinks<T> = unnull_or_error(this);
     let result = links.create();
-    result_into_log(result, links.constants().error)
+    result_into_error(result, links.constants().error)
 }
 
 #[ffi::specialize_for(
@@ -276,7 +326,7 @@ unsafe fn smart_create_united<T: LinkType>(this: *mut c_void) -> T {
 unsafe fn smart_update_united<T: LinkType>(this: *mut c_void, index: T, source: T, target: T) -> T {
     let links: &mut WrappedLinks<T> = unnull_or_error(this);
     let result = links.update(index, source, target);
-    result_into_log(result, links.constants().error)
+    result_into_error(result, links.constants().error)
 }
 
 #[ffi::specialize_for(
@@ -355,7 +405,7 @@ unsafe fn update_united<T: LinkType>(
         };
         links.update_by_with(restrictions, substitutuion, handler)
     };
-    result_into_log(
+    result_into_error(
         result.map(|flow| {
             if flow.branch().is_continue() {
                 continue_
@@ -395,7 +445,7 @@ unsafe fn delete_united<T: LinkType>(
         };
         links.delete_by_with(query, handler)
     };
-    result_into_log(
+    result_into_error(
         result.map(|flow| {
             if flow.branch().is_continue() {
                 continue_
@@ -451,7 +501,30 @@ pub extern "C" fn init_fmt_logger() {
     setup_shared_logger(logger);
 }
 
+#[cfg(test)]
 mod tests {
+    use std::{env, ffi::CString, fs, path::PathBuf, process};
+
+    // a file in the temporary directory unique for the process and the test, removed on drop
+    struct TempFile(PathBuf);
+
+    impl TempFile {
+        fn new(name: &str) -> Self {
+            let name = format!("doublets-ffi-{}-{name}.links", process::id());
+            Self(env::temp_dir().join(name))
+        }
+
+        fn c_path(&self) -> CString {
+            CString::new(self.0.to_str().unwrap()).unwrap()
+        }
+    }
+
+    impl Drop for TempFile {
+        fn drop(&mut self) {
+            let _ = fs::remove_file(&self.0);
+        }
+    }
+
     #[test]
     fn error_log() {
         use crate::{build_shared_logger, setup_shared_logger};
@@ -465,4 +538,136 @@ mod tests {
         warn!("warn");
         error!("error");
     }
+
+    #[test]
+    fn last_error() {
+        use crate::{
+            errors::{Links_GetLastErrorMessage, UInt64Links_GetLastError},
+            ErrorKind, UInt64Links_Drop, UInt64Links_New, UInt64Links_SmartCreate,
+            UInt64Links_SmartUpdate,
+        };
+        use std::ffi::CStr;
+
+        let file = TempFile::new("last-error");
+        let path = file.c_path();
+        unsafe {
+            let links = UInt64Links_New(path.as_ptr());
+            assert!(!links.is_null());
+
+            UInt64Links_SmartUpdate(links, 100, 0, 0);
+            let error = UInt64Links_GetLastError();
+            assert_eq!(error.kind, ErrorKind::NotExists);
+            assert_eq!(error.link.index, 100);
+            let message = CStr::from_ptr(Links_GetLastErrorMessage());
+            assert!(message.to_str().unwrap().contains("100"));
+
+            UInt64Links_SmartCreate(links);
+            assert_eq!(UInt64Links_GetLastError().kind, ErrorKind::None);
+            assert!(Links_GetLastErrorMessage().is_null());
+
+            UInt64Links_Drop(links);
+        }
+    }
+
+    #[test]
+    fn split_links() {
+        use crate::{
+            UInt64Links_Count, UInt64Links_Drop, UInt64Links_GetConstants, UInt64Links_SmartCreate,
+            UInt64Links_SmartUpdate, UInt64SplitLinks_New, UInt64SplitLinks_NewWithConstants,
+        };
+        use doublets::data::LinksConstants;
+        use std::ptr::null;
+
+        let data = TempFile::new("split-links-data");
+        let index = TempFile::new("split-links-index");
+        let (data_path, index_path) = (data.c_path(), index.c_path());
+        unsafe {
+            let links = UInt64SplitLinks_New(data_path.as_ptr(), index_path.as_ptr());
+            assert!(!links.is_null());
+
+            let point = UInt64Links_SmartCreate(links);
+            assert_eq!(UInt64Links_SmartUpdate(links, point, point, point), point);
+            let link = UInt64Links_SmartCreate(links);
+            assert_eq!(UInt64Links_SmartUpdate(links, link, point, 1000), link);
+
+            let any = UInt64Links_GetConstants(links).any;
+            assert_eq!(UInt64Links_Count(links, [any, point, any].as_ptr(), 3), 2);
+            assert_eq!(UInt64Links_Count(links, [any, any, 1000].as_ptr(), 3), 1);
+            UInt64Links_Drop(links);
+
+            // links are read back from the same files
+            let constants = LinksConstants::external().into();
+            let links = UInt64SplitLinks_NewWithConstants(
+                data_path.as_ptr(),
+                index_path.as_ptr(),
+                constants,
+            );
+            assert!(!links.is_null());
+            assert_eq!(UInt64Links_Count(links, null(), 0), 2);
+            assert_eq!(UInt64Links_Count(links, [link, point, 1000].as_ptr(), 3), 1);
+            UInt64Links_Drop(links);
+        }
+    }
+
+    #[test]
+    fn iterators() {
+        use crate::{
+            iter::{UInt64Links_IterFree, UInt64Links_IterNew, UInt64Links_IterNext},
+            UInt64Links_Drop, UInt64Links_GetConstants, UInt64Links_New, UInt64Links_SmartCreate,
+            UInt64Links_SmartUpdate,
+        };
+        use doublets::Link;
+        use std::ptr::null_mut;
+
+        unsafe fn collect(links: *mut std::ffi::c_void, query: &[u64]) -> Vec<Link<u64>> {
+            let iter = UInt64Links_IterNew(links, query.as_ptr(), query.len());
+            let mut link = Link::nothing();
+            let mut found = Vec::new();
+            while UInt64Links_IterNext(iter, &mut link) {
+                found.push(link);
+            }
+            UInt64Links_IterFree(iter);
+            found
+        }
+
+        let file = TempFile::new("iterators");
+        let path = file.c_path();
+        unsafe {
+            let links = UInt64Links_New(path.as_ptr());
+            assert!(!links.is_null());
+            let any = UInt64Links_GetConstants(links).any;
+            let root = UInt64Links_SmartCreate(links);
+            UInt64Links_SmartUpdate(links, root, root, root);
+            // more links than in a page read at once
+            for i in 0..200 {
+                let link = UInt64Links_SmartCreate(links);
+                UInt64Links_SmartUpdate(links, link, root, i % 3 + 1000);
+            }
+
+            let all = collect(links, &[]);
+            assert_eq!(all.len(), 201);
+            assert!(all.windows(2).all(|pair| pair[0].index < pair[1].index));
+            assert_eq!(collect(links, &[any, root, any]).len(), 201);
+            let found = collect(links, &[any, root, 1001]);
+            assert_eq!(found.len(), 67);
+            assert!(found.iter().all(|link| link.target == 1001));
+            assert_eq!(collect(links, &[5]), [Link::new(5, root, 1000)]);
+
+            // the store is read while the iterator is advanced
+            let iter = UInt64Links_IterNew(links, [any, any, 1002].as_ptr(), 3);
+            let mut link = Link::nothing();
+            assert!(UInt64Links_IterNext(iter, &mut link));
+            let created = UInt64Links_SmartCreate(links);
+            UInt64Links_SmartUpdate(links, created, root, 1002);
+            let mut count = 1;
+            while UInt64Links_IterNext(iter, &mut link) {
+                count += 1;
+            }
+            assert_eq!(count, 67);
+            UInt64Links_IterFree(iter);
+
+            UInt64Links_IterFree(null_mut());
+            UInt64Links_Drop(links);
+        }
+    }
 }
@@ -0,0 +1,71 @@
+/* Checks the generated header against the static library. */
+
+#include <stdio.h>
+#include <string.h>
+
+#include "doublets.h"
+
+#define CHECK(cond)                                                            \
+    do {                                                                       \
+        if (!(cond)) {                                                         \
+            fprintf(stderr, "%s:%d: `%s` failed\n", __FILE__, __LINE__, #cond); \
+            return 1;                                                          \
+        }                                                                      \
+    } while (0)
+
+static UInt64Constants constants;
+static uint64_t visited = 0;
+
+static uint64_t visit(UInt64Link link) {
+    (void)link;
+    visited++;
+    return constants.continue_;
+}
+
+static uint64_t changed(UInt64Link before, UInt64Link after) {
+    (void)before;
+    (void)after;
+    return constants.continue_;
+}
+
+int main(int argc, char **argv) {
+    CHECK(argc == 2);
+    void *links = UInt64Links_New(argv[1]);
+    CHECK(links != NULL);
+    constants = UInt64Links_GetConstants(links);
+
+    uint64_t a = UInt64Links_SmartCreate(links);
+    uint64_t b = UInt64Links_SmartCreate(links);
+    uint64_t c = UInt64Links_SmartCreate(links);
+    CHECK(UInt64Links_SmartUpdate(links, a, a, b) == a);
+    CHECK(UInt64Links_SmartUpdate(links, c, b, a) == c);
+    CHECK(UInt64Links_Count(links, NULL, 0) == 3);
+
+    uint64_t query[] = {constants.any, b, constants.any};
+    CHECK(UInt64Links_Count(links, query, 3) == 1);
+    CHECK(UInt64Links_Each(links, NULL, 0, visit) == constants.continue_);
+    CHECK(visited == 3);
+
+    void *iter = UInt64Links_IterNew(links, query, 3);
+    CHECK(iter != NULL);
+    UInt64Link link;
+    CHECK(UInt64Links_IterNext(iter, &link));
+    CHECK(link.index == c && link.source == b && link.target == a);
+    CHECK(!UInt64Links_IterNext(iter, &link));
+    UInt64Links_IterFree(iter);
+
+    CHECK(UInt64Links_SmartUpdate(links, 100, 0, 0) == constants.error);
+    UInt64LastError error = UInt64Links_GetLastError();
+    CHECK(error.kind == DoubletsErrorKind_NotExists);
+    CHECK(error.link.index == 100);
+    const char *message = Links_GetLastErrorMessage();
+    CHECK(message != NULL && strstr(message, "100") != NULL);
+
+    uint64_t restriction[] = {c};
+    CHECK(UInt64Links_Delete(links, restriction, 1, changed) == constants.continue_);
+    CHECK(UInt64Links_GetLastError().kind == DoubletsErrorKind_None);
+    CHECK(UInt64Links_Count(links, NULL, 0) == 2);
+
+    UInt64Links_Drop(links);
+    return 0;
+}
@@ -0,0 +1,66 @@
+// Tests for the generated `doublets.h` by a C program linked with the static library
+
+#![cfg(unix)]
+
+use std::{
+    env,
+    io::{BufRead, BufReader},
+    path::PathBuf,
+    process::{Command, Stdio},
+};
+
+use serde_json::Value;
+
+// the static library is located by cargo, so it is found in any target directory
+fn static_library() -> PathBuf {
+    let mut cargo = Command::new(env!("CARGO"))
+        .args(["build", "--package", "doublets-ffi"])
+        .arg("--message-format=json-render-diagnostics")
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let stdout = cargo.stdout.take().unwrap();
+    // all messages are read, so cargo is not stopped by a closed pipe
+    let files: Vec<_> = BufReader::new(stdout)
+        .lines()
+        .map(|line| serde_json::from_str::<Value>(&line.unwrap()).unwrap())
+        .filter(|message| message["reason"] == "compiler-artifact")
+        .filter(|message| message["target"]["name"] == "doublets_ffi")
+        .flat_map(|message| message["filenames"].as_array().cloned().unwrap_or_default())
+        .filter_map(|file| file.as_str().map(PathBuf::from))
+        .collect();
+    assert!(
+        cargo.wait().unwrap().success(),
+        "unable to build the library"
+    );
+    files
+        .into_iter()
+        .find(|file| file.extension().map_or(false, |ext| ext == "a"))
+        .expect("the static library is not built")
+}
+
+#[test]
+fn c_program() {
+    let tmp = PathBuf::from(env!("CARGO_TARGET_TMPDIR"));
+    let program = tmp.join("doublets-c-links");
+    let links = tmp.join(format!("doublets-c-links-{}.links", std::process::id()));
+
+    let status = Command::new(env::var("CC").unwrap_or_else(|_| "cc".to_string()))
+        .arg("-std=c99")
+        .arg("-Wall")
+        .arg("-Werror")
+        .arg(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/c/links.c"))
+        .arg("-I")
+        .arg(env!("OUT_DIR"))
+        .arg(static_library())
+        .args(["-lpthread", "-ldl", "-lm"])
+        .arg("-o")
+        .arg(&program)
+        .status()
+        .unwrap();
+    assert!(status.success(), "unable to compile the C program");
+
+    let status = Command::new(&program).arg(&links).status().unwrap();
+    let _ = std::fs::remove_file(&links);
+    assert!(status.success(), "the C program failed");
+}
@@ -0,0 +1,199 @@
+use std::vec;
+
+use doublets::{
+    mem::{FileMapped, Global},
+    split, unit, Doublets, DoubletsExt, Error, Link,
+};
+use pyo3::{
+    create_exception,
+    exceptions::{PyException, PyValueError},
+    prelude::*,
+};
+
+type T = u64;
+
+create_exception!(doublets, DoubletsError, PyException);
+create_exception!(doublets, NotExistsError, DoubletsError);
+create_exception!(doublets, HasUsagesError, DoubletsError);
+create_exception!(doublets, AlreadyExistsError, DoubletsError);
+create_exception!(doublets, LimitReachedError, DoubletsError);
+create_exception!(doublets, AllocFailedError, DoubletsError);
+create_exception!(doublets, ReadOnlyError, DoubletsError);
+
+// exception arguments are the message and the offending links
+fn into_py_err(err: Error<T>) -> PyErr {
+    let message = err.to_string();
+    match err {
+        Error::NotExists(index) => NotExistsError::new_err((message, index)),
+        Error::HasUsages(usages) => {
+            let usages: Vec<_> = usages.into_iter().map(into_tuple).collect();
+            HasUsagesError::new_err((message, usages))
+        }
+        Error::AlreadyExists(doublet) => {
+            AlreadyExistsError::new_err((message, (doublet.source, doublet.target)))
+        }
+        Error::LimitReached(limit) => LimitReachedError::new_err((message, limit)),
+        Error::AllocFailed(_) => AllocFailedError::new_err(message),
+        Error::ReadOnly => ReadOnlyError::new_err(message),
+        Error::Other(_) => DoubletsError::new_err(message),
+    }
+}
+
+fn into_tuple(link: Link<T>) -> (T, T, T) {
+    (link.index, link.source, link.target)
+}
+
+/// Links store with `(index, source, target)` links.
+///
+/// Use `Links.unit` or `Links.split` to create it, `None` in queries matches any part.
+#[pyclass(unsendable)]
+struct Links {
+    links: Box<dyn Doublets<T>>,
+}
+
+#[pymethods]
+impl Links {
+    /// Creates `unit::Store` in the file at `path` or in memory if it is `None`.
+    #[staticmethod]
+    fn unit(path: Option<&str>) -> PyResult<Self> {
+        let links: Box<dyn Doublets<T>> = match path {
+            Some(path) => {
+                let mem = FileMapped::from_path(path).map_err(|err| into_py_err(err.into()))?;
+                Box::new(unit::Store::<T, _>::new(mem).map_err(into_py_err)?)
+            }
+            None => Box::new(unit::Store::<T, _>::new(Global::new()).map_err(into_py_err)?),
+        };
+        Ok(Self { links })
+    }
+
+    /// Creates `split::Store` in the files at `data_path` and `index_path`
+    /// or in memory if they are `None`.
+    #[staticmethod]
+    fn split(data_path: Option<&str>, index_path: Option<&str>) -> PyResult<Self> {
+        let links: Box<dyn Doublets<T>> = match (data_path, index_path) {
+            (Some(data_path), Some(index_path)) => {
+                let data =
+                    FileMapped::from_path(data_path).map_err(|err| into_py_err(err.into()))?;
+                let index =
+                    FileMapped::from_path(index_path).map_err(|err| into_py_err(err.into()))?;
+                Box::new(split::Store::<T, _, _>::new(data, index).map_err(into_py_err)?)
+            }
+            (None, None) => Box::new(
+                split::Store::<T, _, _>::new(Global::new(), Global::new()).map_err(into_py_err)?,
+            ),
+            _ => {
+                return Err(PyValueError::new_err(
+                    "both `data_path` and `index_path` must be set or be `None`",
+                ));
+            }
+        };
+        Ok(Self { links })
+    }
+
+    fn create(&mut self) -> PyResult<T> {
+        self.links.create().map_err(into_py_err)
+    }
+
+    fn create_point(&mut self) -> PyResult<T> {
+        self.links.create_point().map_err(into_py_err)
+    }
+
+    fn create_link(&mut self, source: T, target: T) -> PyResult<T> {
+        self.links.create_link(source, target).map_err(into_py_err)
+    }
+
+    fn get_or_create(&mut self, source: T, target: T) -> PyResult<T> {
+        self.links
+            .get_or_create(source, target)
+            .map_err(into_py_err)
+    }
+
+    fn update(&mut self, index: T, source: T, target: T) -> PyResult<T> {
+        self.links
+            .update(index, source, target)
+            .map_err(into_py_err)
+    }
+
+    fn delete(&mut self, index: T) -> PyResult<T> {
+        self.links.delete(index).map_err(into_py_err)
+    }
+
+    /// Returns the index of the link with `source` and `target` or `None`.
+    fn search(&self, source: T, target: T) -> Option<T> {
+        self.links.search(source, target)
+    }
+
+    /// Returns the link as `(index, source, target)` or `None`.
+    fn get(&self, index: T) -> Option<(T, T, T)> {
+        self.links.get_link(index).map(into_tuple)
+    }
+
+    fn count(&self, index: Option<T>, source: Option<T>, target: Option<T>) -> T {
+        self.links.count_by(self.query(index, source, target))
+    }
+
+    /// Returns an iterator over matching links as `(index, source, target)`.
+    ///
+    /// Links are collected when it is called, so the iterator is not affected
+    /// by later changes of the store.
+    fn each(&self, index: Option<T>, source: Option<T>, target: Option<T>) -> LinksIter {
+        let query = self.query(index, source, target);
+        LinksIter {
+            iter: self.links.each_iter(query),
+        }
+    }
+
+    fn __len__(&self) -> usize {
+        self.links.count() as usize
+    }
+
+    fn __contains__(&self, index: T) -> bool {
+        self.links.exist(index)
+    }
+
+    fn __iter__(&self) -> LinksIter {
+        self.each(None, None, None)
+    }
+}
+
+impl Links {
+    fn query(&self, index: Option<T>, source: Option<T>, target: Option<T>) -> [T; 3] {
+        let any = self.links.constants().any;
+        [
+            index.unwrap_or(any),
+            source.unwrap_or(any),
+            target.unwrap_or(any),
+        ]
+    }
+}
+
+#[pyclass]
+struct LinksIter {
+    iter: vec::IntoIter<Link<T>>,
+}
+
+#[pymethods]
+impl LinksIter {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__(&mut self) -> Option<(T, T, T)> {
+        self.iter.next().map(into_tuple)
+    }
+}
+
+#[pymodule]
+#[pyo3(name = "doublets")]
+fn doublets_py(py: Python<'_>, module: &PyModule) -> PyResult<()> {
+    module.add_class::<Links>()?;
+    module.add_class::<LinksIter>()?;
+    module.add("DoubletsError", py.get_type::<DoubletsError>())?;
+    module.add("NotExistsError", py.get_type::<NotExistsError>())?;
+    module.add("HasUsagesError", py.get_type::<HasUsagesError>())?;
+    module.add("AlreadyExistsError", py.get_type::<AlreadyExistsError>())?;
+    module.add("LimitReachedError", py.get_type::<LimitReachedError>())?;
+    module.add("AllocFailedError", py.get_type::<AllocFailedError>())?;
+    module.add("ReadOnlyError", py.get_type::<ReadOnlyError>())?;
+    Ok(())
+}
@@ -0,0 +1,76 @@
+# Tests for the Python bindings, run `maturin develop && pytest` in `doublets-py`
+
+import pytest
+
+import doublets
+from doublets import Links
+
+
+@pytest.fixture(params=["unit", "split", "unit_file", "split_file"])
+def links(request, tmp_path):
+    if request.param == "unit":
+        return Links.unit()
+    if request.param == "split":
+        return Links.split()
+    if request.param == "unit_file":
+        return Links.unit(str(tmp_path / "db.links"))
+    return Links.split(str(tmp_path / "data.links"), str(tmp_path / "index.links"))
+
+
+def test_create(links):
+    point = links.create_point()
+    link = links.create_link(point, point)
+    assert links.get(point) == (point, point, point)
+    assert links.get(link) == (link, point, point)
+    assert len(links) == 2
+    assert link in links
+    assert 100 not in links
+
+
+def test_update_and_delete(links):
+    a = links.create_point()
+    b = links.create_point()
+    link = links.create()
+    assert links.update(link, a, b) == link
+    assert links.search(a, b) == link
+    assert links.get_or_create(a, b) == link
+
+    links.delete(link)
+    assert links.search(a, b) is None
+    assert links.get(link) is None
+    assert len(links) == 2
+
+
+def test_count_and_each(links):
+    a = links.create_point()
+    b = links.create_point()
+    ab = links.create_link(a, b)
+    ba = links.create_link(b, a)
+
+    assert links.count() == 4
+    assert links.count(source=a) == 2
+    assert links.count(source=a, target=b) == 1
+    assert sorted(links.each(target=a)) == [(a, a, a), (ba, b, a)]
+    assert [link for link, _, _ in links] == [a, b, ab, ba]
+
+
+def test_each_is_snapshot(links):
+    a = links.create_point()
+    iterator = links.each()
+    links.create_point()
+    assert list(iterator) == [(a, a, a)]
+
+
+def test_errors(links):
+    with pytest.raises(doublets.NotExistsError) as error:
+        links.update(100, 0, 0)
+    assert error.value.args[1] == 100
+
+    with pytest.raises(doublets.DoubletsError):
+        links.delete(100)
+    assert links.count() == 0
+
+
+def test_split_paths():
+    with pytest.raises(ValueError):
+        Links.split("data.links")
@@ -0,0 +1,122 @@
+use doublets::{mem::Global, split, unit, Doublets, DoubletsExt, Error, Link};
+use js_sys::Array;
+use wasm_bindgen::prelude::*;
+
+type T = u32;
+
+// errors are thrown as `Error` with the variant name, e.g. `NotExistsError`
+fn into_js_err(err: Error<T>) -> JsValue {
+    let name = match err {
+        Error::NotExists(_) => "NotExistsError",
+        Error::HasUsages(_) => "HasUsagesError",
+        Error::AlreadyExists(_) => "AlreadyExistsError",
+        Error::LimitReached(_) => "LimitReachedError",
+        Error::AllocFailed(_) => "AllocFailedError",
+        Error::ReadOnly => "ReadOnlyError",
+        Error::Other(_) => "DoubletsError",
+    };
+    let error = js_sys::Error::new(&err.to_string());
+    error.set_name(name);
+    error.into()
+}
+
+fn into_array(link: Link<T>) -> Array {
+    Array::of3(&link.index.into(), &link.source.into(), &link.target.into())
+}
+
+/// In-memory links store, links are `[index, source, target]` arrays.
+///
+/// `undefined` in queries matches any part.
+#[wasm_bindgen]
+pub struct Links {
+    links: Box<dyn Doublets<T>>,
+}
+
+#[wasm_bindgen]
+impl Links {
+    /// Creates `unit::Store`.
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Result<Links, JsValue> {
+        let links = unit::Store::<T, _>::new(Global::new()).map_err(into_js_err)?;
+        Ok(Self {
+            links: Box::new(links),
+        })
+    }
+
+    /// Creates `split::Store`.
+    pub fn split() -> Result<Links, JsValue> {
+        let links =
+            split::Store::<T, _, _>::new(Global::new(), Global::new()).map_err(into_js_err)?;
+        Ok(Self {
+            links: Box::new(links),
+        })
+    }
+
+    pub fn create(&mut self) -> Result<T, JsValue> {
+        self.links.create().map_err(into_js_err)
+    }
+
+    #[wasm_bindgen(js_name = createPoint)]
+    pub fn create_point(&mut self) -> Result<T, JsValue> {
+        self.links.create_point().map_err(into_js_err)
+    }
+
+    #[wasm_bindgen(js_name = createLink)]
+    pub fn create_link(&mut self, source: T, target: T) -> Result<T, JsValue> {
+        self.links.create_link(source, target).map_err(into_js_err)
+    }
+
+    #[wasm_bindgen(js_name = getOrCreate)]
+    pub fn get_or_create(&mut self, source: T, target: T) -> Result<T, JsValue> {
+        self.links
+            .get_or_create(source, target)
+            .map_err(into_js_err)
+    }
+
+    pub fn update(&mut self, index: T, source: T, target: T) -> Result<T, JsValue> {
+        self.links
+            .update(index, source, target)
+            .map_err(into_js_err)
+    }
+
+    pub fn delete(&mut self, index: T) -> Result<T, JsValue> {
+        self.links.delete(index).map_err(into_js_err)
+    }
+
+    /// Returns the index of the link with `source` and `target` or `undefined`.
+    pub fn search(&self, source: T, target: T) -> Option<T> {
+        self.links.search(source, target)
+    }
+
+    /// Returns the link or `undefined`.
+    pub fn get(&self, index: T) -> Option<Array> {
+        self.links.get_link(index).map(into_array)
+    }
+
+    pub fn exists(&self, index: T) -> bool {
+        self.links.exist(index)
+    }
+
+    pub fn count(&self, index: Option<T>, source: Option<T>, target: Option<T>) -> T {
+        self.links.count_by(self.query(index, source, target))
+    }
+
+    /// Returns all matching links.
+    pub fn each(&self, index: Option<T>, source: Option<T>, target: Option<T>) -> Array {
+        self.links
+            .each_iter(self.query(index, source, target))
+            .map(into_array)
+            .collect()
+    }
+}
+
+impl Links {
+    fn query(&self, index: Option<T>, source: Option<T>, target: Option<T>) -> [T; 3] {
+        let any = self.links.constants().any;
+        [
+            index.unwrap_or(any),
+            source.unwrap_or(any),
+            target.unwrap_or(any),
+        ]
+    }
+}
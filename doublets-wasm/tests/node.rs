@@ -0,0 +1,52 @@
+// Tests for the JS API, run by `wasm-pack test --node`
+
+use doublets_wasm::Links;
+use js_sys::Array;
+use wasm_bindgen::JsValue;
+use wasm_bindgen_test::wasm_bindgen_test;
+
+fn link(index: u32, source: u32, target: u32) -> Vec<JsValue> {
+    vec![index.into(), source.into(), target.into()]
+}
+
+fn links_impl(mut links: Links) {
+    let a = links.create_point().unwrap();
+    let b = links.create_point().unwrap();
+    let ab = links.create_link(a, b).unwrap();
+    assert_eq!(links.search(a, b), Some(ab));
+    assert_eq!(links.get_or_create(a, b).unwrap(), ab);
+    assert_eq!(links.get(ab).unwrap().to_vec(), link(ab, a, b));
+
+    assert_eq!(links.count(None, None, None), 3);
+    assert_eq!(links.count(None, Some(a), None), 2);
+    let mut each: Vec<_> = links
+        .each(None, None, Some(b))
+        .iter()
+        .map(|link| Array::from(&link).to_vec())
+        .collect();
+    each.sort_by_key(|link| link[0].as_f64().map(|index| index as u32));
+    assert_eq!(each, vec![link(b, b, b), link(ab, a, b)]);
+
+    assert_eq!(links.update(ab, b, a).unwrap(), ab);
+    assert_eq!(links.search(a, b), None);
+    links.delete(ab).unwrap();
+    assert!(!links.exists(ab));
+    assert!(links.get(ab).is_none());
+}
+
+#[wasm_bindgen_test]
+fn unit_links() {
+    links_impl(Links::new().unwrap());
+}
+
+#[wasm_bindgen_test]
+fn split_links() {
+    links_impl(Links::split().unwrap());
+}
+
+#[wasm_bindgen_test]
+fn not_exists() {
+    let mut links = Links::new().unwrap();
+    let error = js_sys::Error::from(links.update(100, 0, 0).unwrap_err());
+    assert_eq!(error.name(), "NotExistsError");
+}
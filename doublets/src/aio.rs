@@ -0,0 +1,201 @@
+//! Async facade for stores.
+//!
+//! [`AsyncLinks`] moves a store to a dedicated worker thread, so blocking scans
+//! and page faults of `FileMapped` storages do not stall the async executor.
+
+use std::{
+    any::Any,
+    future::Future,
+    marker::PhantomData,
+    panic::{self, AssertUnwindSafe},
+    pin::Pin,
+    task::{Context, Poll},
+    thread,
+};
+
+use futures_core::Stream;
+use tokio::sync::{mpsc, oneshot};
+
+use crate::{Doublets, Error, Link};
+use data::{Flow, LinkType, ToQuery};
+
+type Job<S> = Box<dyn FnOnce(&mut S) + Send>;
+
+/// Runs operations of the store `S` on its worker thread.
+///
+/// Operations are executed one by one in the order they are called.
+/// An operation is executed even if its future is dropped before completion.
+pub struct AsyncLinks<T: LinkType, S: Doublets<T>> {
+    jobs: mpsc::UnboundedSender<Job<S>>,
+    stopped: oneshot::Receiver<S>,
+    _marker: PhantomData<fn() -> T>,
+}
+
+impl<T, S> AsyncLinks<T, S>
+where
+    T: LinkType,
+    S: Doublets<T> + 'static,
+{
+    /// Moves the `store` to a new worker thread.
+    #[must_use]
+    pub fn new(store: S) -> Self {
+        let (jobs, mut receiver) = mpsc::unbounded_channel::<Job<S>>();
+        let (stop, stopped) = oneshot::channel();
+        thread::Builder::new()
+            .name("doublets-worker".to_owned())
+            .spawn(move || {
+                let mut store = store;
+                while let Some(job) = receiver.blocking_recv() {
+                    // jobs report their own panics, other panics must not stop the worker
+                    panic::catch_unwind(AssertUnwindSafe(|| job(&mut store))).ok();
+                }
+                // the store is dropped here if `AsyncLinks` was dropped
+                stop.send(store).ok();
+            })
+            .expect("failed to spawn a worker thread");
+        Self {
+            jobs,
+            stopped,
+            _marker: PhantomData,
+        }
+    }
+
+    fn send(&self, job: Job<S>) {
+        self.jobs
+            .send(job)
+            .unwrap_or_else(|_| unreachable!("worker lives until `AsyncLinks` is dropped"));
+    }
+
+    /// Runs `f` with the store on the worker thread.
+    ///
+    /// If `f` panics, the panic is resumed in the caller and the worker keeps running.
+    pub async fn run<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&mut S) -> R + Send + 'static,
+        R: Send + 'static,
+    {
+        let (sender, receiver) = oneshot::channel();
+        self.send(Box::new(move |store| {
+            sender
+                .send(panic::catch_unwind(AssertUnwindSafe(|| f(store))))
+                .ok();
+        }));
+        match receiver.await {
+            Ok(Ok(output)) => output,
+            Ok(Err(payload)) => panic::resume_unwind(payload),
+            Err(_) => unreachable!("worker runs every sent job"),
+        }
+    }
+
+    /// Stops the worker after all pending operations and returns the store.
+    pub async fn into_inner(self) -> S {
+        let Self { jobs, stopped, .. } = self;
+        drop(jobs);
+        stopped
+            .await
+            .unwrap_or_else(|_| unreachable!("worker catches panics of jobs"))
+    }
+
+    pub async fn create(&self) -> Result<T, Error<T>> {
+        self.run(S::create).await
+    }
+
+    pub async fn create_point(&self) -> Result<T, Error<T>> {
+        self.run(S::create_point).await
+    }
+
+    pub async fn create_link(&self, source: T, target: T) -> Result<T, Error<T>> {
+        self.run(move |store| store.create_link(source, target))
+            .await
+    }
+
+    pub async fn get_or_create(&self, source: T, target: T) -> Result<T, Error<T>> {
+        self.run(move |store| store.get_or_create(source, target))
+            .await
+    }
+
+    pub async fn update(&self, index: T, source: T, target: T) -> Result<T, Error<T>> {
+        self.run(move |store| store.update(index, source, target))
+            .await
+    }
+
+    pub async fn delete(&self, index: T) -> Result<T, Error<T>> {
+        self.run(move |store| store.delete(index)).await
+    }
+
+    pub async fn search(&self, source: T, target: T) -> Option<T> {
+        self.run(move |store| store.search(source, target)).await
+    }
+
+    pub async fn get_link(&self, index: T) -> Option<Link<T>> {
+        self.run(move |store| store.get_link(index)).await
+    }
+
+    pub async fn exist(&self, index: T) -> bool {
+        self.run(move |store| store.exist(index)).await
+    }
+
+    pub fn count_by(&self, query: impl ToQuery<T>) -> impl Future<Output = T> + '_ {
+        // the query is copied to not hold it across `.await`
+        let query = query.to_query()[..].to_vec();
+        self.run(move |store| store.count_links(&query))
+    }
+
+    pub async fn count(&self) -> T {
+        self.run(S::count).await
+    }
+
+    /// Streams links matching the `query`.
+    ///
+    /// The worker sends links while at most `buffer` of them are not received
+    /// and waits for the stream otherwise, so other operations are executed
+    /// only after the stream is exhausted or dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `buffer` is zero.
+    /// If the scan panics, the panic is resumed by the stream after the sent links
+    /// and the worker keeps running.
+    #[must_use]
+    pub fn each_stream(&self, query: impl ToQuery<T>, buffer: usize) -> LinksStream<T> {
+        let query = query.to_query()[..].to_vec();
+        let (sender, receiver) = mpsc::channel(buffer);
+        let (panic_sender, panicked) = oneshot::channel();
+        self.send(Box::new(move |store| {
+            let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                store.each_links(&query, &mut |link| {
+                    if sender.blocking_send(link).is_ok() {
+                        Flow::Continue
+                    } else {
+                        Flow::Break
+                    }
+                })
+            }));
+            // sent before `sender` is dropped, so the stream ends only after getting it
+            if let Err(payload) = result {
+                panic_sender.send(payload).ok();
+            }
+        }));
+        LinksStream { receiver, panicked }
+    }
+}
+
+/// Stream of links returned by [`AsyncLinks::each_stream`].
+pub struct LinksStream<T: LinkType> {
+    receiver: mpsc::Receiver<Link<T>>,
+    panicked: oneshot::Receiver<Box<dyn Any + Send>>,
+}
+
+impl<T: LinkType> Stream for LinksStream<T> {
+    type Item = Link<T>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Link<T>>> {
+        let poll = self.receiver.poll_recv(cx);
+        if let Poll::Ready(None) = poll {
+            if let Ok(payload) = self.panicked.try_recv() {
+                panic::resume_unwind(payload);
+            }
+        }
+        poll
+    }
+}
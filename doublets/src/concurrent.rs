@@ -0,0 +1,356 @@
+//! Concurrent readers with a single writer.
+//!
+//! [`SharedLinks`] keeps two copies of a store: readers query the published one
+//! while the writer changes the other, then they are swapped and the changes
+//! are replayed on the old copy before the next write.
+
+use std::{
+    marker::PhantomData,
+    mem,
+    ops::Deref,
+    sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError, RwLock},
+    thread::{self, ThreadId},
+};
+
+use crate::{Doublets, Error, Link, Links, PartRange, ReadHandler, WriteHandler};
+use data::{Flow, LinkType, LinksConstants};
+
+enum Op<T: LinkType> {
+    Create(Vec<T>),
+    Update(Vec<T>, Vec<T>),
+    Delete(Vec<T>),
+    CreateMany(T),
+    UpdateMany(Vec<Link<T>>),
+    DeleteMany(Vec<T>),
+}
+
+impl<T: LinkType> Op<T> {
+    fn apply<S: Links<T>>(
+        &self,
+        store: &mut S,
+        handler: WriteHandler<'_, T>,
+    ) -> Result<Flow, Error<T>> {
+        match self {
+            Self::Create(query) => store.create_links(query, handler),
+            Self::Update(query, change) => store.update_links(query, change, handler),
+            Self::Delete(query) => store.delete_links(query, handler),
+            Self::CreateMany(count) => store.create_many(*count, handler),
+            Self::UpdateMany(changes) => store.update_many(changes, handler),
+            Self::DeleteMany(indices) => store.delete_many(indices, handler),
+        }
+    }
+}
+
+// `stop` is the handler call that broke the operation
+struct Record<T: LinkType> {
+    op: Op<T>,
+    stop: Option<usize>,
+}
+
+impl<T: LinkType> Record<T> {
+    fn replay<S: Links<T>>(&self, store: &mut S) {
+        let mut calls = 0;
+        // both copies hold the same links, so the result is the same as the recorded one
+        self.op
+            .apply(store, &mut |_, _| {
+                calls += 1;
+                if Some(calls) == self.stop {
+                    Flow::Break
+                } else {
+                    Flow::Continue
+                }
+            })
+            .ok();
+    }
+}
+
+struct Back<T: LinkType, S> {
+    store: Arc<S>,
+    // changes of the published copy which are not applied to this one yet
+    pending: Vec<Record<T>>,
+}
+
+// threads holding versions, by the address of their copy
+#[derive(Default)]
+struct Readers {
+    held: Mutex<Vec<(usize, ThreadId)>>,
+    released: Condvar,
+}
+
+fn address<S>(store: &Arc<S>) -> usize {
+    Arc::as_ptr(store).cast::<()>() as usize
+}
+
+impl Readers {
+    fn held(&self) -> MutexGuard<'_, Vec<(usize, ThreadId)>> {
+        self.held.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Version of links returned by [`SharedLinks::read`].
+///
+/// The version is not changed while it is held and is held by the thread which read it.
+pub struct Version<'a, S> {
+    // taken on drop to release the copy before waking the writer
+    store: Option<Arc<S>>,
+    readers: &'a Readers,
+    // a version is not sent to other threads, so the writer knows which thread holds it
+    _marker: PhantomData<*const ()>,
+}
+
+impl<S> Deref for Version<'_, S> {
+    type Target = S;
+
+    fn deref(&self) -> &S {
+        self.store
+            .as_deref()
+            .unwrap_or_else(|| unreachable!("store is taken only on drop"))
+    }
+}
+
+impl<S> Drop for Version<'_, S> {
+    fn drop(&mut self) {
+        let store = self
+            .store
+            .take()
+            .unwrap_or_else(|| unreachable!("store is taken only on drop"));
+        let address = address(&store);
+        drop(store);
+
+        let mut held = self.readers.held();
+        let reader = (address, thread::current().id());
+        if let Some(position) = held.iter().position(|&held| held == reader) {
+            held.swap_remove(position);
+        }
+        self.readers.released.notify_all();
+    }
+}
+
+/// Store shared between many readers and a single writer.
+///
+/// Readers are never blocked by writes: [`read`](Self::read) returns
+/// the last published version, which is not changed while it is held.
+/// A write waits until other threads drop the version published before the previous write.
+pub struct SharedLinks<T: LinkType, S: Doublets<T>> {
+    front: RwLock<Arc<S>>,
+    back: Mutex<Back<T, S>>,
+    readers: Readers,
+}
+
+impl<T: LinkType, S: Doublets<T>> SharedLinks<T, S> {
+    /// Creates shared links from two copies of the same links, e.g. two empty stores.
+    #[must_use]
+    pub fn new(front: S, back: S) -> Self {
+        Self {
+            front: RwLock::new(Arc::new(front)),
+            back: Mutex::new(Back {
+                store: Arc::new(back),
+                pending: Vec::new(),
+            }),
+            readers: Readers::default(),
+        }
+    }
+
+    /// Returns the last published version of links.
+    #[must_use]
+    pub fn read(&self) -> Version<'_, S> {
+        // the version is registered before the writer can swap copies
+        let front = self.front.read().unwrap_or_else(PoisonError::into_inner);
+        let store = Arc::clone(&front);
+        let address = address(&store);
+        self.readers.held().push((address, thread::current().id()));
+        Version {
+            store: Some(store),
+            readers: &self.readers,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Runs `f` with the writer and publishes its changes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a previous write panicked, because its changes are lost,
+    /// or if this thread holds the version published before the previous write,
+    /// because the write would wait for it forever.
+    pub fn write<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&mut Writer<'_, T, S>) -> R,
+    {
+        let mut back = self.back.lock().expect("previous write panicked");
+        let address = address(&back.store);
+
+        let mut held = self.readers.held();
+        let this = thread::current().id();
+        while held.iter().any(|&(copy, _)| copy == address) {
+            if held.contains(&(address, this)) {
+                drop((held, back));
+                panic!("the writing thread holds the version published before the previous write");
+            }
+            held = self
+                .readers
+                .released
+                .wait(held)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+        drop(held);
+
+        let Back { store, pending } = &mut *back;
+        // versions release the copy before they are unregistered
+        let store =
+            Arc::get_mut(store).unwrap_or_else(|| unreachable!("only versions share copies"));
+        for record in pending.iter() {
+            record.replay(store);
+        }
+        pending.clear();
+
+        let output = f(&mut Writer {
+            store,
+            log: pending,
+        });
+
+        let mut front = self.front.write().unwrap_or_else(PoisonError::into_inner);
+        mem::swap(&mut *front, &mut back.store);
+        output
+    }
+}
+
+/// Changes links of [`SharedLinks::write`], recording them for the other copy.
+pub struct Writer<'a, T: LinkType, S> {
+    store: &'a mut S,
+    log: &'a mut Vec<Record<T>>,
+}
+
+impl<T: LinkType, S: Doublets<T>> Writer<'_, T, S> {
+    fn record(&mut self, op: Op<T>, handler: WriteHandler<'_, T>) -> Result<Flow, Error<T>> {
+        let (mut calls, mut stop) = (0, None);
+        let result = op.apply(self.store, &mut |before, after| {
+            calls += 1;
+            let flow = handler(before, after);
+            if let Flow::Break = flow {
+                stop = Some(calls);
+            }
+            flow
+        });
+        self.log.push(Record { op, stop });
+        result
+    }
+}
+
+impl<T: LinkType, S: Doublets<T>> Links<T> for Writer<'_, T, S> {
+    fn constants(&self) -> &LinksConstants<T> {
+        self.store.constants()
+    }
+
+    fn count_links(&self, query: &[T]) -> T {
+        self.store.count_links(query)
+    }
+
+    fn create_links(
+        &mut self,
+        query: &[T],
+        handler: WriteHandler<'_, T>,
+    ) -> Result<Flow, Error<T>> {
+        self.record(Op::Create(query.to_vec()), handler)
+    }
+
+    fn each_links(&self, query: &[T], handler: ReadHandler<'_, T>) -> Flow {
+        self.store.each_links(query, handler)
+    }
+
+    fn update_links(
+        &mut self,
+        query: &[T],
+        change: &[T],
+        handler: WriteHandler<'_, T>,
+    ) -> Result<Flow, Error<T>> {
+        self.record(Op::Update(query.to_vec(), change.to_vec()), handler)
+    }
+
+    fn delete_links(
+        &mut self,
+        query: &[T],
+        handler: WriteHandler<'_, T>,
+    ) -> Result<Flow, Error<T>> {
+        self.record(Op::Delete(query.to_vec()), handler)
+    }
+
+    fn create_many(&mut self, count: T, handler: WriteHandler<'_, T>) -> Result<Flow, Error<T>> {
+        self.record(Op::CreateMany(count), handler)
+    }
+
+    fn update_many(
+        &mut self,
+        changes: &[Link<T>],
+        handler: WriteHandler<'_, T>,
+    ) -> Result<Flow, Error<T>> {
+        self.record(Op::UpdateMany(changes.to_vec()), handler)
+    }
+
+    fn delete_many(
+        &mut self,
+        indices: &[T],
+        handler: WriteHandler<'_, T>,
+    ) -> Result<Flow, Error<T>> {
+        self.record(Op::DeleteMany(indices.to_vec()), handler)
+    }
+}
+
+impl<T: LinkType, S: Doublets<T>> Doublets<T> for Writer<'_, T, S> {
+    fn get_link(&self, index: T) -> Option<Link<T>> {
+        self.store.get_link(index)
+    }
+
+    fn nth_usage_as_source(&self, source: T, n: T) -> Option<Link<T>> {
+        self.store.nth_usage_as_source(source, n)
+    }
+
+    fn nth_usage_as_target(&self, target: T, n: T) -> Option<Link<T>> {
+        self.store.nth_usage_as_target(target, n)
+    }
+
+    fn usage_rank_as_source(&self, index: T) -> Option<T> {
+        self.store.usage_rank_as_source(index)
+    }
+
+    fn usage_rank_as_target(&self, index: T) -> Option<T> {
+        self.store.usage_rank_as_target(index)
+    }
+
+    fn each_usages_as_source_in(
+        &self,
+        source: T,
+        targets: PartRange<T>,
+        handler: ReadHandler<'_, T>,
+    ) -> Flow {
+        self.store
+            .each_usages_as_source_in(source, targets, handler)
+    }
+
+    fn each_usages_as_target_in(
+        &self,
+        target: T,
+        sources: PartRange<T>,
+        handler: ReadHandler<'_, T>,
+    ) -> Flow {
+        self.store
+            .each_usages_as_target_in(target, sources, handler)
+    }
+
+    fn each_from(&self, start: T, handler: ReadHandler<'_, T>) -> Flow {
+        self.store.each_from(start, handler)
+    }
+
+    fn count_in_ranges(&self, sources: PartRange<T>, targets: PartRange<T>) -> T {
+        self.store.count_in_ranges(sources, targets)
+    }
+
+    fn each_in_ranges(
+        &self,
+        sources: PartRange<T>,
+        targets: PartRange<T>,
+        handler: ReadHandler<'_, T>,
+    ) -> Flow {
+        self.store.each_in_ranges(sources, targets, handler)
+    }
+}
@@ -0,0 +1,265 @@
+use alloc::{string::String, vec, vec::Vec};
+use core::{
+    fmt::{self, Display, Formatter},
+    ops::Bound,
+};
+
+use crate::{Doublets, Link, PartRange, ReadHandler};
+use data::{Flow, LinkType, LinksConstants};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Scan<T> {
+    All,
+    Source(T),
+    Target(T),
+}
+
+/// Resumable position of a full or usages scan.
+///
+/// Position is stored as the last seen `(key, index)` pair rather than as an offset,
+/// so scans stay stable across inserts and deletes of unrelated links.
+/// It is displayed as an opaque URL-safe token, which [`Cursor::parse`] reads back.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Cursor<T: LinkType> {
+    scan: Scan<T>,
+    last: Option<(T, T)>,
+}
+
+impl<T: LinkType> Cursor<T> {
+    /// Scan of all links ordered by index.
+    #[must_use]
+    pub const fn all() -> Self {
+        Self {
+            scan: Scan::All,
+            last: None,
+        }
+    }
+
+    /// Scan of links with the given `source` ordered by target and index.
+    #[must_use]
+    pub const fn usages_as_source(source: T) -> Self {
+        Self {
+            scan: Scan::Source(source),
+            last: None,
+        }
+    }
+
+    /// Scan of links with the given `target` ordered by source and index.
+    #[must_use]
+    pub const fn usages_as_target(target: T) -> Self {
+        Self {
+            scan: Scan::Target(target),
+            last: None,
+        }
+    }
+
+    /// Creates a cursor for the `each_by` query if it is a full or usages scan.
+    pub fn from_query(query: &[T], constants: &LinksConstants<T>) -> Option<Self> {
+        let any = constants.any;
+        match *query {
+            [] | [_] | [_, _] if query.iter().all(|&part| part == any) => Some(Self::all()),
+            [index, source, target] if index == any => {
+                if (source, target) == (any, any) {
+                    Some(Self::all())
+                } else if target == any {
+                    Some(Self::usages_as_source(source))
+                } else if source == any {
+                    Some(Self::usages_as_target(target))
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        }
+    }
+
+    /// Parses a token produced by the `Display` implementation.
+    #[must_use]
+    pub fn parse(token: &str) -> Option<Self> {
+        Self::from_bytes(&decode(token)?)
+    }
+
+    // a tag byte followed by the scanned value and the last pair as little-endian `u64`s
+    fn to_bytes(&self) -> Vec<u8> {
+        let (tag, value) = match self.scan {
+            Scan::All => (ALL, None),
+            Scan::Source(source) => (SOURCE, Some(source)),
+            Scan::Target(target) => (TARGET, Some(target)),
+        };
+        let last = self.last.map(|(key, index)| [key, index]);
+        let mut bytes = vec![if last.is_some() { tag | LAST } else { tag }];
+        for part in value.into_iter().chain(last.into_iter().flatten()) {
+            let part = u64::try_from(part.as_usize()).expect("always ok");
+            bytes.extend_from_slice(&part.to_le_bytes());
+        }
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Option<Self> {
+        let (&tag, rest) = bytes.split_first()?;
+        if rest.len() % 8 != 0 {
+            return None;
+        }
+        let mut parts = rest.chunks_exact(8).map(|chunk| {
+            let part = u64::from_le_bytes(chunk.try_into().ok()?);
+            T::try_from(usize::try_from(part).ok()?).ok()
+        });
+        let scan = match tag & !LAST {
+            ALL => Scan::All,
+            SOURCE => Scan::Source(parts.next()??),
+            TARGET => Scan::Target(parts.next()??),
+            _ => return None,
+        };
+        let last = if tag & LAST == 0 {
+            None
+        } else {
+            Some((parts.next()??, parts.next()??))
+        };
+        parts.next().is_none().then_some(Self { scan, last })
+    }
+
+    fn next(&self, last: Option<(T, T)>) -> Option<Self> {
+        last.map(|last| Self {
+            scan: self.scan,
+            last: Some(last),
+        })
+    }
+}
+
+impl<T: LinkType> Display for Cursor<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(&encode(&self.to_bytes()))
+    }
+}
+
+const ALL: u8 = 0;
+const SOURCE: u8 = 1;
+const TARGET: u8 = 2;
+const LAST: u8 = 4;
+
+// tokens are URL-safe base64 without padding, so they are opaque to clients
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+fn encode(bytes: &[u8]) -> String {
+    let mut token = String::with_capacity((bytes.len() * 4 + 2) / 3);
+    for chunk in bytes.chunks(3) {
+        let bits = chunk.iter().enumerate().fold(0, |bits, (i, &byte)| {
+            bits | (usize::from(byte) << (16 - 8 * i))
+        });
+        for i in 0..=chunk.len() {
+            token.push(char::from(ALPHABET[(bits >> (18 - 6 * i)) & 63]));
+        }
+    }
+    token
+}
+
+fn decode(token: &str) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(token.len() * 3 / 4);
+    for chunk in token.as_bytes().chunks(4) {
+        if chunk.len() == 1 {
+            return None;
+        }
+        let mut bits = 0;
+        for (i, symbol) in chunk.iter().enumerate() {
+            let value = ALPHABET.iter().position(|c| c == symbol)?;
+            bits |= value << (18 - 6 * i);
+        }
+        for i in 0..chunk.len() - 1 {
+            bytes.push(u8::try_from((bits >> (16 - 8 * i)) & 0xff).expect("always ok"));
+        }
+    }
+    Some(bytes)
+}
+
+// visits usages after the `after` pair in `(key, index)` order by a single walk from its key
+fn each_after<T: LinkType>(
+    each: impl FnOnce(PartRange<T>, ReadHandler<'_, T>) -> Flow,
+    key: fn(&Link<T>) -> T,
+    after: Option<(T, T)>,
+    last: &mut Option<(T, T)>,
+    handler: ReadHandler<'_, T>,
+) -> Flow {
+    let start = after.map_or(Bound::Unbounded, |(key, _)| Bound::Included(key));
+    each((start, Bound::Unbounded), &mut |link| {
+        let position = (key(&link), link.index);
+        if after.map_or(false, |after| position <= after) {
+            return Flow::Continue;
+        }
+        *last = Some(position);
+        handler(link)
+    })
+}
+
+pub(crate) fn each_by_cursor<T, L>(
+    links: &L,
+    cursor: &Cursor<T>,
+    handler: ReadHandler<'_, T>,
+) -> Option<Cursor<T>>
+where
+    T: LinkType,
+    L: Doublets<T> + ?Sized,
+{
+    let mut last = None;
+
+    let flow = match cursor.scan {
+        Scan::All => {
+            let start = cursor
+                .last
+                .map_or(T::funty(1), |(_, index)| index + T::funty(1));
+            links.each_from(start, &mut |link| {
+                last = Some((link.index, link.index));
+                handler(link)
+            })
+        }
+        Scan::Source(source) => each_after(
+            |targets, handler| links.each_usages_as_source_in(source, targets, handler),
+            |link| link.target,
+            cursor.last,
+            &mut last,
+            handler,
+        ),
+        Scan::Target(target) => each_after(
+            |sources, handler| links.each_usages_as_target_in(target, sources, handler),
+            |link| link.source,
+            cursor.last,
+            &mut last,
+            handler,
+        ),
+    };
+
+    match flow {
+        Flow::Continue => None,
+        Flow::Break => cursor.next(last),
+    }
+}
+
+pub(crate) fn page_by_cursor<T, L>(
+    links: &L,
+    cursor: &Cursor<T>,
+    limit: usize,
+) -> (Vec<Link<T>>, Option<Cursor<T>>)
+where
+    T: LinkType,
+    L: Doublets<T> + ?Sized,
+{
+    // `limit` may be as large as `usize::MAX` for no limit, so the page grows with its links
+    let mut page = Vec::new();
+    if limit == 0 {
+        // the cursor stays in place while any link is left after it
+        let mut rest = false;
+        links.each_by_cursor(cursor, &mut |_| {
+            rest = true;
+            Flow::Break
+        });
+        return (page, rest.then(|| cursor.clone()));
+    }
+    let next = links.each_by_cursor(cursor, &mut |link| {
+        page.push(link);
+        if page.len() < limit {
+            Flow::Continue
+        } else {
+            Flow::Break
+        }
+    });
+    (page, next)
+}
@@ -1,8 +1,9 @@
-use std::fmt::{Debug, Display, Formatter};
+use core::fmt::{Debug, Display, Formatter};
 
 use data::LinkType;
 
 #[derive(Debug, Eq, PartialEq, Hash, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Doublet<T: LinkType> {
     pub source: T,
     pub target: T,
@@ -15,7 +16,7 @@ impl<T: LinkType> Doublet<T> {
 }
 
 impl<T: LinkType> Display for Doublet<T> {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
         write!(f, "{}->{}", self.source, self.target)
     }
 }
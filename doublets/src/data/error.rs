@@ -1,30 +1,86 @@
 use crate::{Doublet, Link};
+use alloc::{boxed::Box, vec::Vec};
+use core::fmt::{self, Debug, Display, Formatter};
 use data::LinkType;
-use std::{error::Error as StdError, io};
 
-#[derive(thiserror::Error, Debug)]
+/// Boxed error of other sources.
+#[cfg(feature = "std")]
+pub type OtherError = Box<dyn std::error::Error + Sync + Send>;
+
+/// Boxed error of other sources.
+#[cfg(not(feature = "std"))]
+pub type OtherError = Box<dyn OtherDisplay>;
+
+/// `Debug + Display` error without `std::error::Error`.
+#[cfg(not(feature = "std"))]
+pub trait OtherDisplay: Debug + Display + Sync + Send {}
+
+#[cfg(not(feature = "std"))]
+impl<E: Debug + Display + Sync + Send> OtherDisplay for E {}
+
+#[derive(Debug)]
 pub enum Error<T: LinkType> {
-    #[error("link {0} does not exist.")]
     NotExists(T),
 
-    #[error("link {0:?} has dependencies")]
     HasUsages(Vec<Link<T>>),
 
-    #[error("link {0} already exists")]
     AlreadyExists(Doublet<T>),
 
-    #[error("limit for the number of links in the storage has been reached: {0}")]
     LimitReached(T),
 
-    #[error("unable to allocate memory for links storage: `{0}`")]
-    AllocFailed(#[from] mem::Error),
+    AllocFailed(mem::Error),
+
+    /// Write to a store opened with `read_only`.
+    ReadOnly,
 
-    #[error("other internal error: `{0}`")]
-    Other(#[from] Box<dyn StdError + Sync + Send>),
+    Other(OtherError),
+}
+
+impl<T: LinkType> Display for Error<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotExists(index) => write!(f, "link {index} does not exist."),
+            Self::HasUsages(usages) => write!(f, "link {usages:?} has dependencies"),
+            Self::AlreadyExists(doublet) => write!(f, "link {doublet} already exists"),
+            Self::LimitReached(limit) => write!(
+                f,
+                "limit for the number of links in the storage has been reached: {limit}"
+            ),
+            Self::AllocFailed(err) => {
+                write!(f, "unable to allocate memory for links storage: `{err}`")
+            }
+            Self::ReadOnly => write!(f, "unable to change links of a read-only storage"),
+            Self::Other(err) => write!(f, "other internal error: `{err}`"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: LinkType> std::error::Error for Error<T> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::AllocFailed(err) => Some(err),
+            Self::Other(err) => Some(&**err),
+            _ => None,
+        }
+    }
+}
+
+impl<T: LinkType> From<mem::Error> for Error<T> {
+    fn from(err: mem::Error) -> Self {
+        Self::AllocFailed(err)
+    }
+}
+
+impl<T: LinkType> From<OtherError> for Error<T> {
+    fn from(err: OtherError) -> Self {
+        Self::Other(err)
+    }
 }
 
-impl<T: LinkType> From<io::Error> for Error<T> {
-    fn from(err: io::Error) -> Self {
+#[cfg(feature = "std")]
+impl<T: LinkType> From<std::io::Error> for Error<T> {
+    fn from(err: std::io::Error) -> Self {
         Self::AllocFailed(err.into())
     }
 }
@@ -1,11 +1,124 @@
 use crate::Link;
+use core::{convert::Infallible, marker::PhantomData, ops::ControlFlow};
 use data::{Flow, LinkType};
-use std::{marker::PhantomData, mem::MaybeUninit, ops::Try};
+
+/// Return value of handlers: continues the traversal or breaks it with a residual.
+///
+/// It is a stable replacement of `Try<Output = ()>`
+/// implemented for `Flow`, `()`, `ControlFlow<B>`, `Result<(), E>` and `Option<()>`.
+pub trait Branch: Sized {
+    type Residual;
+
+    fn from_output() -> Self;
+
+    fn from_residual(residual: Self::Residual) -> Self;
+
+    fn branch(self) -> ControlFlow<Self::Residual>;
+
+    fn flow(self) -> Flow {
+        if self.branch().is_break() {
+            Flow::Break
+        } else {
+            Flow::Continue
+        }
+    }
+}
+
+impl Branch for Flow {
+    type Residual = ();
+
+    fn from_output() -> Self {
+        Self::Continue
+    }
+
+    fn from_residual((): ()) -> Self {
+        Self::Break
+    }
+
+    fn branch(self) -> ControlFlow<()> {
+        match self {
+            Self::Continue => ControlFlow::Continue(()),
+            Self::Break => ControlFlow::Break(()),
+        }
+    }
+
+    fn flow(self) -> Flow {
+        self
+    }
+}
+
+impl Branch for () {
+    type Residual = Infallible;
+
+    fn from_output() -> Self {}
+
+    fn from_residual(residual: Infallible) -> Self {
+        match residual {}
+    }
+
+    fn branch(self) -> ControlFlow<Infallible> {
+        ControlFlow::Continue(())
+    }
+}
+
+impl<B> Branch for ControlFlow<B> {
+    type Residual = B;
+
+    fn from_output() -> Self {
+        Self::Continue(())
+    }
+
+    fn from_residual(residual: B) -> Self {
+        Self::Break(residual)
+    }
+
+    fn branch(self) -> Self {
+        self
+    }
+}
+
+impl<E> Branch for Result<(), E> {
+    type Residual = E;
+
+    fn from_output() -> Self {
+        Ok(())
+    }
+
+    fn from_residual(residual: E) -> Self {
+        Err(residual)
+    }
+
+    fn branch(self) -> ControlFlow<E> {
+        match self {
+            Ok(()) => ControlFlow::Continue(()),
+            Err(err) => ControlFlow::Break(err),
+        }
+    }
+}
+
+impl Branch for Option<()> {
+    type Residual = ();
+
+    fn from_output() -> Self {
+        Some(())
+    }
+
+    fn from_residual((): ()) -> Self {
+        None
+    }
+
+    fn branch(self) -> ControlFlow<()> {
+        match self {
+            Some(()) => ControlFlow::Continue(()),
+            None => ControlFlow::Break(()),
+        }
+    }
+}
 
 pub trait Handler<T, R>: FnMut(Link<T>, Link<T>) -> R
 where
     T: LinkType,
-    R: Try<Output = ()>,
+    R: Branch,
 {
     fn fuse(self) -> Fuse<T, Self, R>
     where
@@ -18,7 +131,7 @@ where
 impl<T, R, All> Handler<T, R> for All
 where
     T: LinkType,
-    R: Try<Output = ()>,
+    R: Branch,
     All: FnMut(Link<T>, Link<T>) -> R,
 {
 }
@@ -27,7 +140,7 @@ pub struct Fuse<T, H, R>
 where
     T: LinkType,
     H: Handler<T, R>,
-    R: Try<Output = ()>,
+    R: Branch,
 {
     handler: H,
     done: bool,
@@ -38,7 +151,7 @@ impl<T, F, R> Fuse<T, F, R>
 where
     T: LinkType,
     F: FnMut(Link<T>, Link<T>) -> R,
-    R: Try<Output = ()>,
+    R: Branch,
 {
     pub fn new(handler: F) -> Self {
         Self {
@@ -47,49 +160,61 @@ where
             _marker: PhantomData,
         }
     }
+
+    /// Calls the handler once and converts its result to `Flow`.
+    pub fn handle_once(mut self, before: Link<T>, after: Link<T>) -> Flow {
+        (self.handler)(before, after).flow()
+    }
+
+    /// Calls the handler while it continues, then only returns `Flow::Break`.
+    pub fn handle(&mut self, before: Link<T>, after: Link<T>) -> Flow {
+        if self.done {
+            Flow::Break
+        } else {
+            let result = (self.handler)(before, after);
+            if result.branch().is_break() {
+                self.done = true;
+                Flow::Break
+            } else {
+                Flow::Continue
+            }
+        }
+    }
 }
 
 impl<T, H, R> From<H> for Fuse<T, H, R>
 where
     T: LinkType,
     H: Handler<T, R>,
-    R: Try<Output = ()>,
+    R: Branch,
 {
     fn from(handler: H) -> Self {
         Self::new(handler)
     }
 }
 
+#[cfg(feature = "nightly")]
 impl<T, H, R> FnOnce<(Link<T>, Link<T>)> for Fuse<T, H, R>
 where
     H: FnMut(Link<T>, Link<T>) -> R,
-    R: Try<Output = ()>,
+    R: Branch,
     T: LinkType,
 {
     type Output = Flow;
 
-    extern "rust-call" fn call_once(self, args: (Link<T>, Link<T>)) -> Self::Output {
-        self.handler.call_once(args).branch().into()
+    extern "rust-call" fn call_once(self, (before, after): (Link<T>, Link<T>)) -> Flow {
+        self.handle_once(before, after)
     }
 }
 
+#[cfg(feature = "nightly")]
 impl<T, H, R> FnMut<(Link<T>, Link<T>)> for Fuse<T, H, R>
 where
     T: LinkType,
     H: Handler<T, R>,
-    R: Try<Output = ()>,
+    R: Branch,
 {
-    extern "rust-call" fn call_mut(&mut self, args: (Link<T>, Link<T>)) -> Self::Output {
-        if self.done {
-            Flow::Break
-        } else {
-            let result = self.handler.call_mut(args);
-            if result.branch().is_break() {
-                self.done = false;
-                Flow::Break
-            } else {
-                Flow::Continue
-            }
-        }
+    extern "rust-call" fn call_mut(&mut self, (before, after): (Link<T>, Link<T>)) -> Flow {
+        self.handle(before, after)
     }
 }
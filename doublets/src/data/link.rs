@@ -1,8 +1,9 @@
-use std::fmt::{self, Debug, Formatter};
+use core::fmt::{self, Debug, Formatter};
 
 use data::{LinkType, Query, ToQuery};
 
 #[derive(Default, Eq, PartialEq, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C)]
 pub struct Link<T: LinkType> {
     pub index: T,
@@ -46,7 +47,7 @@ impl<T: LinkType> Link<T> {
     pub(crate) const unsafe fn from_slice_unchecked(slice: &[T]) -> Self {
         match slice {
             [index, source, target] => Self::new(*index, *source, *target),
-            _ => std::hint::unreachable_unchecked(),
+            _ => core::hint::unreachable_unchecked(),
         }
     }
 
@@ -1,13 +1,22 @@
+mod cursor;
 mod doublet;
 mod error;
 mod handler;
 mod link;
+mod range;
 mod traits;
+mod usages;
 
+pub use cursor::Cursor;
+pub(crate) use cursor::{each_by_cursor, page_by_cursor};
 pub use doublet::Doublet;
-pub use error::Error;
-pub use handler::{Fuse, Handler};
+#[cfg(not(feature = "std"))]
+pub use error::OtherDisplay;
+pub use error::{Error, OtherError};
+pub use handler::{Branch, Fuse, Handler};
 pub use link::Link;
+pub use range::PartRange;
+pub(crate) use range::{is_any, matches_link, part_range};
 pub use traits::{Doublets, DoubletsExt, Links, ReadHandler, WriteHandler};
 
 #[cfg(feature = "data")]
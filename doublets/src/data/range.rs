@@ -0,0 +1,61 @@
+use core::ops::{Bound, RangeBounds};
+
+use crate::{Link, Links, ReadHandler};
+use data::{Flow, LinkType};
+
+/// Range constraint on the source or target part of a link.
+pub type PartRange<T> = (Bound<T>, Bound<T>);
+
+pub(crate) fn part_range<T: LinkType>(range: &impl RangeBounds<T>) -> PartRange<T> {
+    (range.start_bound().cloned(), range.end_bound().cloned())
+}
+
+// `..` puts no constraint on the part, so null parts are matched only by it
+pub(crate) const fn is_any<T>(range: &PartRange<T>) -> bool {
+    matches!(range, (Bound::Unbounded, Bound::Unbounded))
+}
+
+pub(crate) fn matches<T: LinkType>(range: &PartRange<T>, part: T) -> bool {
+    is_any(range) || (part != T::funty(0) && range.contains(&part))
+}
+
+pub(crate) fn matches_link<T: LinkType>(
+    sources: &PartRange<T>,
+    targets: &PartRange<T>,
+    link: &Link<T>,
+) -> bool {
+    matches(sources, link.source) && matches(targets, link.target)
+}
+
+// fallback for stores without indexes: a full scan in index order
+pub(crate) fn each_in_ranges<T, L>(
+    links: &L,
+    sources: PartRange<T>,
+    targets: PartRange<T>,
+    handler: ReadHandler<'_, T>,
+) -> Flow
+where
+    T: LinkType,
+    L: Links<T> + ?Sized,
+{
+    links.each_links(&[], &mut |link| {
+        if matches_link(&sources, &targets, &link) {
+            handler(link)
+        } else {
+            Flow::Continue
+        }
+    })
+}
+
+pub(crate) fn count_in_ranges<T, L>(links: &L, sources: PartRange<T>, targets: PartRange<T>) -> T
+where
+    T: LinkType,
+    L: Links<T> + ?Sized,
+{
+    let mut count = T::funty(0);
+    each_in_ranges(links, sources, targets, &mut |_| {
+        count += T::funty(1);
+        Flow::Continue
+    });
+    count
+}
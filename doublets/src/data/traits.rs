@@ -1,14 +1,18 @@
-use bumpalo::Bump;
+use alloc::{boxed::Box, vec, vec::Vec};
+use core::ops::{ControlFlow, RangeBounds};
 #[cfg(feature = "rayon")]
 use rayon::prelude::*;
-use std::{
-    default::default,
-    ops::{ControlFlow, Try},
-};
 
-use crate::{Error, Fuse, Link};
+use crate::{
+    data::{each_by_cursor, page_by_cursor, part_range, range, usages},
+    Branch, Cursor, Error, Fuse, Link, PartRange,
+};
 use data::{Flow, LinkType, LinksConstants, ToQuery};
 
+// fixme: later use const generics
+#[cfg(feature = "small-search")]
+const SMALL_SEARCH: usize = 2;
+
 pub type ReadHandler<'a, T> = &'a mut dyn FnMut(Link<T>) -> Flow;
 
 pub type WriteHandler<'a, T> = &'a mut dyn FnMut(Link<T>, Link<T>) -> Flow;
@@ -32,6 +36,53 @@ pub trait Links<T: LinkType>: Send + Sync {
 
     fn delete_links(&mut self, query: &[T], handler: WriteHandler<'_, T>)
     -> Result<Flow, Error<T>>;
+
+    /// Creates `count` links, calling `handler` for each of them until it breaks.
+    ///
+    /// The batch is not atomic: it stops at the first error, earlier links stay created.
+    fn create_many(&mut self, count: T, handler: WriteHandler<'_, T>) -> Result<Flow, Error<T>> {
+        for _ in 0..count.as_usize() {
+            if let Flow::Break = self.create_links(&[], handler)? {
+                return Ok(Flow::Break);
+            }
+        }
+        Ok(Flow::Continue)
+    }
+
+    /// Sets parts of every link in `changes` by its index, calling `handler`
+    /// for each of them until it breaks.
+    ///
+    /// The batch is not atomic: it stops at the first error, earlier links stay updated.
+    fn update_many(
+        &mut self,
+        changes: &[Link<T>],
+        handler: WriteHandler<'_, T>,
+    ) -> Result<Flow, Error<T>> {
+        for change in changes {
+            let (index, source, target) = (change.index, change.source, change.target);
+            if let Flow::Break = self.update_links(&[index], &[index, source, target], handler)? {
+                return Ok(Flow::Break);
+            }
+        }
+        Ok(Flow::Continue)
+    }
+
+    /// Deletes links with the given `indices` in order, calling `handler`
+    /// for each of them until it breaks.
+    ///
+    /// The batch is not atomic: it stops at the first error, earlier links stay deleted.
+    fn delete_many(
+        &mut self,
+        indices: &[T],
+        handler: WriteHandler<'_, T>,
+    ) -> Result<Flow, Error<T>> {
+        for &index in indices {
+            if let Flow::Break = self.delete_links(&[index], handler)? {
+                return Ok(Flow::Break);
+            }
+        }
+        Ok(Flow::Continue)
+    }
 }
 
 pub trait Doublets<T: LinkType>: Links<T> {
@@ -56,10 +107,10 @@ pub trait Doublets<T: LinkType>: Links<T> {
     ) -> Result<R, Error<T>>
     where
         F: FnMut(Link<T>, Link<T>) -> R,
-        R: Try<Output = ()>,
+        R: Branch,
         Self: Sized,
     {
-        let mut output = R::from_output(());
+        let mut output = R::from_output();
         let query = query.to_query();
 
         self.create_links(
@@ -79,7 +130,7 @@ pub trait Doublets<T: LinkType>: Links<T> {
     where
         Self: Sized,
     {
-        let mut index = default();
+        let mut index = T::funty(0);
         self.create_by_with(query, |_before, link| {
             index = link.index;
             Flow::Continue
@@ -90,7 +141,7 @@ pub trait Doublets<T: LinkType>: Links<T> {
     fn create_with<F, R>(&mut self, handler: F) -> Result<R, Error<T>>
     where
         F: FnMut(Link<T>, Link<T>) -> R,
-        R: Try<Output = ()>,
+        R: Branch,
         Self: Sized,
     {
         self.create_by_with([], handler)
@@ -106,10 +157,10 @@ pub trait Doublets<T: LinkType>: Links<T> {
     fn each_by<F, R>(&self, query: impl ToQuery<T>, mut handler: F) -> R
     where
         F: FnMut(Link<T>) -> R,
-        R: Try<Output = ()>,
+        R: Branch,
         Self: Sized,
     {
-        let mut output = R::from_output(());
+        let mut output = R::from_output();
         let query = query.to_query();
 
         self.each_links(&query[..], &mut |link| match handler(link).branch() {
@@ -126,7 +177,7 @@ pub trait Doublets<T: LinkType>: Links<T> {
     fn each<F, R>(&self, handler: F) -> R
     where
         F: FnMut(Link<T>) -> R,
-        R: Try<Output = ()>,
+        R: Branch,
         Self: Sized,
     {
         self.each_by([], handler)
@@ -140,10 +191,10 @@ pub trait Doublets<T: LinkType>: Links<T> {
     ) -> Result<R, Error<T>>
     where
         H: FnMut(Link<T>, Link<T>) -> R,
-        R: Try<Output = ()>,
+        R: Branch,
         Self: Sized,
     {
-        let mut output = R::from_output(());
+        let mut output = R::from_output();
         let query = query.to_query();
         let change = change.to_query();
 
@@ -165,7 +216,7 @@ pub trait Doublets<T: LinkType>: Links<T> {
     where
         Self: Sized,
     {
-        let mut result = default();
+        let mut result = T::funty(0);
         self.update_by_with(query, change, |_, after| {
             result = after.index;
             Flow::Continue
@@ -182,7 +233,7 @@ pub trait Doublets<T: LinkType>: Links<T> {
     ) -> Result<R, Error<T>>
     where
         F: FnMut(Link<T>, Link<T>) -> R,
-        R: Try<Output = ()>,
+        R: Branch,
         Self: Sized,
     {
         self.update_by_with([index], [index, source, target], handler)
@@ -202,10 +253,10 @@ pub trait Doublets<T: LinkType>: Links<T> {
     ) -> Result<R, Error<T>>
     where
         F: FnMut(Link<T>, Link<T>) -> R,
-        R: Try<Output = ()>,
+        R: Branch,
         Self: Sized,
     {
-        let mut output = R::from_output(());
+        let mut output = R::from_output();
         let query = query.to_query();
 
         self.delete_links(
@@ -225,7 +276,7 @@ pub trait Doublets<T: LinkType>: Links<T> {
     where
         Self: Sized,
     {
-        let mut result = default();
+        let mut result = T::funty(0);
         self.delete_by_with(query, |_before, after| {
             result = after.index;
             Flow::Continue
@@ -236,7 +287,7 @@ pub trait Doublets<T: LinkType>: Links<T> {
     fn delete_with<F, R>(&mut self, index: T, handler: F) -> Result<R, Error<T>>
     where
         F: FnMut(Link<T>, Link<T>) -> R,
-        R: Try<Output = ()>,
+        R: Branch,
         Self: Sized,
     {
         self.delete_by_with([index], handler)
@@ -275,7 +326,7 @@ pub trait Doublets<T: LinkType>: Links<T> {
     ) -> Result<(), Error<T>>
     where
         F: FnMut(Link<T>, Link<T>) -> R,
-        R: Try<Output = ()>,
+        R: Branch,
         Self: Sized,
     {
         let query = query.to_query();
@@ -289,7 +340,7 @@ pub trait Doublets<T: LinkType>: Links<T> {
 
         let mut handler = Fuse::new(handler);
         for index in vec.into_iter().rev() {
-            self.delete_with(index, &mut handler)?;
+            self.delete_with(index, |before, after| handler.handle(before, after))?;
         }
         Ok(())
     }
@@ -297,7 +348,7 @@ pub trait Doublets<T: LinkType>: Links<T> {
     fn delete_usages_with<F, R>(&mut self, index: T, handler: F) -> Result<(), Error<T>>
     where
         F: FnMut(Link<T>, Link<T>) -> R,
-        R: Try<Output = ()>,
+        R: Branch,
         Self: Sized,
     {
         let any = self.constants().any;
@@ -321,7 +372,7 @@ pub trait Doublets<T: LinkType>: Links<T> {
 
         let mut handler = Fuse::new(handler);
         for index in to_delete.into_iter().rev() {
-            self.delete_with(index, &mut handler)?;
+            self.delete_with(index, |before, after| handler.handle(before, after))?;
         }
         Ok(())
     }
@@ -344,25 +395,30 @@ pub trait Doublets<T: LinkType>: Links<T> {
     fn create_link_with<F, R>(&mut self, source: T, target: T, handler: F) -> Result<Flow, Error<T>>
     where
         F: FnMut(Link<T>, Link<T>) -> R,
-        R: Try<Output = ()>,
+        R: Branch,
         Self: Sized,
     {
-        let mut new = default();
+        let (mut new, mut flow) = (Link::nothing(), Flow::Continue);
         let mut handler = Fuse::new(handler);
-        self.create_with(|before, after| {
-            new = after.index;
-            handler(before, after);
+        // stores find a duplicate by the query before creating, so it may be redirected to
+        self.create_by_with([source, target], |before, after| {
+            new = after;
+            flow = handler.handle(before, after);
             Flow::Continue
         })?;
-
-        self.update_with(new, source, target, handler)
+        if (new.source, new.target) == (source, target) {
+            return Ok(flow);
+        }
+        self.update_with(new.index, source, target, |before, after| {
+            handler.handle(before, after)
+        })
     }
 
     fn create_link(&mut self, source: T, target: T) -> Result<T, Error<T>>
     where
         Self: Sized,
     {
-        let mut result = default();
+        let mut result = T::funty(0);
         self.create_link_with(source, target, |_, link| {
             result = link.index;
             Flow::Continue
@@ -500,7 +556,7 @@ pub trait Doublets<T: LinkType>: Links<T> {
     fn rebase_with<F, R>(&mut self, old: T, new: T, handler: F) -> Result<(), Error<T>>
     where
         F: FnMut(Link<T>, Link<T>) -> R,
-        R: Try<Output = ()>,
+        R: Branch,
         Self: Sized,
     {
         // guard
@@ -521,10 +577,14 @@ pub trait Doublets<T: LinkType>: Links<T> {
             .filter(|usage| usage.index != old)
             .try_for_each(|usage| {
                 if usage.source == old {
-                    self.update_with(usage.index, new, usage.target, &mut handler)?;
+                    self.update_with(usage.index, new, usage.target, |before, after| {
+                        handler.handle(before, after)
+                    })?;
                 }
                 if usage.target == old {
-                    self.update_with(usage.index, usage.source, new, &mut handler)?;
+                    self.update_with(usage.index, usage.source, new, |before, after| {
+                        handler.handle(before, after)
+                    })?;
                 }
                 Ok(())
             })
@@ -549,6 +609,181 @@ pub trait Doublets<T: LinkType>: Links<T> {
             self.delete(old)
         }
     }
+
+    /// Returns the `n`-th (zero-based) link with the given `source`,
+    /// ordered by target and then by index.
+    fn nth_usage_as_source(&self, source: T, n: T) -> Option<Link<T>> {
+        let any = self.constants().any;
+        usages::nth(self, [any, source, any], |link| link.target, n)
+    }
+
+    /// Returns the `n`-th (zero-based) link with the given `target`,
+    /// ordered by source and then by index.
+    fn nth_usage_as_target(&self, target: T, n: T) -> Option<Link<T>> {
+        let any = self.constants().any;
+        usages::nth(self, [any, any, target], |link| link.source, n)
+    }
+
+    /// Returns the position of `index` among the links with the same source,
+    /// so that `nth_usage_as_source(source, rank)` is the link itself.
+    fn usage_rank_as_source(&self, index: T) -> Option<T> {
+        let any = self.constants().any;
+        let link = self.get_link(index)?;
+        Some(usages::rank(
+            self,
+            [any, link.source, any],
+            |usage| usage.target,
+            &link,
+        ))
+    }
+
+    /// Returns the position of `index` among the links with the same target,
+    /// so that `nth_usage_as_target(target, rank)` is the link itself.
+    fn usage_rank_as_target(&self, index: T) -> Option<T> {
+        let any = self.constants().any;
+        let link = self.get_link(index)?;
+        Some(usages::rank(
+            self,
+            [any, any, link.target],
+            |usage| usage.source,
+            &link,
+        ))
+    }
+
+    /// Visits links with the given `source` whose target lies in `targets`,
+    /// ordered by target and then by index.
+    fn each_usages_as_source_in(
+        &self,
+        source: T,
+        targets: PartRange<T>,
+        handler: ReadHandler<'_, T>,
+    ) -> Flow {
+        let any = self.constants().any;
+        usages::each_in(
+            self,
+            [any, source, any],
+            |link| link.target,
+            targets,
+            handler,
+        )
+    }
+
+    /// Visits links with the given `target` whose source lies in `sources`,
+    /// ordered by source and then by index.
+    fn each_usages_as_target_in(
+        &self,
+        target: T,
+        sources: PartRange<T>,
+        handler: ReadHandler<'_, T>,
+    ) -> Flow {
+        let any = self.constants().any;
+        usages::each_in(
+            self,
+            [any, any, target],
+            |link| link.source,
+            sources,
+            handler,
+        )
+    }
+
+    /// Returns at most `limit` links with the given `source` from the `offset`-th one on,
+    /// in the order of [`nth_usage_as_source`](Self::nth_usage_as_source).
+    ///
+    /// The page is collected by a single walk over the usages after the `offset`-th one.
+    fn usages_as_source_page(&self, source: T, offset: T, limit: T) -> Vec<Link<T>> {
+        usages::source_page(self, source, offset, limit)
+    }
+
+    /// Returns at most `limit` links with the given `target` from the `offset`-th one on,
+    /// in the order of [`nth_usage_as_target`](Self::nth_usage_as_target).
+    ///
+    /// The page is collected by a single walk over the usages after the `offset`-th one.
+    fn usages_as_target_page(&self, target: T, offset: T, limit: T) -> Vec<Link<T>> {
+        usages::target_page(self, target, offset, limit)
+    }
+
+    /// Counts links whose source lies in `sources` and target lies in `targets`.
+    ///
+    /// Null parts are not indexed, so they are matched only by the unbounded `..` range.
+    fn count_in_ranges(&self, sources: PartRange<T>, targets: PartRange<T>) -> T {
+        range::count_in_ranges(self, sources, targets)
+    }
+
+    /// Visits links whose source lies in `sources` and target lies in `targets`.
+    ///
+    /// Stores with indexes visit links in ascending order of the more selective part.
+    fn each_in_ranges(
+        &self,
+        sources: PartRange<T>,
+        targets: PartRange<T>,
+        handler: ReadHandler<'_, T>,
+    ) -> Flow {
+        range::each_in_ranges(self, sources, targets, handler)
+    }
+
+    fn count_in(&self, sources: impl RangeBounds<T>, targets: impl RangeBounds<T>) -> T
+    where
+        Self: Sized,
+    {
+        self.count_in_ranges(part_range(&sources), part_range(&targets))
+    }
+
+    fn each_in<F, R>(
+        &self,
+        sources: impl RangeBounds<T>,
+        targets: impl RangeBounds<T>,
+        mut handler: F,
+    ) -> R
+    where
+        F: FnMut(Link<T>) -> R,
+        R: Branch,
+        Self: Sized,
+    {
+        let mut output = R::from_output();
+
+        self.each_in_ranges(
+            part_range(&sources),
+            part_range(&targets),
+            &mut |link| match handler(link).branch() {
+                ControlFlow::Continue(_) => Flow::Continue,
+                ControlFlow::Break(residual) => {
+                    output = R::from_residual(residual);
+                    Flow::Break
+                }
+            },
+        );
+
+        output
+    }
+
+    /// Visits links with index not less than `start` in index order.
+    fn each_from(&self, start: T, handler: ReadHandler<'_, T>) -> Flow {
+        self.each_links(&[], &mut |link| {
+            if link.index < start {
+                Flow::Continue
+            } else {
+                handler(link)
+            }
+        })
+    }
+
+    /// Continues the scan from `cursor` until `handler` breaks.
+    ///
+    /// Returns the cursor pointing right after the last handled link
+    /// or `None` if the scan is finished.
+    fn each_by_cursor(&self, cursor: &Cursor<T>, handler: ReadHandler<'_, T>) -> Option<Cursor<T>> {
+        each_by_cursor(self, cursor, handler)
+    }
+
+    /// Returns at most `limit` links from `cursor` on and the cursor of the next page,
+    /// which is `None` if no links are left.
+    fn page_by_cursor(
+        &self,
+        cursor: &Cursor<T>,
+        limit: usize,
+    ) -> (Vec<Link<T>>, Option<Cursor<T>>) {
+        page_by_cursor(self, cursor, limit)
+    }
 }
 
 impl<T: LinkType, All: Doublets<T> + ?Sized> Links<T> for Box<All> {
@@ -588,12 +823,83 @@ impl<T: LinkType, All: Doublets<T> + ?Sized> Links<T> for Box<All> {
     ) -> Result<Flow, Error<T>> {
         (**self).delete_links(query, handler)
     }
+
+    fn create_many(&mut self, count: T, handler: WriteHandler<'_, T>) -> Result<Flow, Error<T>> {
+        (**self).create_many(count, handler)
+    }
+
+    fn update_many(
+        &mut self,
+        changes: &[Link<T>],
+        handler: WriteHandler<'_, T>,
+    ) -> Result<Flow, Error<T>> {
+        (**self).update_many(changes, handler)
+    }
+
+    fn delete_many(
+        &mut self,
+        indices: &[T],
+        handler: WriteHandler<'_, T>,
+    ) -> Result<Flow, Error<T>> {
+        (**self).delete_many(indices, handler)
+    }
 }
 
 impl<T: LinkType, All: Doublets<T> + ?Sized> Doublets<T> for Box<All> {
     fn get_link(&self, index: T) -> Option<Link<T>> {
         (**self).get_link(index)
     }
+
+    fn nth_usage_as_source(&self, source: T, n: T) -> Option<Link<T>> {
+        (**self).nth_usage_as_source(source, n)
+    }
+
+    fn nth_usage_as_target(&self, target: T, n: T) -> Option<Link<T>> {
+        (**self).nth_usage_as_target(target, n)
+    }
+
+    fn usage_rank_as_source(&self, index: T) -> Option<T> {
+        (**self).usage_rank_as_source(index)
+    }
+
+    fn usage_rank_as_target(&self, index: T) -> Option<T> {
+        (**self).usage_rank_as_target(index)
+    }
+
+    fn each_usages_as_source_in(
+        &self,
+        source: T,
+        targets: PartRange<T>,
+        handler: ReadHandler<'_, T>,
+    ) -> Flow {
+        (**self).each_usages_as_source_in(source, targets, handler)
+    }
+
+    fn each_usages_as_target_in(
+        &self,
+        target: T,
+        sources: PartRange<T>,
+        handler: ReadHandler<'_, T>,
+    ) -> Flow {
+        (**self).each_usages_as_target_in(target, sources, handler)
+    }
+
+    fn each_from(&self, start: T, handler: ReadHandler<'_, T>) -> Flow {
+        (**self).each_from(start, handler)
+    }
+
+    fn count_in_ranges(&self, sources: PartRange<T>, targets: PartRange<T>) -> T {
+        (**self).count_in_ranges(sources, targets)
+    }
+
+    fn each_in_ranges(
+        &self,
+        sources: PartRange<T>,
+        targets: PartRange<T>,
+        handler: ReadHandler<'_, T>,
+    ) -> Flow {
+        (**self).each_in_ranges(sources, targets, handler)
+    }
 }
 
 pub trait DoubletsExt<T: LinkType>: Sized + Doublets<T> {
@@ -606,9 +912,7 @@ pub trait DoubletsExt<T: LinkType>: Sized + Doublets<T> {
     #[cfg(feature = "rayon")]
     fn par_each_iter(&self, query: impl ToQuery<T>) -> Self::IdxParIter;
 
-    // Box<dyn Iterator<Item = T>> must used while `-> impl Trait` is not stabilized
-    // Box<dyn> than easier `Self::ImplIterator1,2,...`
-    // and have same performance if has only one possible dyn variant
+    // iterators are concrete types, because `impl Trait` in associated types is not stable
 
     type ImplIter: Iterator<Item = Link<T>>;
     fn iter(&self) -> Self::ImplIter;
@@ -629,7 +933,7 @@ pub trait DoubletsExt<T: LinkType>: Sized + Doublets<T> {
 
 impl<T: LinkType, All: Doublets<T> + Sized> DoubletsExt<T> for All {
     #[cfg(feature = "rayon")]
-    type IdxParIter = impl IndexedParallelIterator<Item = Link<T>>;
+    type IdxParIter = rayon::vec::IntoIter<Link<T>>;
 
     #[cfg(feature = "rayon")]
     fn par_iter(&self) -> Self::IdxParIter {
@@ -653,7 +957,7 @@ impl<T: LinkType, All: Doublets<T> + Sized> DoubletsExt<T> for All {
         self.each_iter([self.constants().any; 3])
     }
 
-    type ImplIterEach = impl Iterator<Item = Link<T>> + ExactSizeIterator + DoubleEndedIterator;
+    type ImplIterEach = vec::IntoIter<Link<T>>;
 
     #[cfg_attr(feature = "more-inline", inline)]
     fn each_iter(&self, query: impl ToQuery<T>) -> Self::ImplIterEach {
@@ -677,16 +981,12 @@ impl<T: LinkType, All: Doublets<T> + Sized> DoubletsExt<T> for All {
     }
 
     #[cfg(feature = "small-search")]
-    type ImplIterEachSmall =
-        impl Iterator<Item = Link<T>> + ExactSizeIterator + DoubleEndedIterator;
+    type ImplIterEachSmall = smallvec::IntoIter<[Link<T>; SMALL_SEARCH]>;
 
     #[cfg(feature = "small-search")]
     #[cfg_attr(feature = "more-inline", inline)]
     fn each_iter_small(&self, query: impl ToQuery<T>) -> Self::ImplIterEachSmall {
-        // fixme: later use const generics
-        const SIZE_HINT: usize = 2;
-
-        let mut vec = smallvec::SmallVec::<[Link<_>; SIZE_HINT]>::with_capacity(
+        let mut vec = smallvec::SmallVec::<[Link<_>; SMALL_SEARCH]>::with_capacity(
             self.count_by(query.to_query()).as_usize(),
         );
         self.each_by(query, |link| {
@@ -0,0 +1,144 @@
+use alloc::vec::Vec;
+use core::ops::{Bound, RangeBounds};
+
+use crate::{Doublets, Link, Links, PartRange, ReadHandler};
+use data::{Flow, LinkType};
+
+// fallbacks for stores without order statistics: usages are ordered by `key` and then by index
+
+fn collect<T, L>(links: &L, query: [T; 3], mut filter: impl FnMut(&Link<T>) -> bool) -> Vec<Link<T>>
+where
+    T: LinkType,
+    L: Links<T> + ?Sized,
+{
+    let mut usages = Vec::new();
+    links.each_links(&query, &mut |link| {
+        if filter(&link) {
+            usages.push(link);
+        }
+        Flow::Continue
+    });
+    usages
+}
+
+pub(crate) fn nth<T, L>(links: &L, query: [T; 3], key: fn(&Link<T>) -> T, n: T) -> Option<Link<T>>
+where
+    T: LinkType,
+    L: Links<T> + ?Sized,
+{
+    let mut usages = collect(links, query, |_| true);
+    let n = n.as_usize();
+    if n < usages.len() {
+        Some(
+            usages
+                .select_nth_unstable_by_key(n, |link| (key(link), link.index))
+                .1
+                .clone(),
+        )
+    } else {
+        None
+    }
+}
+
+pub(crate) fn rank<T, L>(links: &L, query: [T; 3], key: fn(&Link<T>) -> T, link: &Link<T>) -> T
+where
+    T: LinkType,
+    L: Links<T> + ?Sized,
+{
+    let mut rank = T::funty(0);
+    links.each_links(&query, &mut |usage| {
+        if (key(&usage), usage.index) < (key(link), link.index) {
+            rank += T::funty(1);
+        }
+        Flow::Continue
+    });
+    rank
+}
+
+pub(crate) fn each_in<T, L>(
+    links: &L,
+    query: [T; 3],
+    key: fn(&Link<T>) -> T,
+    range: PartRange<T>,
+    handler: ReadHandler<'_, T>,
+) -> Flow
+where
+    T: LinkType,
+    L: Links<T> + ?Sized,
+{
+    let mut usages = collect(links, query, |link| range.contains(&key(link)));
+    usages.sort_unstable_by_key(|link| (key(link), link.index));
+    for link in usages {
+        handler(link)?;
+    }
+    Flow::Continue
+}
+
+// `limit` usages from `first` on, visited by a single walk over usages with keys from its key
+fn page<T: LinkType>(
+    first: Option<Link<T>>,
+    limit: T,
+    key: fn(&Link<T>) -> T,
+    each_from: impl FnOnce(T, ReadHandler<'_, T>) -> Flow,
+) -> Vec<Link<T>> {
+    let limit = limit.as_usize();
+    let mut page = Vec::with_capacity(limit);
+    let first = match first {
+        Some(first) if limit > 0 => first,
+        _ => return page,
+    };
+    let start = (key(&first), first.index);
+    each_from(start.0, &mut |link| {
+        // usages with the same key and a smaller index precede `first`
+        if (key(&link), link.index) < start {
+            return Flow::Continue;
+        }
+        page.push(link);
+        if page.len() < limit {
+            Flow::Continue
+        } else {
+            Flow::Break
+        }
+    });
+    page
+}
+
+pub(crate) fn source_page<T, L>(links: &L, source: T, offset: T, limit: T) -> Vec<Link<T>>
+where
+    T: LinkType,
+    L: Doublets<T> + ?Sized,
+{
+    let first = links.nth_usage_as_source(source, offset);
+    page(
+        first,
+        limit,
+        |link| link.target,
+        |from, handler| {
+            links.each_usages_as_source_in(
+                source,
+                (Bound::Included(from), Bound::Unbounded),
+                handler,
+            )
+        },
+    )
+}
+
+pub(crate) fn target_page<T, L>(links: &L, target: T, offset: T, limit: T) -> Vec<Link<T>>
+where
+    T: LinkType,
+    L: Doublets<T> + ?Sized,
+{
+    let first = links.nth_usage_as_target(target, offset);
+    page(
+        first,
+        limit,
+        |link| link.source,
+        |from, handler| {
+            links.each_usages_as_target_in(
+                target,
+                (Bound::Included(from), Bound::Unbounded),
+                handler,
+            )
+        },
+    )
+}
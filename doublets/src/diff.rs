@@ -0,0 +1,222 @@
+//! Differences between two stores and merging them into a store.
+//!
+//! [`diff`] matches links by index, so a link with new parts is [`Change::Changed`].
+//! [`structural_diff`] matches links by `(source, target)` parts instead,
+//! so stores filled in a different order differ only by links missing in one of them.
+
+use alloc::{collections::BTreeMap, vec::Vec};
+use core::cmp::Ordering;
+
+use crate::{Doublets, Error, Link};
+use data::{Flow, LinkType};
+
+/// How links of two stores are matched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Matching {
+    /// Links with the same index.
+    Index,
+    /// Links with the same source and target, whatever their indices are.
+    Content,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Change<T: LinkType> {
+    /// Link of the second store only.
+    Added(Link<T>),
+    /// Link of the first store only.
+    Removed(Link<T>),
+    /// Link with the same index and other parts, never made by [`structural_diff`].
+    Changed { before: Link<T>, after: Link<T> },
+}
+
+/// Changes turning the first store into the second one.
+///
+/// Changes are ordered by index, and removals go first when links are matched by content.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Diff<T: LinkType> {
+    pub matching: Matching,
+    pub changes: Vec<Change<T>>,
+}
+
+impl<T: LinkType> Diff<T> {
+    /// Returns `true` if the stores hold the same links.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+fn links<T: LinkType>(store: &impl Doublets<T>) -> Vec<Link<T>> {
+    let mut links = Vec::with_capacity(store.count().as_usize());
+    store.each(|link| {
+        links.push(link);
+        Flow::Continue
+    });
+    links.sort_unstable_by_key(|link| link.index);
+    links
+}
+
+/// Compares links of `a` and `b` with the same index.
+pub fn diff<T, A, B>(a: &A, b: &B) -> Diff<T>
+where
+    T: LinkType,
+    A: Doublets<T>,
+    B: Doublets<T>,
+{
+    let (mut a, mut b) = (
+        links(a).into_iter().peekable(),
+        links(b).into_iter().peekable(),
+    );
+    let mut changes = Vec::new();
+    loop {
+        let order = match (a.peek(), b.peek()) {
+            (Some(before), Some(after)) => before.index.cmp(&after.index),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => break,
+        };
+        match order {
+            Ordering::Less => changes.extend(a.next().map(Change::Removed)),
+            Ordering::Greater => changes.extend(b.next().map(Change::Added)),
+            Ordering::Equal => {
+                if let (Some(before), Some(after)) = (a.next(), b.next()) {
+                    if before != after {
+                        changes.push(Change::Changed { before, after });
+                    }
+                }
+            }
+        }
+    }
+    Diff {
+        matching: Matching::Index,
+        changes,
+    }
+}
+
+/// Compares links of `a` and `b` by their source and target.
+///
+/// Links with the same parts are matched as many times as both stores have them,
+/// so duplicates are added or removed too.
+pub fn structural_diff<T, A, B>(a: &A, b: &B) -> Diff<T>
+where
+    T: LinkType,
+    A: Doublets<T>,
+    B: Doublets<T>,
+{
+    let mut unmatched: BTreeMap<(T, T), Vec<Link<T>>> = BTreeMap::new();
+    for link in links(a).into_iter().rev() {
+        unmatched
+            .entry((link.source, link.target))
+            .or_default()
+            .push(link);
+    }
+
+    let mut changes = Vec::new();
+    for link in links(b) {
+        let matched = unmatched
+            .get_mut(&(link.source, link.target))
+            .and_then(Vec::pop);
+        if matched.is_none() {
+            changes.push(Change::Added(link));
+        }
+    }
+    changes.extend(unmatched.into_values().flatten().map(Change::Removed));
+    changes.sort_by_key(|change| match change {
+        Change::Removed(link) => (0, link.index),
+        Change::Added(link) | Change::Changed { after: link, .. } => (1, link.index),
+    });
+    Diff {
+        matching: Matching::Content,
+        changes,
+    }
+}
+
+/// Applies `diff` to `target`, usually the first store of the diff or its copy.
+///
+/// With [`Matching::Index`] links are created, updated and deleted at their indices,
+/// while with [`Matching::Content`] removed links are searched by their parts
+/// and added links get new indices. Removed links missing in `target` are skipped.
+pub fn merge<T, S>(target: &mut S, diff: &Diff<T>) -> Result<(), Error<T>>
+where
+    T: LinkType,
+    S: Doublets<T>,
+{
+    match diff.matching {
+        Matching::Index => merge_by_index(target, &diff.changes),
+        Matching::Content => merge_by_content(target, &diff.changes),
+    }
+}
+
+fn merge_by_index<T: LinkType>(
+    target: &mut impl Doublets<T>,
+    changes: &[Change<T>],
+) -> Result<(), Error<T>> {
+    // links are created until the wanted index is free, then the extra ones are deleted
+    let mut holes = Vec::new();
+    for change in changes {
+        match change {
+            Change::Added(link) | Change::Changed { after: link, .. } => {
+                if target.get_link(link.index).is_none() {
+                    match holes.iter().position(|&hole| hole == link.index) {
+                        Some(hole) => {
+                            holes.swap_remove(hole);
+                        }
+                        None => loop {
+                            let index = target.create()?;
+                            if index == link.index {
+                                break;
+                            }
+                            holes.push(index);
+                        },
+                    }
+                }
+                target.update(link.index, link.source, link.target)?;
+            }
+            Change::Removed(_) => {}
+        }
+    }
+    for hole in holes.into_iter().rev() {
+        target.delete(hole)?;
+    }
+    for change in changes {
+        if let Change::Removed(link) = change {
+            if target.get_link(link.index).is_some() {
+                target.delete(link.index)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn merge_by_content<T: LinkType>(
+    target: &mut impl Doublets<T>,
+    changes: &[Change<T>],
+) -> Result<(), Error<T>> {
+    for change in changes {
+        match change {
+            // the link with the same index is preferred among links with the same parts
+            Change::Removed(link) => {
+                let index = if target.get_link(link.index).as_ref() == Some(link) {
+                    Some(link.index)
+                } else {
+                    target.search(link.source, link.target)
+                };
+                if let Some(index) = index {
+                    target.delete(index)?;
+                }
+            }
+            Change::Added(link) => {
+                target.create_link(link.source, link.target)?;
+            }
+            Change::Changed { before, after } => {
+                if let Some(index) = target.search(before.source, before.target) {
+                    target.update(index, after.source, after.target)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,309 @@
+//! Garbage collection of links unreachable from roots.
+//!
+//! A link is reachable if it is a root or the source or the target of a reachable link.
+//! [`Collector`] marks reachable links, scans the store for the rest
+//! and then reports or deletes them, all at once or in steps with bounded work.
+
+use alloc::{collections::VecDeque, vec, vec::Vec};
+
+use crate::{Doublets, Error, Link};
+use data::{Flow, LinkType};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Phase {
+    Mark,
+    Scan,
+    Sweep,
+    Done,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Fate {
+    Pending,
+    Kept,
+    Deleted,
+}
+
+/// Finds and deletes links unreachable from the given roots.
+///
+/// Work of [`mark`](Self::mark) and [`sweep`](Self::sweep) is bounded by `budget`
+/// visited links per call, so a large store can be collected between other operations.
+/// Garbage is what was unreachable when marking finished: a link is deleted
+/// only if it is unchanged and no other link uses it, so no link is left referring
+/// to a deleted one when the store is changed between steps. Links changed or used
+/// by live links are kept together with the garbage they use, links used only by
+/// other garbage wait for their usages to be deleted, and cycles of garbage using
+/// only each other are deleted together in one step.
+#[derive(Debug, Clone)]
+pub struct Collector<T: LinkType> {
+    roots: Vec<T>,
+    keep_points: bool,
+    phase: Phase,
+    // reachable links by index
+    marked: Vec<bool>,
+    stack: Vec<T>,
+    // next root while marking and next index while scanning
+    cursor: usize,
+    garbage: Vec<Link<T>>,
+    // fates of `garbage` and positions of pending ones waiting to be swept
+    fates: Vec<Fate>,
+    pending: VecDeque<usize>,
+    // pending links put back in a row because only garbage uses them
+    deferred: usize,
+    deleted: usize,
+}
+
+impl<T: LinkType> Collector<T> {
+    #[must_use]
+    pub fn new(roots: impl IntoIterator<Item = T>) -> Self {
+        Self {
+            roots: roots.into_iter().collect(),
+            keep_points: false,
+            phase: Phase::Mark,
+            marked: Vec::new(),
+            stack: Vec::new(),
+            cursor: 0,
+            garbage: Vec::new(),
+            fates: Vec::new(),
+            pending: VecDeque::new(),
+            deferred: 0,
+            deleted: 0,
+        }
+    }
+
+    /// Keeps point links, whose source and target are the link itself, even if unreachable.
+    #[must_use]
+    pub const fn keep_points(mut self, keep: bool) -> Self {
+        self.keep_points = keep;
+        self
+    }
+
+    /// Links found unreachable, in index order, filled when marking is finished.
+    #[must_use]
+    pub fn garbage(&self) -> &[Link<T>] {
+        &self.garbage
+    }
+
+    /// Count of links deleted by [`sweep`](Self::sweep).
+    #[must_use]
+    pub const fn deleted(&self) -> usize {
+        self.deleted
+    }
+
+    /// Starts the collection over, e.g. after the store is changed between steps.
+    pub fn reset(&mut self) {
+        self.phase = Phase::Mark;
+        self.marked.clear();
+        self.stack.clear();
+        self.cursor = 0;
+        self.garbage.clear();
+        self.fates.clear();
+        self.pending.clear();
+        self.deferred = 0;
+        self.deleted = 0;
+    }
+
+    /// Marks reachable links and scans for unreachable ones, visiting at most `budget` links.
+    ///
+    /// Returns `true` when [`garbage`](Self::garbage) is complete.
+    /// Fails with [`Error::NotExists`] if a root does not exist.
+    pub fn mark<S: Doublets<T>>(&mut self, store: &S, mut budget: usize) -> Result<bool, Error<T>> {
+        self.mark_within(store, &mut budget)?;
+        Ok(matches!(self.phase, Phase::Sweep | Phase::Done))
+    }
+
+    /// Continues marking if needed and deletes unreachable links,
+    /// visiting at most `budget` links.
+    ///
+    /// Returns `true` when the collection is finished.
+    pub fn sweep<S: Doublets<T>>(
+        &mut self,
+        store: &mut S,
+        mut budget: usize,
+    ) -> Result<bool, Error<T>> {
+        self.mark_within(store, &mut budget)?;
+        if self.phase == Phase::Sweep {
+            self.sweep_within(store, &mut budget)?;
+        }
+        Ok(self.phase == Phase::Done)
+    }
+
+    /// Finds all unreachable links without changing the store.
+    pub fn report<S: Doublets<T>>(&mut self, store: &S) -> Result<&[Link<T>], Error<T>> {
+        while !self.mark(store, usize::MAX)? {}
+        Ok(&self.garbage)
+    }
+
+    /// Deletes all unreachable links and returns their count.
+    pub fn collect<S: Doublets<T>>(&mut self, store: &mut S) -> Result<usize, Error<T>> {
+        while !self.sweep(store, usize::MAX)? {}
+        Ok(self.deleted)
+    }
+
+    fn is_marked(&self, index: T) -> bool {
+        is_marked(&self.marked, index)
+    }
+
+    fn set_marked(&mut self, index: T) {
+        let index = index.as_usize();
+        if index >= self.marked.len() {
+            self.marked.resize(index + 1, false);
+        }
+        self.marked[index] = true;
+    }
+
+    fn mark_within<S: Doublets<T>>(
+        &mut self,
+        store: &S,
+        budget: &mut usize,
+    ) -> Result<(), Error<T>> {
+        while self.phase == Phase::Mark && *budget > 0 {
+            let index = match self.stack.pop() {
+                Some(index) => index,
+                None if self.cursor < self.roots.len() => {
+                    let root = self.roots[self.cursor];
+                    self.cursor += 1;
+                    store.try_get_link(root)?;
+                    root
+                }
+                None => {
+                    self.phase = Phase::Scan;
+                    self.cursor = 0;
+                    break;
+                }
+            };
+            *budget -= 1;
+            if self.is_marked(index) {
+                continue;
+            }
+            // parts which are not links, like external references, are not followed
+            if let Some(link) = store.get_link(index) {
+                self.set_marked(index);
+                for part in [link.source, link.target] {
+                    if !self.is_marked(part) {
+                        self.stack.push(part);
+                    }
+                }
+            }
+        }
+
+        if self.phase == Phase::Scan && *budget > 0 {
+            let start = T::try_from(self.cursor).expect("always ok");
+            let (marked, garbage, keep_points) =
+                (&self.marked, &mut self.garbage, self.keep_points);
+            let mut next = None;
+            store.each_from(start, &mut |link| {
+                if *budget == 0 {
+                    next = Some(link.index);
+                    return Flow::Break;
+                }
+                *budget -= 1;
+                let point = link.source == link.index && link.target == link.index;
+                if !is_marked(marked, link.index) && !(keep_points && point) {
+                    garbage.push(link);
+                }
+                Flow::Continue
+            });
+            match next {
+                Some(next) => self.cursor = next.as_usize(),
+                None => {
+                    self.phase = Phase::Sweep;
+                    self.cursor = 0;
+                    self.marked = Vec::new();
+                    self.fates = vec![Fate::Pending; self.garbage.len()];
+                    self.pending = (0..self.garbage.len()).collect();
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // links which are not garbage or are kept are live, and so are new links
+    // reusing indices of deleted garbage
+    fn is_pending(&self, index: T) -> bool {
+        self.garbage
+            .binary_search_by_key(&index, |link| link.index)
+            .map_or(false, |position| self.fates[position] == Fate::Pending)
+    }
+
+    fn sweep_within<S: Doublets<T>>(
+        &mut self,
+        store: &mut S,
+        budget: &mut usize,
+    ) -> Result<(), Error<T>> {
+        while *budget > 0 {
+            let position = match self.pending.pop_front() {
+                Some(position) => position,
+                None => break,
+            };
+            *budget -= 1;
+
+            let link = self.garbage[position].clone();
+            if store.get_link(link.index).as_ref() != Some(&link) {
+                self.settle(position, Fate::Kept);
+                continue;
+            }
+            let usages = store.usages(link.index)?;
+            if usages.is_empty() {
+                store.delete(link.index)?;
+                self.settle(position, Fate::Deleted);
+            } else if usages.iter().all(|&usage| self.is_pending(usage)) {
+                self.pending.push_back(position);
+                self.deferred += 1;
+                if self.deferred >= self.pending.len() {
+                    self.delete_cycles(store)?;
+                }
+            } else {
+                self.settle(position, Fate::Kept);
+            }
+        }
+        if self.pending.is_empty() {
+            self.phase = Phase::Done;
+        }
+        Ok(())
+    }
+
+    fn settle(&mut self, position: usize, fate: Fate) {
+        self.fates[position] = fate;
+        self.deferred = 0;
+        if fate == Fate::Deleted {
+            self.deleted += 1;
+        }
+    }
+
+    // every pending link is used by pending ones only, so they form cycles: links changed
+    // or used by live links are kept until nothing changes, and the rest is deleted at once
+    fn delete_cycles<S: Doublets<T>>(&mut self, store: &mut S) -> Result<(), Error<T>> {
+        let mut kept = true;
+        while kept {
+            kept = false;
+            for &position in &self.pending {
+                if self.fates[position] != Fate::Pending {
+                    continue;
+                }
+                let link = &self.garbage[position];
+                let live = store.get_link(link.index).as_ref() != Some(link)
+                    || store
+                        .usages(link.index)?
+                        .into_iter()
+                        .any(|usage| !self.is_pending(usage));
+                if live {
+                    self.fates[position] = Fate::Kept;
+                    kept = true;
+                }
+            }
+        }
+        for position in core::mem::take(&mut self.pending) {
+            if self.fates[position] == Fate::Pending {
+                store.delete(self.garbage[position].index)?;
+                self.settle(position, Fate::Deleted);
+            }
+        }
+        self.deferred = 0;
+        Ok(())
+    }
+}
+
+fn is_marked<T: LinkType>(marked: &[bool], index: T) -> bool {
+    marked.get(index.as_usize()).copied().unwrap_or_default()
+}
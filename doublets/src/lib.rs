@@ -1,15 +1,5 @@
-#![feature(fn_traits)]
-#![feature(generators)]
-#![feature(try_trait_v2)]
-#![feature(default_free_fn)]
-#![feature(unboxed_closures)]
-#![feature(nonnull_slice_from_raw_parts)]
-#![feature(associated_type_defaults)]
-#![feature(type_alias_impl_trait)]
-#![feature(maybe_uninit_uninit_array)]
-#![feature(allocator_api)]
-#![feature(bench_black_box)]
-#![feature(maybe_uninit_array_assume_init)]
+#![cfg_attr(not(feature = "std"), no_std)]
+#![cfg_attr(feature = "nightly", feature(fn_traits, unboxed_closures))]
 #![cfg_attr(not(test), forbid(clippy::unwrap_used))]
 #![warn(
     clippy::perf,
@@ -57,10 +47,25 @@
 // must be fixed later
 #![allow(clippy::needless_pass_by_value, clippy::comparison_chain)]
 
+extern crate alloc;
+
+#[cfg(feature = "async")]
+pub mod aio;
+#[cfg(feature = "std")]
+pub mod concurrent;
 pub mod data;
+pub mod diff;
+pub mod gc;
 pub mod mem;
+#[cfg(feature = "metrics")]
+pub mod metrics;
 
-pub use self::mem::{parts, split, unit};
+pub use self::mem::{parts, sharded, split, unit};
 
-pub use self::data::{Doublet, Doublets, DoubletsExt, Error, Fuse, Handler, Link, Links};
+#[cfg(not(feature = "std"))]
+pub use self::data::OtherDisplay;
+pub use self::data::{
+    Branch, Cursor, Doublet, Doublets, DoubletsExt, Error, Fuse, Handler, Link, Links, OtherError,
+    PartRange,
+};
 pub(crate) use self::data::{Error as LinksError, ReadHandler, WriteHandler};
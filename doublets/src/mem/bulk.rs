@@ -0,0 +1,90 @@
+use crate::{mem::LinksHeader, Links, LinksError, WriteHandler};
+use data::{Flow, LinkType, LinksConstants};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+use trees::SzbTree;
+
+// fails if `links` do not fit after the `allocated` ones or have a part
+// which is neither zero nor an internal or external reference
+pub(crate) fn check_load<T: LinkType>(
+    links: &[(T, T)],
+    allocated: T,
+    constants: &LinksConstants<T>,
+) -> Result<(), LinksError<T>> {
+    let max_inner = *constants.internal_range.end();
+    if links.len() > (max_inner - allocated).as_usize() {
+        return Err(LinksError::LimitReached(max_inner));
+    }
+    let is_valid =
+        |part: T| part == T::funty(0) || constants.is_internal(part) || constants.is_external(part);
+    match links
+        .iter()
+        .flat_map(|&(source, target)| [source, target])
+        .find(|&part| !is_valid(part))
+    {
+        Some(part) => Err(LinksError::NotExists(part)),
+        None => Ok(()),
+    }
+}
+
+// creates `count` links after growing the memory of `links` by `reserve` at once:
+// free links are reused first, so only the rest is reserved, and links are still created
+// one by one, so errors stop the batch where the default `Links::create_many` stops it
+pub(crate) fn create_many_reserved<T, L>(
+    links: &mut L,
+    header: LinksHeader<T>,
+    count: T,
+    reserve: fn(&mut L, usize) -> Result<(), LinksError<T>>,
+    handler: WriteHandler<'_, T>,
+) -> Result<Flow, LinksError<T>>
+where
+    T: LinkType,
+    L: Links<T>,
+{
+    let max_inner = *links.constants().internal_range.end();
+    let new = count.as_usize().saturating_sub(header.free.as_usize()).min(
+        max_inner
+            .as_usize()
+            .saturating_sub(header.allocated.as_usize()),
+    );
+    reserve(links, new)?;
+
+    for _ in 0..count.as_usize() {
+        if let Flow::Break = links.create_links(&[], handler)? {
+            return Ok(Flow::Break);
+        }
+    }
+    Ok(Flow::Continue)
+}
+
+// sorts tree nodes by their `(base, key)` parts and then by index
+pub(crate) fn sort_nodes<T, K>(nodes: &mut [T], parts: K)
+where
+    T: LinkType,
+    K: Fn(T) -> (T, T) + Sync,
+{
+    #[cfg(feature = "rayon")]
+    nodes.par_sort_unstable_by_key(|&node| (parts(node), node));
+    #[cfg(not(feature = "rayon"))]
+    nodes.sort_unstable_by_key(|&node| (parts(node), node));
+}
+
+// builds a perfectly balanced tree (so a valid size-balanced one)
+// from nodes sorted in tree order and returns its root
+pub(crate) unsafe fn build_balanced<T, Tree>(tree: &mut Tree, nodes: &[T]) -> T
+where
+    T: LinkType,
+    Tree: SzbTree<T> + ?Sized,
+{
+    if nodes.is_empty() {
+        return T::funty(0);
+    }
+    let mid = nodes.len() / 2;
+    let root = nodes[mid];
+    let left = build_balanced(tree, &nodes[..mid]);
+    let right = build_balanced(tree, &nodes[mid + 1..]);
+    tree.set_left(root, left);
+    tree.set_right(root, right);
+    tree.set_size(root, T::try_from(nodes.len()).expect("always ok"));
+    root
+}
@@ -1,5 +1,9 @@
 use data::LinkType;
 
+// equal links in trees are ordered by index since version 1,
+// trees of older files (version 0) are rebuilt when the file is opened
+const LINKS_VERSION: usize = 1;
+
 #[derive(Debug, Default, Clone, Eq, PartialEq)]
 #[repr(C)]
 pub struct LinksHeader<T: LinkType> {
@@ -10,6 +14,16 @@ pub struct LinksHeader<T: LinkType> {
     pub root_as_source: T,
     pub root_as_target: T,
     pub last_free: T,
+    pub version: T,
+}
+
+impl<T: LinkType> LinksHeader<T> {
+    // trees are written in the current order, so they are used as is
+    pub(crate) fn is_current(&self) -> bool {
+        self.version.as_usize() >= LINKS_VERSION
+    }
 
-    __reserved_8: T,
+    pub(crate) fn set_current(&mut self) {
+        self.version = T::try_from(LINKS_VERSION).expect("always ok");
+    }
 }
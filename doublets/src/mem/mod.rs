@@ -1,14 +1,27 @@
 pub use header::LinksHeader;
+#[cfg(feature = "mmap")]
+pub use read_only::ReadOnlyMapped;
+pub use stats::{Stats, TreeStats};
 pub use traits::{
     LinksList, LinksTree, SplitList, SplitTree, SplitUpdateMem, UnitTree, UnitUpdateMem,
 };
+pub use unique::Duplicates;
+mod bulk;
 mod header;
+#[cfg(feature = "mmap")]
+mod read_only;
+pub mod sharded;
 pub mod split;
+mod stats;
 mod traits;
+mod unique;
 pub mod unit;
 
-#[cfg(feature = "mem")]
+#[cfg(all(feature = "mem", feature = "mmap"))]
 pub use mem::*;
+// `FileMapped` maps files, so only in-memory storages are available without `mmap`
+#[cfg(all(feature = "mem", not(feature = "mmap")))]
+pub use mem::{Error, Global, RawMem, DEFAULT_PAGE_SIZE};
 
 pub mod parts {
     pub use super::{
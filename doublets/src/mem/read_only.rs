@@ -0,0 +1,57 @@
+use core::{marker::PhantomData, mem::size_of, slice};
+use std::{fs::File, io, path::Path};
+
+use mem::RawMem;
+use memmap2::{Mmap, MmapOptions};
+
+/// File memory for stores opened with `read_only`.
+///
+/// The file is opened and mapped without write permission, so it is never changed:
+/// stores opened with `read_only` do not write their memory, and a write would fault.
+/// As with `FileMapped`, the file must not be changed by others while it is mapped.
+/// The memory cannot grow: `alloc` fails for more elements than the file has.
+pub struct ReadOnlyMapped<T> {
+    map: Mmap,
+    _marker: PhantomData<T>,
+}
+
+impl<T> ReadOnlyMapped<T> {
+    /// Maps the whole file, the trailing bytes not forming an element are ignored.
+    pub fn new(file: &File) -> io::Result<Self> {
+        // SAFETY: the file is not changed while mapped, which is required from callers
+        // as for `FileMapped`
+        let map = unsafe { MmapOptions::new().map(file)? };
+        Ok(Self {
+            map,
+            _marker: PhantomData,
+        })
+    }
+
+    pub fn from_path<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::new(&File::open(path)?)
+    }
+}
+
+impl<T> RawMem<T> for ReadOnlyMapped<T> {
+    fn alloc(&mut self, capacity: usize) -> Result<&mut [T], mem::Error> {
+        if capacity > self.allocated() {
+            return Err(io::Error::new(
+                io::ErrorKind::PermissionDenied,
+                "read-only memory cannot grow",
+            )
+            .into());
+        }
+        if capacity == 0 {
+            return Ok(&mut []);
+        }
+        // SAFETY: the mapping is page aligned and has at least `capacity` elements,
+        // `RawMem` requires a mutable slice, but the memory is only read:
+        // stores opened with `read_only` fail with `ReadOnly` before any write
+        let ptr = self.map.as_ptr() as *mut u8;
+        Ok(unsafe { slice::from_raw_parts_mut(ptr.cast(), capacity) })
+    }
+
+    fn allocated(&self) -> usize {
+        self.map.len() / size_of::<T>()
+    }
+}
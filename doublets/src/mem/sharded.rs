@@ -0,0 +1,295 @@
+use alloc::vec::Vec;
+
+use crate::{
+    mem::Duplicates, Doublet, Doublets, Link, Links, LinksError, ReadHandler, WriteHandler,
+};
+use data::{Flow, LinkType, LinksConstants};
+
+/// Store that spreads links across `shards` by index ranges.
+///
+/// The shard `k` holds links with global indices `k * capacity + 1..=(k + 1) * capacity`
+/// at local indices `1..=capacity`.
+/// Sources and targets in other shards are stored above `capacity`, so every shard
+/// keeps them apart from its own links, e.g. in external trees of [`split::Store`].
+/// New links are created in the first shard that is not full.
+/// Queries by source or target are routed to all shards, so `search` stays exact.
+/// Duplicates are checked by [`Store::with_duplicates`] across all shards,
+/// since a shard finds only its own links.
+///
+/// [`split::Store`]: crate::split::Store
+pub struct Store<T: LinkType, S: Doublets<T>> {
+    shards: Vec<S>,
+    capacity: T,
+    shifts: Vec<Shift<T>>,
+    // links in every shard, so a shard for new links is found without asking all of them
+    counts: Vec<T>,
+    duplicates: Duplicates,
+}
+
+// rotates the global range `1..=total` by `offset` for one shard: its own links
+// take `1..=capacity`, links of other shards follow them, other values are not changed
+#[derive(Clone, Copy)]
+struct Shift<T> {
+    offset: T,
+    total: T,
+}
+
+impl<T: LinkType> Shift<T> {
+    fn local(self, value: T) -> T {
+        if value == T::funty(0) || value > self.total {
+            value
+        } else if value > self.offset {
+            value - self.offset
+        } else {
+            value + (self.total - self.offset)
+        }
+    }
+
+    fn global(self, value: T) -> T {
+        if value == T::funty(0) || value > self.total {
+            value
+        } else if value <= self.total - self.offset {
+            value + self.offset
+        } else {
+            value - (self.total - self.offset)
+        }
+    }
+
+    fn query(self, query: &[T]) -> Vec<T> {
+        query.iter().map(|&value| self.local(value)).collect()
+    }
+
+    fn link(self, link: Link<T>) -> Link<T> {
+        Link::new(
+            self.global(link.index),
+            self.global(link.source),
+            self.global(link.target),
+        )
+    }
+
+    // shards report their local values
+    fn err(self, err: LinksError<T>) -> LinksError<T> {
+        match err {
+            LinksError::NotExists(value) => LinksError::NotExists(self.global(value)),
+            LinksError::HasUsages(usages) => {
+                LinksError::HasUsages(usages.into_iter().map(|link| self.link(link)).collect())
+            }
+            LinksError::AlreadyExists(doublet) => LinksError::AlreadyExists(Doublet::new(
+                self.global(doublet.source),
+                self.global(doublet.target),
+            )),
+            err => err,
+        }
+    }
+}
+
+impl<T: LinkType, S: Doublets<T>> Store<T, S> {
+    /// Creates a store from `shards` holding at most `capacity` links each.
+    ///
+    /// Returns `LimitReached` if any shard already holds more than `capacity` links.
+    /// Links of `shards` must be written by a sharded store with the same `capacity`
+    /// and number of shards, because parts in other shards are stored shifted.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `shards` is empty or their ranges exceed the internal range of constants.
+    pub fn new(shards: Vec<S>, capacity: T) -> Result<Self, LinksError<T>> {
+        assert!(!shards.is_empty(), "at least one shard is required");
+        let max = shards[0].constants().internal_range.end().as_usize();
+        let total = capacity.as_usize().checked_mul(shards.len());
+        assert!(
+            capacity > T::funty(0) && matches!(total, Some(total) if total <= max),
+            "shard ranges exceed the internal range",
+        );
+
+        let counts: Vec<_> = shards.iter().map(|shard| shard.count_links(&[])).collect();
+        if counts.iter().any(|&count| count > capacity) {
+            return Err(LinksError::LimitReached(capacity));
+        }
+        let total = T::try_from(capacity.as_usize() * shards.len()).expect("always ok");
+        let shifts = (0..shards.len())
+            .map(|shard| Shift {
+                offset: T::try_from(shard * capacity.as_usize()).expect("always ok"),
+                total,
+            })
+            .collect();
+        Ok(Self {
+            shards,
+            capacity,
+            shifts,
+            counts,
+            duplicates: Duplicates::Allow,
+        })
+    }
+
+    /// Sets how changes making a link with the same source and target as another one
+    /// in any shard are treated.
+    #[must_use]
+    pub const fn with_duplicates(mut self, duplicates: Duplicates) -> Self {
+        self.duplicates = duplicates;
+        self
+    }
+
+    /// Returns how duplicates are treated, [`Duplicates::Allow`] by default.
+    #[must_use]
+    pub const fn duplicates(&self) -> Duplicates {
+        self.duplicates
+    }
+
+    #[must_use]
+    pub fn shards(&self) -> &[S] {
+        &self.shards
+    }
+
+    #[must_use]
+    pub fn into_shards(self) -> Vec<S> {
+        self.shards
+    }
+
+    #[must_use]
+    pub fn capacity(&self) -> T {
+        self.capacity
+    }
+
+    /// Returns the shard holding `index` and its local index.
+    #[must_use]
+    pub fn locate(&self, index: T) -> Option<(usize, T)> {
+        if index == T::funty(0) {
+            return None;
+        }
+        let shard = (index.as_usize() - 1) / self.capacity.as_usize();
+        self.shifts
+            .get(shard)
+            .map(|shift| (shard, index - shift.offset))
+    }
+
+    // another link with the same parts in any shard if duplicates are not allowed
+    fn find_duplicate(&self, index: T, source: T, target: T) -> Option<Link<T>> {
+        let zero = T::funty(0);
+        if self.duplicates == Duplicates::Allow || source == zero || target == zero {
+            return None;
+        }
+        self.search(source, target)
+            .filter(|&existing| existing != index)
+            .and_then(|existing| self.get_link(existing))
+    }
+
+    fn located(&self, query: &[T]) -> Option<T> {
+        query
+            .first()
+            .copied()
+            .filter(|&index| index != self.constants().any)
+    }
+}
+
+impl<T: LinkType, S: Doublets<T>> Links<T> for Store<T, S> {
+    fn constants(&self) -> &LinksConstants<T> {
+        self.shards[0].constants()
+    }
+
+    fn count_links(&self, query: &[T]) -> T {
+        if let Some(index) = self.located(query) {
+            return self.locate(index).map_or(T::funty(0), |(shard, _)| {
+                self.shards[shard].count_links(&self.shifts[shard].query(query))
+            });
+        }
+        self.shards
+            .iter()
+            .zip(&self.shifts)
+            .fold(T::funty(0), |count, (shard, shift)| {
+                count + shard.count_links(&shift.query(query))
+            })
+    }
+
+    fn create_links(
+        &mut self,
+        query: &[T],
+        handler: WriteHandler<'_, T>,
+    ) -> Result<Flow, LinksError<T>> {
+        if let [source, target] = *query {
+            if let Some(existing) = self.find_duplicate(T::funty(0), source, target) {
+                return self.duplicates.resolve(Link::nothing(), existing, handler);
+            }
+        }
+        let capacity = self.capacity;
+        let shard = self
+            .counts
+            .iter()
+            .position(|&count| count < capacity)
+            .ok_or_else(|| LinksError::LimitReached(self.shifts[self.shifts.len() - 1].total))?;
+        let shift = self.shifts[shard];
+        let flow = self.shards[shard]
+            .create_links(&shift.query(query), &mut |before, after| {
+                handler(shift.link(before), shift.link(after))
+            })
+            .map_err(|err| shift.err(err))?;
+        // a duplicate may be redirected to instead of creating a link
+        self.counts[shard] = self.shards[shard].count_links(&[]);
+        Ok(flow)
+    }
+
+    fn each_links(&self, query: &[T], handler: ReadHandler<'_, T>) -> Flow {
+        if let Some(index) = self.located(query) {
+            return self.locate(index).map_or(Flow::Continue, |(shard, _)| {
+                let shift = self.shifts[shard];
+                self.shards[shard]
+                    .each_links(&shift.query(query), &mut |link| handler(shift.link(link)))
+            });
+        }
+        for (shard, &shift) in self.shards.iter().zip(&self.shifts) {
+            let flow = shard.each_links(&shift.query(query), &mut |link| handler(shift.link(link)));
+            if let Flow::Break = flow {
+                return Flow::Break;
+            }
+        }
+        Flow::Continue
+    }
+
+    fn update_links(
+        &mut self,
+        query: &[T],
+        change: &[T],
+        handler: WriteHandler<'_, T>,
+    ) -> Result<Flow, LinksError<T>> {
+        let index = query[0];
+        let (shard, _) = self.locate(index).ok_or(LinksError::NotExists(index))?;
+        if let Some(existing) = self.find_duplicate(index, change[1], change[2]) {
+            let link = self.try_get_link(index)?;
+            return self.duplicates.resolve(link, existing, handler);
+        }
+        let shift = self.shifts[shard];
+        self.shards[shard]
+            .update_links(
+                &shift.query(query),
+                &shift.query(change),
+                &mut |before, after| handler(shift.link(before), shift.link(after)),
+            )
+            .map_err(|err| shift.err(err))
+    }
+
+    fn delete_links(
+        &mut self,
+        query: &[T],
+        handler: WriteHandler<'_, T>,
+    ) -> Result<Flow, LinksError<T>> {
+        let index = query[0];
+        let (shard, _) = self.locate(index).ok_or(LinksError::NotExists(index))?;
+        let shift = self.shifts[shard];
+        let flow = self.shards[shard]
+            .delete_links(&shift.query(query), &mut |before, after| {
+                handler(shift.link(before), shift.link(after))
+            })
+            .map_err(|err| shift.err(err))?;
+        self.counts[shard] -= T::funty(1);
+        Ok(flow)
+    }
+}
+
+impl<T: LinkType, S: Doublets<T>> Doublets<T> for Store<T, S> {
+    fn get_link(&self, index: T) -> Option<Link<T>> {
+        let (shard, local) = self.locate(index)?;
+        self.shards[shard]
+            .get_link(local)
+            .map(|link| self.shifts[shard].link(link))
+    }
+}
@@ -1,4 +1,4 @@
-use std::{mem::transmute, ptr::NonNull};
+use core::{mem::transmute, ops::Bound, ptr::NonNull};
 
 use crate::mem::{
     header::LinksHeader,
@@ -13,7 +13,7 @@ use crate::mem::{
 };
 
 use crate::{
-    mem::{SplitTree, SplitUpdateMem},
+    mem::{bulk::build_balanced, SplitTree, SplitUpdateMem, TreeStats},
     Link,
 };
 use data::{Flow, LinkType, LinksConstants};
@@ -37,19 +37,19 @@ impl<T: LinkType> ExternalSourcesRecursionlessTree<T> {
 
 impl<T: LinkType> SzbTree<T> for ExternalSourcesRecursionlessTree<T> {
     unsafe fn get_left_reference(&self, node: T) -> *const T {
-        std::ptr::addr_of!(self.get_index_part(node).left_as_source)
+        core::ptr::addr_of!(self.get_index_part(node).left_as_source)
     }
 
     unsafe fn get_right_reference(&self, node: T) -> *const T {
-        std::ptr::addr_of!(self.get_index_part(node).right_as_source)
+        core::ptr::addr_of!(self.get_index_part(node).right_as_source)
     }
 
     unsafe fn get_mut_left_reference(&mut self, node: T) -> *mut T {
-        std::ptr::addr_of_mut!(self.get_mut_index_part(node).left_as_source)
+        core::ptr::addr_of_mut!(self.get_mut_index_part(node).left_as_source)
     }
 
     unsafe fn get_mut_right_reference(&mut self, node: T) -> *mut T {
-        std::ptr::addr_of_mut!(self.get_mut_index_part(node).right_as_source)
+        core::ptr::addr_of_mut!(self.get_mut_index_part(node).right_as_source)
     }
 
     unsafe fn get_left(&self, node: T) -> T {
@@ -77,25 +77,11 @@ impl<T: LinkType> SzbTree<T> for ExternalSourcesRecursionlessTree<T> {
     }
 
     unsafe fn first_is_to_the_left_of_second(&self, first: T, second: T) -> bool {
-        let first = self.get_data_part(first);
-        let second = self.get_data_part(second);
-        self.first_is_to_the_left_of_second_4(
-            first.source,
-            first.target,
-            second.source,
-            second.target,
-        )
+        self.node_order(first) < self.node_order(second)
     }
 
     unsafe fn first_is_to_the_right_of_second(&self, first: T, second: T) -> bool {
-        let first = self.get_data_part(first);
-        let second = self.get_data_part(second);
-        self.first_is_to_the_right_of_second_4(
-            first.source,
-            first.target,
-            second.source,
-            second.target,
-        )
+        self.node_order(first) > self.node_order(second)
     }
 
     unsafe fn clear_node(&mut self, node: T) {
@@ -190,6 +176,43 @@ impl<T: LinkType> LinksTree<T> for ExternalSourcesRecursionlessTree<T> {
         each_usages_core(self, root, self.get_tree_root(), handler)
     }
 
+    fn nth_usage(&self, root: T, n: T) -> T {
+        self.nth_usage_core(root, n)
+    }
+
+    fn usage_rank(&self, root: T, index: T) -> T {
+        self.usage_rank_core(root, index)
+    }
+
+    fn each_usages_range<H: FnMut(Link<T>) -> Flow + ?Sized>(
+        &self,
+        root: T,
+        range: (Bound<T>, Bound<T>),
+        handler: &mut H,
+    ) -> Flow {
+        self.each_usages_range_core(self.get_tree_root(), root, range, handler)
+    }
+
+    fn count_range(&self, range: (Bound<T>, Bound<T>)) -> T {
+        self.count_range_core(range)
+    }
+
+    fn each_range<H: FnMut(Link<T>) -> Flow + ?Sized>(
+        &self,
+        range: (Bound<T>, Bound<T>),
+        handler: &mut H,
+    ) -> Flow {
+        self.each_range_core(self.get_tree_root(), range, handler)
+    }
+
+    fn rebuild(&mut self, root: &mut T, nodes: &[T]) {
+        *root = unsafe { build_balanced(self, nodes) };
+    }
+
+    fn stats(&self, root: T) -> TreeStats {
+        unsafe { TreeStats::of(self, root) }
+    }
+
     fn detach(&mut self, root: &mut T, index: T) {
         unsafe { NoRecurSzbTree::detach(self, root as *mut _, index) }
     }
@@ -243,6 +266,10 @@ impl<T: LinkType> ExternalRecursionlessSizeBalancedTreeBaseAbstract<T>
         self.get_data_part(link).source
     }
 
+    fn get_key_part(&self, link: T) -> T {
+        self.get_data_part(link).target
+    }
+
     fn first_is_to_the_left_of_second_4(
         &self,
         first_source: T,
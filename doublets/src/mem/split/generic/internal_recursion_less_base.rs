@@ -1,12 +1,19 @@
-use std::ptr::NonNull;
+use core::{
+    mem::transmute,
+    ops::{Bound, RangeBounds, RangeInclusive},
+    ptr::NonNull,
+};
 
 use crate::mem::traits::LinksTree;
 
 use crate::{
-    mem::split::{DataPart, IndexPart},
+    mem::{
+        header::LinksHeader,
+        split::{DataPart, IndexPart},
+    },
     Link,
 };
-use data::{LinkType, LinksConstants};
+use data::{Flow, LinkType, LinksConstants};
 use trees::NoRecurSzbTree;
 
 // TODO: why is there so much duplication in OOP!!! FIXME
@@ -49,6 +56,10 @@ pub(crate) trait InternalRecursionlessSizeBalancedTreeBaseAbstract<T: LinkType>:
 
     fn get_key_part(&self, link: T) -> T;
 
+    fn get_header(&self) -> &LinksHeader<T> {
+        unsafe { transmute(self.get_index_part(T::funty(0))) }
+    }
+
     fn get_link_value(&self, index: T) -> Link<T> {
         let link = self.get_data_part(index);
         Link::new(index, link.source, link.target)
@@ -73,4 +84,122 @@ pub(crate) trait InternalRecursionlessSizeBalancedTreeBaseAbstract<T: LinkType>:
     fn count_usages_core(&self, link: T) -> T {
         unsafe { self.get_size_or_zero(self.get_tree_root(link)) }
     }
+
+    fn nth_usage_core(&self, mut root: T, mut n: T) -> T {
+        unsafe {
+            while root != T::funty(0) {
+                let left = self.get_left_size(root);
+                if n < left {
+                    root = self.get_left_or_default(root);
+                } else if n > left {
+                    n -= left + T::funty(1);
+                    root = self.get_right_or_default(root);
+                } else {
+                    return root;
+                }
+            }
+            T::funty(0)
+        }
+    }
+
+    fn usage_rank_core(&self, mut root: T, link: T) -> T {
+        let node = (self.get_key_part(link), link);
+        let mut rank = T::funty(0);
+        unsafe {
+            while root != T::funty(0) {
+                if (self.get_key_part(root), root) < node {
+                    rank += self.get_left_size(root) + T::funty(1);
+                    root = self.get_right_or_default(root);
+                } else {
+                    root = self.get_left_or_default(root);
+                }
+            }
+        }
+        rank
+    }
+
+    fn each_usages_range_core<H: FnMut(Link<T>) -> Flow + ?Sized>(
+        &self,
+        link: T,
+        range: (Bound<T>, Bound<T>),
+        handler: &mut H,
+    ) -> Flow {
+        if link == T::funty(0) {
+            return Flow::Continue;
+        }
+        let key = self.get_key_part(link);
+        let to_left = match range.0 {
+            Bound::Unbounded => true,
+            Bound::Included(from) => key >= from,
+            Bound::Excluded(from) => key > from,
+        };
+        let to_right = match range.1 {
+            Bound::Unbounded => true,
+            Bound::Included(to) => key <= to,
+            Bound::Excluded(to) => key < to,
+        };
+        unsafe {
+            if to_left {
+                let left = self.get_left_or_default(link);
+                self.each_usages_range_core(left, range, handler)?;
+            }
+            if range.contains(&key) {
+                handler(self.get_link_value(link))?;
+            }
+            if to_right {
+                let right = self.get_right_or_default(link);
+                self.each_usages_range_core(right, range, handler)?;
+            }
+        }
+        Flow::Continue
+    }
+
+    // every internal tree is rooted in its base link, so only allocated links are visited
+    fn roots_core(&self, (start, end): (Bound<T>, Bound<T>)) -> RangeInclusive<T> {
+        let allocated = self.get_header().allocated;
+        let from = match start {
+            Bound::Unbounded => T::funty(1),
+            Bound::Included(from) => from.max(T::funty(1)),
+            Bound::Excluded(from) if from < allocated => from + T::funty(1),
+            Bound::Excluded(_) => return T::funty(1)..=T::funty(0),
+        };
+        let to = match end {
+            Bound::Unbounded => allocated,
+            Bound::Included(to) => to.min(allocated),
+            Bound::Excluded(to) if to > T::funty(0) => (to - T::funty(1)).min(allocated),
+            Bound::Excluded(_) => return T::funty(1)..=T::funty(0),
+        };
+        from..=to
+    }
+
+    // the size of each tree is read from its root, so links themselves are not visited
+    fn count_range_core(&self, range: (Bound<T>, Bound<T>)) -> T {
+        let mut count = T::funty(0);
+        for root in self.roots_core(range) {
+            count += self.count_usages_core(root);
+        }
+        count
+    }
+
+    fn each_tree_core<H: FnMut(Link<T>) -> Flow + ?Sized>(&self, link: T, handler: &mut H) -> Flow {
+        if link == T::funty(0) {
+            return Flow::Continue;
+        }
+        unsafe {
+            self.each_tree_core(self.get_left_or_default(link), handler)?;
+            handler(self.get_link_value(link))?;
+            self.each_tree_core(self.get_right_or_default(link), handler)
+        }
+    }
+
+    fn each_range_core<H: FnMut(Link<T>) -> Flow + ?Sized>(
+        &self,
+        range: (Bound<T>, Bound<T>),
+        handler: &mut H,
+    ) -> Flow {
+        for root in self.roots_core(range) {
+            self.each_tree_core(self.get_tree_root(root), handler)?;
+        }
+        Flow::Continue
+    }
 }
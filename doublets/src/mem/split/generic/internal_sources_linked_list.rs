@@ -1,8 +1,8 @@
 use crate::Link;
+use core::mem::transmute;
 use data::{Flow, LinksConstants};
-use std::mem::transmute;
 
-use std::ptr::NonNull;
+use core::ptr::NonNull;
 
 use crate::mem::{
     header::LinksHeader,
@@ -1,4 +1,4 @@
-use std::ptr::NonNull;
+use core::{ops::Bound, ptr::NonNull};
 
 use crate::mem::traits::LinksTree;
 
@@ -11,7 +11,7 @@ use crate::mem::split::{
 };
 
 use crate::{
-    mem::{SplitTree, SplitUpdateMem},
+    mem::{bulk::build_balanced, SplitTree, SplitUpdateMem, TreeStats},
     Link,
 };
 use data::{Flow, LinkType, LinksConstants};
@@ -35,19 +35,19 @@ impl<T: LinkType> InternalTargetsRecursionlessTree<T> {
 
 impl<T: LinkType> SzbTree<T> for InternalTargetsRecursionlessTree<T> {
     unsafe fn get_left_reference(&self, node: T) -> *const T {
-        std::ptr::addr_of!(self.get_index_part(node).left_as_target)
+        core::ptr::addr_of!(self.get_index_part(node).left_as_target)
     }
 
     unsafe fn get_right_reference(&self, node: T) -> *const T {
-        std::ptr::addr_of!(self.get_index_part(node).right_as_target)
+        core::ptr::addr_of!(self.get_index_part(node).right_as_target)
     }
 
     unsafe fn get_mut_left_reference(&mut self, node: T) -> *mut T {
-        std::ptr::addr_of_mut!(self.get_mut_index_part(node).left_as_target)
+        core::ptr::addr_of_mut!(self.get_mut_index_part(node).left_as_target)
     }
 
     unsafe fn get_mut_right_reference(&mut self, node: T) -> *mut T {
-        std::ptr::addr_of_mut!(self.get_mut_index_part(node).right_as_target)
+        core::ptr::addr_of_mut!(self.get_mut_index_part(node).right_as_target)
     }
 
     unsafe fn get_left(&self, node: T) -> T {
@@ -74,12 +74,13 @@ impl<T: LinkType> SzbTree<T> for InternalTargetsRecursionlessTree<T> {
         self.get_mut_index_part(node).size_as_target = size;
     }
 
+    // equal links are ordered by index, so each node has its own place in the tree
     unsafe fn first_is_to_the_left_of_second(&self, first: T, second: T) -> bool {
-        self.get_key_part(first) < self.get_key_part(second)
+        (self.get_key_part(first), first) < (self.get_key_part(second), second)
     }
 
     unsafe fn first_is_to_the_right_of_second(&self, first: T, second: T) -> bool {
-        self.get_key_part(first) > self.get_key_part(second)
+        (self.get_key_part(first), first) > (self.get_key_part(second), second)
     }
 
     unsafe fn clear_node(&mut self, node: T) {
@@ -129,6 +130,43 @@ impl<T: LinkType> LinksTree<T> for InternalTargetsRecursionlessTree<T> {
         each_usages_core(self, root, self.get_tree_root(root), handler)
     }
 
+    fn nth_usage(&self, root: T, n: T) -> T {
+        self.nth_usage_core(self.get_tree_root(root), n)
+    }
+
+    fn usage_rank(&self, root: T, index: T) -> T {
+        self.usage_rank_core(self.get_tree_root(root), index)
+    }
+
+    fn each_usages_range<H: FnMut(Link<T>) -> Flow + ?Sized>(
+        &self,
+        root: T,
+        range: (Bound<T>, Bound<T>),
+        handler: &mut H,
+    ) -> Flow {
+        self.each_usages_range_core(self.get_tree_root(root), range, handler)
+    }
+
+    fn count_range(&self, range: (Bound<T>, Bound<T>)) -> T {
+        self.count_range_core(range)
+    }
+
+    fn each_range<H: FnMut(Link<T>) -> Flow + ?Sized>(
+        &self,
+        range: (Bound<T>, Bound<T>),
+        handler: &mut H,
+    ) -> Flow {
+        self.each_range_core(range, handler)
+    }
+
+    fn rebuild(&mut self, root: &mut T, nodes: &[T]) {
+        *root = unsafe { build_balanced(self, nodes) };
+    }
+
+    fn stats(&self, root: T) -> TreeStats {
+        unsafe { TreeStats::of(self, root) }
+    }
+
     fn detach(&mut self, root: &mut T, index: T) {
         unsafe { NoRecurSzbTree::detach(self, root as *mut _, index) }
     }
@@ -1,4 +1,4 @@
-use std::{mem::transmute, ptr::NonNull};
+use core::{mem::transmute, ptr::NonNull};
 
 use crate::{
     mem::{header::LinksHeader, split::DataPart, traits::SplitList, LinksList, SplitUpdateMem},
@@ -1,20 +1,27 @@
-use std::{cmp::Ordering, default::default, error::Error, mem::transmute, ptr::NonNull};
+use core::{cmp::Ordering, mem::transmute, ptr::NonNull};
 
 use crate::{
     mem::{
+        bulk::create_many_reserved,
         split::{
             DataPart, ExternalSourcesRecursionlessTree, ExternalTargetsRecursionlessTree,
             IndexPart, InternalSourcesLinkedList, InternalSourcesRecursionlessTree,
             InternalTargetsRecursionlessTree, UnusedLinks,
         },
-        LinksHeader, LinksTree, SplitList, SplitTree, SplitUpdateMem,
+        Duplicates, LinksHeader, LinksTree, SplitList, SplitTree, SplitUpdateMem,
     },
-    Doublets, DoubletsExt, Link, Links, LinksError, ReadHandler, WriteHandler,
+    Doublets, DoubletsExt, Link, Links, LinksError, PartRange, ReadHandler, WriteHandler,
 };
 use data::{Flow, LinkType, LinksConstants, ToQuery};
 use mem::{RawMem, DEFAULT_PAGE_SIZE};
 use trees::RelativeCircularLinkedList;
 
+mod bulk;
+mod ranges;
+mod read_only;
+mod stats;
+mod unique;
+
 pub struct Store<
     T: LinkType,
     MD: RawMem<DataPart<T>>,
@@ -35,6 +42,8 @@ pub struct Store<
     index_step: usize,
 
     constants: LinksConstants<T>,
+    read_only: bool,
+    duplicates: Duplicates,
 
     internal_sources: IS,
     pub external_sources: ES,
@@ -68,8 +77,17 @@ impl<
         index_mem: MI,
         constants: LinksConstants<T>,
     ) -> Result<Store<T, MD, MI>, LinksError<T>> {
-        let dangling_data = NonNull::slice_from_raw_parts(NonNull::dangling(), 0);
-        let dangling_index = NonNull::slice_from_raw_parts(NonNull::dangling(), 0);
+        Self::open(data_mem, index_mem, constants, false)
+    }
+
+    fn open(
+        data_mem: MD,
+        index_mem: MI,
+        constants: LinksConstants<T>,
+        read_only: bool,
+    ) -> Result<Store<T, MD, MI>, LinksError<T>> {
+        let dangling_data = NonNull::from(&mut [][..]);
+        let dangling_index = NonNull::from(&mut [][..]);
 
         let internal_sources =
             InternalSourcesRecursionlessTree::new(constants.clone(), dangling_data, dangling_index);
@@ -93,6 +111,8 @@ impl<
             data_step: Self::SIZE_STEP,
             index_step: Self::SIZE_STEP,
             constants,
+            read_only,
+            duplicates: Duplicates::Allow,
             internal_sources,
             external_sources,
             internal_targets,
@@ -103,13 +123,17 @@ impl<
 
         // SAFETY: Without this, the code will become unsafe
         unsafe {
-            new.init()?;
+            if read_only {
+                new.init_read_only()?;
+            } else {
+                new.init()?;
+            }
         }
         Ok(new)
     }
 
     pub fn new(data_mem: MD, index_mem: MI) -> Result<Store<T, MD, MI>, LinksError<T>> {
-        Self::with_constants(data_mem, index_mem, default())
+        Self::with_constants(data_mem, index_mem, LinksConstants::new())
     }
 
     fn mut_from_mem<'a, U>(mut ptr: NonNull<[U]>, index: usize) -> Option<&'a mut U> {
@@ -128,7 +152,8 @@ impl<
         Self::mut_from_mem(ptr, index).map(|v| &*v)
     }
 
-    fn get_header(&self) -> &LinksHeader<T> {
+    /// Returns the header with counters of allocated, reserved and free links.
+    pub fn get_header(&self) -> &LinksHeader<T> {
         // SAFETY: `LinksHeader` and `IndexPart` layout are equivalent
         unsafe {
             Self::get_from_mem(self.index_ptr, 0)
@@ -304,6 +329,7 @@ impl<
         self.update_mem(data, index);
 
         self.mut_header().reserved = T::try_from(self.data_mem.allocated() - 1).expect("always ok");
+        self.upgrade_trees();
         Ok(())
     }
 
@@ -418,33 +444,7 @@ impl<
                         self.internal_sources.each_usages(source, handler)
                     }
                 } else {
-                    let link = if true {
-                        if is_virtual_source && is_virtual_target {
-                            self.external_sources.search(source, target)
-                        } else if is_virtual_source {
-                            self.internal_targets.search(source, target)
-                        } else if is_virtual_target {
-                            if Self::USE_LIST {
-                                self.external_sources.search(source, target)
-                            } else {
-                                self.internal_sources.search(source, target)
-                            }
-                        } else if Self::USE_LIST
-                            || self.internal_sources.count_usages(source)
-                                > self.internal_targets.count_usages(target)
-                        {
-                            self.internal_targets.search(source, target)
-                        } else {
-                            self.internal_sources.search(source, target)
-                        }
-                    } else if Self::USE_LIST
-                        || self.internal_sources.count_usages(source)
-                            > self.internal_targets.count_usages(target)
-                    {
-                        self.internal_targets.search(source, target)
-                    } else {
-                        self.internal_sources.search(source, target)
-                    };
+                    let link = self.search_doublet(source, target);
                     return if link == constants.null {
                         Flow::Continue
                     } else {
@@ -622,33 +622,7 @@ impl<
                         self.internal_sources.count_usages(source)
                     }
                 } else {
-                    let link = if true {
-                        if is_virtual_source && is_virtual_target {
-                            self.external_sources.search(source, target)
-                        } else if is_virtual_source {
-                            self.internal_targets.search(source, target)
-                        } else if is_virtual_target {
-                            if Self::USE_LIST {
-                                self.external_sources.search(source, target)
-                            } else {
-                                self.internal_sources.search(source, target)
-                            }
-                        } else if Self::USE_LIST
-                            || self.internal_sources.count_usages(source)
-                                > self.internal_targets.count_usages(target)
-                        {
-                            self.internal_targets.search(source, target)
-                        } else {
-                            self.internal_sources.search(source, target)
-                        }
-                    } else if Self::USE_LIST
-                        || self.internal_sources.count_usages(source)
-                            > self.internal_targets.count_usages(target)
-                    {
-                        self.internal_targets.search(source, target)
-                    } else {
-                        self.internal_sources.search(source, target)
-                    };
+                    let link = self.search_doublet(source, target);
                     return if link == constants.null {
                         T::funty(0)
                     } else {
@@ -690,9 +664,13 @@ impl<
 
     fn create_links(
         &mut self,
-        _query: &[T],
+        query: &[T],
         handler: WriteHandler<'_, T>,
     ) -> Result<Flow, LinksError<T>> {
+        self.check_writable()?;
+        if let Some(existing) = self.created_duplicate(query) {
+            return self.duplicates.resolve(Link::nothing(), existing, handler);
+        }
         let constants = self.constants().clone();
         let header = self.get_header();
         let mut free = header.first_free;
@@ -743,11 +721,16 @@ impl<
         change: &[T],
         handler: WriteHandler<'_, T>,
     ) -> Result<Flow, LinksError<T>> {
+        self.check_writable()?;
         let index = query[0];
         let new_source = change[1];
         let new_target = change[2];
 
         let link = self.try_get_link(index)?;
+        if let Some(existing) = self.find_duplicate(index, new_source, new_target) {
+            let existing = self.try_get_link(existing)?;
+            return self.duplicates.resolve(link, existing, handler);
+        }
 
         if link.source != T::funty(0) {
             // SAFETY: Here index attach to source
@@ -809,11 +792,22 @@ impl<
         Ok(handler(link, Link::new(index, place.source, place.target)))
     }
 
+    fn create_many(
+        &mut self,
+        count: T,
+        handler: WriteHandler<'_, T>,
+    ) -> Result<Flow, LinksError<T>> {
+        self.check_writable()?;
+        let header = self.get_header().clone();
+        create_many_reserved(self, header, count, Self::reserve_links, handler)
+    }
+
     fn delete_links(
         &mut self,
         query: &[T],
         handler: WriteHandler<'_, T>,
     ) -> Result<Flow, LinksError<T>> {
+        self.check_writable()?;
         let index = query[0];
         let link = self.try_get_link(index)?;
 
@@ -867,6 +861,96 @@ impl<
             None
         }
     }
+
+    fn each_from(&self, start: T, handler: ReadHandler<'_, T>) -> Flow {
+        for index in start.max(T::funty(1))..=self.get_header().allocated {
+            if let Some(link) = self.get_link(index) {
+                handler(link)?;
+            }
+        }
+        Flow::Continue
+    }
+
+    fn nth_usage_as_source(&self, source: T, n: T) -> Option<Link<T>> {
+        self.get_link(if self.is_virtual(source) {
+            self.external_sources.nth_usage(source, n)
+        } else {
+            self.internal_sources.nth_usage(source, n)
+        })
+    }
+
+    fn nth_usage_as_target(&self, target: T, n: T) -> Option<Link<T>> {
+        self.get_link(if self.is_virtual(target) {
+            self.external_targets.nth_usage(target, n)
+        } else {
+            self.internal_targets.nth_usage(target, n)
+        })
+    }
+
+    fn usage_rank_as_source(&self, index: T) -> Option<T> {
+        let source = self.get_link(index)?.source;
+        (source != T::funty(0)).then(|| {
+            if self.is_virtual(source) {
+                self.external_sources.usage_rank(source, index)
+            } else {
+                self.internal_sources.usage_rank(source, index)
+            }
+        })
+    }
+
+    fn usage_rank_as_target(&self, index: T) -> Option<T> {
+        let target = self.get_link(index)?.target;
+        (target != T::funty(0)).then(|| {
+            if self.is_virtual(target) {
+                self.external_targets.usage_rank(target, index)
+            } else {
+                self.internal_targets.usage_rank(target, index)
+            }
+        })
+    }
+
+    fn each_usages_as_source_in(
+        &self,
+        source: T,
+        targets: PartRange<T>,
+        handler: ReadHandler<'_, T>,
+    ) -> Flow {
+        if self.is_virtual(source) {
+            self.external_sources
+                .each_usages_range(source, targets, handler)
+        } else {
+            self.internal_sources
+                .each_usages_range(source, targets, handler)
+        }
+    }
+
+    fn each_usages_as_target_in(
+        &self,
+        target: T,
+        sources: PartRange<T>,
+        handler: ReadHandler<'_, T>,
+    ) -> Flow {
+        if self.is_virtual(target) {
+            self.external_targets
+                .each_usages_range(target, sources, handler)
+        } else {
+            self.internal_targets
+                .each_usages_range(target, sources, handler)
+        }
+    }
+
+    fn count_in_ranges(&self, sources: PartRange<T>, targets: PartRange<T>) -> T {
+        self.count_in_ranges_core(sources, targets)
+    }
+
+    fn each_in_ranges(
+        &self,
+        sources: PartRange<T>,
+        targets: PartRange<T>,
+        handler: ReadHandler<'_, T>,
+    ) -> Flow {
+        self.each_in_ranges_core(sources, targets, handler)
+    }
 }
 
 unsafe impl<
@@ -0,0 +1,198 @@
+use alloc::vec::Vec;
+use core::{cmp, ops::Range, ptr::NonNull};
+
+use super::Store;
+use crate::{
+    mem::{
+        bulk::{check_load, sort_nodes},
+        split::{DataPart, IndexPart},
+        unique::check_unique,
+        Duplicates, SplitList, SplitTree,
+    },
+    Doublets, LinksError,
+};
+use data::LinkType;
+use mem::RawMem;
+
+// splits nodes sorted by base part into `(base, nodes range)` groups
+fn groups<T: LinkType>(nodes: &[T], base: impl Fn(T) -> T) -> Vec<(T, Range<usize>)> {
+    let mut groups: Vec<(T, Range<usize>)> = Vec::new();
+    for (i, &node) in nodes.iter().enumerate() {
+        let base = base(node);
+        match groups.last_mut() {
+            Some((last, range)) if *last == base => range.end = i + 1,
+            _ => groups.push((base, i..i + 1)),
+        }
+    }
+    groups
+}
+
+impl<
+    T: LinkType,
+    MD: RawMem<DataPart<T>>,
+    MI: RawMem<IndexPart<T>>,
+    IS: SplitTree<T>,
+    ES: SplitTree<T>,
+    IT: SplitTree<T>,
+    ET: SplitTree<T>,
+    UL: SplitList<T>,
+> Store<T, MD, MI, IS, ES, IT, ET, UL>
+{
+    /// Appends links with the given `(source, target)` parts and returns their indices.
+    ///
+    /// Records are written sequentially after the allocated ones, so free links are not reused.
+    /// When the batch is at least as large as the store, all trees are built bottom-up
+    /// from all links in one pass, which is much faster than attaching links one by one,
+    /// otherwise only the new links are attached.
+    /// Nothing is written unless all links can be loaded: the capacity, the parts and,
+    /// unless duplicates are allowed, the uniqueness of the links are checked first,
+    /// and a duplicate fails with [`LinksError::AlreadyExists`] even with [`Duplicates::Redirect`].
+    pub fn bulk_load(
+        &mut self,
+        links: impl IntoIterator<Item = (T, T)>,
+    ) -> Result<Range<T>, LinksError<T>> {
+        self.check_writable()?;
+        let links: Vec<_> = links.into_iter().collect();
+        let allocated = self.get_header().allocated;
+        check_load(&links, allocated, &self.constants)?;
+        if self.duplicates != Duplicates::Allow {
+            check_unique(&links, |source, target| self.search_doublet(source, target))?;
+        }
+        self.reserve_links(links.len())?;
+
+        let start = allocated + T::funty(1);
+        if links.len() >= allocated.as_usize() {
+            let mut nodes: Vec<_> = (T::funty(1)..start)
+                .filter(|&index| self.exists(index))
+                .collect();
+            for (source, target) in links {
+                nodes.push(self.push_link(DataPart { source, target }));
+            }
+            self.rebuild_trees(start, nodes);
+        } else {
+            // all links are allocated first, so the ones referring to later links
+            // are attached to internal trees rather than moved there afterwards
+            let mut index = allocated;
+            for _ in 0..links.len() {
+                index = self.push_link(DataPart::default());
+                self.resolve_danglind_external(index);
+            }
+            for (index, (source, target)) in (start..=index).zip(links) {
+                // the link exists and is not a duplicate, so it is updated as is
+                self.update(index, source, target)?;
+            }
+        }
+        Ok(start..self.get_header().allocated + T::funty(1))
+    }
+
+    // writes the link after the allocated ones, which must be reserved
+    fn push_link(&mut self, part: DataPart<T>) -> T {
+        let header = self.mut_header();
+        header.allocated += T::funty(1);
+        let index = header.allocated;
+        *self.mut_data_part(index) = part;
+        *self.mut_index_part(index) = IndexPart::default();
+        index
+    }
+
+    // grows both memories so that `count` more links fit in them
+    pub(super) fn reserve_links(&mut self, count: usize) -> Result<(), LinksError<T>> {
+        let header = self.get_header();
+        let required = header.allocated.as_usize() + count;
+        if required >= header.reserved.as_usize() {
+            let data = NonNull::from(self.data_mem.alloc(cmp::max(
+                self.data_mem.allocated() + self.data_step,
+                required + 1,
+            ))?);
+            let index = NonNull::from(self.index_mem.alloc(cmp::max(
+                self.index_mem.allocated() + self.index_step,
+                required + 1,
+            ))?);
+            self.update_mem(data, index);
+            let reserved = self.index_mem.allocated();
+            let header = self.mut_header();
+            header.reserved = T::try_from(reserved).expect("always ok");
+        }
+        Ok(())
+    }
+
+    // trees of older versions order equal links differently, so they are built again
+    pub(super) fn upgrade_trees(&mut self) {
+        if !self.get_header().is_current() {
+            let allocated = self.get_header().allocated;
+            let nodes = (T::funty(1)..=allocated)
+                .filter(|&index| self.exists(index))
+                .collect();
+            self.rebuild_trees(allocated + T::funty(1), nodes);
+            self.mut_header().set_current();
+        }
+    }
+
+    fn rebuild_trees(&mut self, start: T, nodes: Vec<T>) {
+        let allocated = self.get_header().allocated;
+        // loaded links are not attached yet, so they are taken as they will be after it:
+        // only links with source and without target are virtual
+        let is_virtual = |value: T| {
+            if value >= start && value <= allocated {
+                let part = self.get_data_part(value);
+                part.target == T::funty(0) && part.source != T::funty(0)
+            } else {
+                self.is_virtual(value)
+            }
+        };
+
+        let (mut sources, mut targets): (Vec<_>, Vec<_>) = (
+            nodes
+                .iter()
+                .copied()
+                .filter(|&index| self.get_data_part(index).source != T::funty(0))
+                .collect(),
+            nodes
+                .into_iter()
+                .filter(|&index| self.get_data_part(index).target != T::funty(0))
+                .collect(),
+        );
+        sort_nodes(&mut sources, |index| {
+            let part = self.get_data_part(index);
+            (part.source, part.target)
+        });
+        sort_nodes(&mut targets, |index| {
+            let part = self.get_data_part(index);
+            (part.target, part.source)
+        });
+
+        let (external_sources, internal_sources): (Vec<_>, Vec<_>) = sources
+            .into_iter()
+            .partition(|&index| is_virtual(self.get_data_part(index).source));
+        let (external_targets, internal_targets): (Vec<_>, Vec<_>) = targets
+            .into_iter()
+            .partition(|&index| is_virtual(self.get_data_part(index).target));
+        let sources_groups = groups(&internal_sources, |index| self.get_data_part(index).source);
+        let targets_groups = groups(&internal_targets, |index| self.get_data_part(index).target);
+
+        for index in T::funty(1)..=allocated {
+            let part = self.mut_index_part(index);
+            part.root_as_source = T::funty(0);
+            part.root_as_target = T::funty(0);
+        }
+
+        // SAFETY: roots are stored apart from the node fields written by rebuilding
+        unsafe {
+            let root = self.mut_source_header_root();
+            self.external_sources.rebuild(&mut *root, &external_sources);
+            let root = self.mut_target_header_root();
+            self.external_targets.rebuild(&mut *root, &external_targets);
+
+            for (base, range) in sources_groups {
+                let root = self.mut_source_root(base);
+                self.internal_sources
+                    .rebuild(&mut *root, &internal_sources[range]);
+            }
+            for (base, range) in targets_groups {
+                let root = self.mut_target_root(base);
+                self.internal_targets
+                    .rebuild(&mut *root, &internal_targets[range]);
+            }
+        }
+    }
+}
@@ -0,0 +1,105 @@
+use core::ops::Bound;
+
+use super::Store;
+use crate::{
+    data::{is_any, matches_link},
+    mem::{
+        split::{DataPart, IndexPart},
+        LinksTree, SplitList, SplitTree,
+    },
+    Link, Links, PartRange, ReadHandler,
+};
+use data::{Flow, LinkType};
+use mem::RawMem;
+
+// internal and external trees indexing the same part of links
+type Trees<'a, I, E> = (&'a I, &'a E);
+
+fn count_part<T, I, E>((internal, external): Trees<'_, I, E>, range: PartRange<T>) -> T
+where
+    T: LinkType,
+    I: LinksTree<T>,
+    E: LinksTree<T>,
+{
+    internal.count_range(range) + external.count_range(range)
+}
+
+// internal trees are visited link by link, so external links with lesser
+// or equal base part are flushed before each internal one to keep the order
+fn each_part<T, I, E>(
+    (internal, external): Trees<'_, I, E>,
+    base: fn(&Link<T>) -> T,
+    range: PartRange<T>,
+    handler: ReadHandler<'_, T>,
+) -> Flow
+where
+    T: LinkType,
+    I: LinksTree<T>,
+    E: LinksTree<T>,
+{
+    let mut flushed = range.0;
+    internal.each_range(range, &mut |link| {
+        let part = base(&link);
+        external.each_range((flushed, Bound::Included(part)), handler)?;
+        flushed = Bound::Excluded(part);
+        handler(link)
+    })?;
+    external.each_range((flushed, range.1), handler)
+}
+
+impl<
+    T: LinkType,
+    MD: RawMem<DataPart<T>>,
+    MI: RawMem<IndexPart<T>>,
+    IS: SplitTree<T>,
+    ES: SplitTree<T>,
+    IT: SplitTree<T>,
+    ET: SplitTree<T>,
+    UL: SplitList<T>,
+> Store<T, MD, MI, IS, ES, IT, ET, UL>
+{
+    pub(super) fn count_in_ranges_core(&self, sources: PartRange<T>, targets: PartRange<T>) -> T {
+        match (is_any(&sources), is_any(&targets)) {
+            (true, true) => self.count_links(&[]),
+            (false, true) => count_part((&self.internal_sources, &self.external_sources), sources),
+            (true, false) => count_part((&self.internal_targets, &self.external_targets), targets),
+            (false, false) => {
+                let mut count = T::funty(0);
+                self.each_in_ranges_core(sources, targets, &mut |_| {
+                    count += T::funty(1);
+                    Flow::Continue
+                });
+                count
+            }
+        }
+    }
+
+    pub(super) fn each_in_ranges_core(
+        &self,
+        sources: PartRange<T>,
+        targets: PartRange<T>,
+        handler: ReadHandler<'_, T>,
+    ) -> Flow {
+        if is_any(&sources) && is_any(&targets) {
+            return self.each_links(&[], handler);
+        }
+        let sources_trees = (&self.internal_sources, &self.external_sources);
+        let targets_trees = (&self.internal_targets, &self.external_targets);
+        let handler = &mut |link: Link<T>| {
+            if matches_link(&sources, &targets, &link) {
+                handler(link)
+            } else {
+                Flow::Continue
+            }
+        };
+        // walk the trees of the more selective part and filter by the other one
+        if is_any(&targets)
+            || (!is_any(&sources)
+                && count_part(sources_trees, sources) <= count_part(targets_trees, targets))
+        {
+            each_part(sources_trees, |link| link.source, sources, handler)
+        } else {
+            each_part(targets_trees, |link| link.target, targets, handler)
+        }
+    }
+}
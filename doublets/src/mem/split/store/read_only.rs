@@ -0,0 +1,85 @@
+use core::{cmp, ptr::NonNull};
+
+use super::Store;
+use crate::{
+    mem::{
+        split::{DataPart, IndexPart},
+        SplitList, SplitTree,
+    },
+    LinksError,
+};
+use data::{LinkType, LinksConstants};
+use mem::RawMem;
+
+impl<
+    T: LinkType,
+    MD: RawMem<DataPart<T>>,
+    MI: RawMem<IndexPart<T>>,
+    IS: SplitTree<T>,
+    ES: SplitTree<T>,
+    IT: SplitTree<T>,
+    ET: SplitTree<T>,
+    UL: SplitList<T>,
+> Store<T, MD, MI, IS, ES, IT, ET, UL>
+{
+    /// Opens links of `data_mem` and `index_mem` without changing them,
+    /// e.g. of [`ReadOnlyMapped`] files.
+    ///
+    /// The header is neither written nor the memories grown,
+    /// and changing methods return [`LinksError::ReadOnly`].
+    /// Files with trees of an older version fail with [`LinksError::ReadOnly`],
+    /// since their trees are rebuilt only when opened with [`Store::new`].
+    ///
+    /// [`ReadOnlyMapped`]: crate::mem::ReadOnlyMapped
+    pub fn read_only(data_mem: MD, index_mem: MI) -> Result<Store<T, MD, MI>, LinksError<T>> {
+        Self::read_only_with_constants(data_mem, index_mem, LinksConstants::new())
+    }
+
+    /// Opens links of `data_mem` and `index_mem` with `constants` without changing them,
+    /// as [`Store::read_only`] does.
+    pub fn read_only_with_constants(
+        data_mem: MD,
+        index_mem: MI,
+        constants: LinksConstants<T>,
+    ) -> Result<Store<T, MD, MI>, LinksError<T>> {
+        Self::open(data_mem, index_mem, constants, true)
+    }
+
+    /// Returns `true` if the store is opened with `read_only`.
+    #[must_use]
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    // maps only the memory that is already there, since it may not grow
+    pub(super) unsafe fn init_read_only(&mut self) -> Result<(), LinksError<T>> {
+        let data = NonNull::from(
+            self.data_mem
+                .alloc(cmp::max(self.data_mem.allocated(), 1))?,
+        );
+        let index = NonNull::from(self.index_mem.alloc(self.index_mem.allocated())?);
+        self.update_mem(data, index);
+
+        let required = self.get_header().allocated.as_usize() + 1;
+        if data.len() < required || index.len() < required {
+            let data = NonNull::from(self.data_mem.alloc(cmp::max(data.len(), required))?);
+            let index = NonNull::from(self.index_mem.alloc(cmp::max(index.len(), required))?);
+            self.update_mem(data, index);
+        }
+
+        // trees of older versions can only be rebuilt by opening the store writable
+        let header = self.get_header();
+        if !header.is_current() && header.allocated != T::funty(0) {
+            return Err(LinksError::ReadOnly);
+        }
+        Ok(())
+    }
+
+    pub(super) fn check_writable(&self) -> Result<(), LinksError<T>> {
+        if self.read_only {
+            Err(LinksError::ReadOnly)
+        } else {
+            Ok(())
+        }
+    }
+}
@@ -0,0 +1,50 @@
+use core::mem::size_of;
+
+use super::Store;
+use crate::mem::{
+    split::{DataPart, IndexPart},
+    stats::top_usages,
+    SplitList, SplitTree, Stats,
+};
+use data::LinkType;
+use mem::RawMem;
+
+impl<
+    T: LinkType,
+    MD: RawMem<DataPart<T>>,
+    MI: RawMem<IndexPart<T>>,
+    IS: SplitTree<T>,
+    ES: SplitTree<T>,
+    IT: SplitTree<T>,
+    ET: SplitTree<T>,
+    UL: SplitList<T>,
+> Store<T, MD, MI, IS, ES, IT, ET, UL>
+{
+    /// Reports how full the store is with the `top` most used links.
+    ///
+    /// Internal trees of every link are measured, so it takes time linear in the count of links.
+    #[must_use]
+    pub fn stats(&self, top: usize) -> Stats<T> {
+        let header = self.get_header();
+        let mut sources = self.external_sources.stats(header.root_as_source);
+        let mut targets = self.external_targets.stats(header.root_as_target);
+        for index in 1..=header.allocated.as_usize() {
+            let link = T::try_from(index).expect("always ok");
+            if self.exists(link) {
+                let part = self.get_index_part(link);
+                sources = sources.merge(self.internal_sources.stats(part.root_as_source));
+                targets = targets.merge(self.internal_targets.stats(part.root_as_target));
+            }
+        }
+        Stats {
+            allocated: header.allocated,
+            reserved: header.reserved,
+            free: header.free,
+            data_bytes: self.data_mem.allocated() * size_of::<DataPart<T>>(),
+            index_bytes: self.index_mem.allocated() * size_of::<IndexPart<T>>(),
+            sources,
+            targets,
+            top_usages: top_usages(self, top),
+        }
+    }
+}
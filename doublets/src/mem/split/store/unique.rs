@@ -0,0 +1,79 @@
+use super::Store;
+use crate::{
+    mem::{
+        split::{DataPart, IndexPart},
+        Duplicates, LinksTree, SplitList, SplitTree,
+    },
+    Doublets, Link,
+};
+use data::LinkType;
+use mem::RawMem;
+
+impl<
+    T: LinkType,
+    MD: RawMem<DataPart<T>>,
+    MI: RawMem<IndexPart<T>>,
+    IS: SplitTree<T>,
+    ES: SplitTree<T>,
+    IT: SplitTree<T>,
+    ET: SplitTree<T>,
+    UL: SplitList<T>,
+> Store<T, MD, MI, IS, ES, IT, ET, UL>
+{
+    /// Sets how changes making a link with the same source and target as another one are treated.
+    #[must_use]
+    pub const fn with_duplicates(mut self, duplicates: Duplicates) -> Self {
+        self.duplicates = duplicates;
+        self
+    }
+
+    /// Returns how duplicates are treated, [`Duplicates::Allow`] by default.
+    #[must_use]
+    pub const fn duplicates(&self) -> Duplicates {
+        self.duplicates
+    }
+
+    // searches the smaller of the trees holding the link with the given parts
+    pub(super) fn search_doublet(&self, source: T, target: T) -> T {
+        let is_virtual_source = self.is_virtual(source);
+        let is_virtual_target = self.is_virtual(target);
+        if is_virtual_source && is_virtual_target {
+            self.external_sources.search(source, target)
+        } else if is_virtual_source {
+            self.internal_targets.search(source, target)
+        } else if is_virtual_target {
+            if Self::USE_LIST {
+                self.external_sources.search(source, target)
+            } else {
+                self.internal_sources.search(source, target)
+            }
+        } else if Self::USE_LIST
+            || self.internal_sources.count_usages(source)
+                > self.internal_targets.count_usages(target)
+        {
+            self.internal_targets.search(source, target)
+        } else {
+            self.internal_sources.search(source, target)
+        }
+    }
+
+    // another link with the same parts if duplicates are not allowed
+    pub(super) fn find_duplicate(&self, index: T, source: T, target: T) -> Option<T> {
+        let zero = T::funty(0);
+        if self.duplicates == Duplicates::Allow || source == zero || target == zero {
+            return None;
+        }
+        let existing = self.search_doublet(source, target);
+        (existing != zero && existing != index).then_some(existing)
+    }
+
+    // the duplicate of the link created by a `[source, target]` query
+    pub(super) fn created_duplicate(&self, query: &[T]) -> Option<Link<T>> {
+        match *query {
+            [source, target] => self
+                .find_duplicate(T::funty(0), source, target)
+                .and_then(|index| self.get_link(index)),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,122 @@
+use alloc::{collections::BinaryHeap, vec::Vec};
+use core::cmp::{self, Reverse};
+
+use crate::Links;
+use data::{Flow, LinkType};
+use trees::SzbTree;
+
+/// Report of how full a store is, returned by `unit::Store::stats` and `split::Store::stats`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Stats<T: LinkType> {
+    /// Links ever allocated, including free ones.
+    pub allocated: T,
+    /// Links that fit into the mapped memory without growing it.
+    pub reserved: T,
+    /// Length of the free list.
+    pub free: T,
+    /// Bytes mapped for the memory of a unit store or the data memory of a split one.
+    pub data_bytes: usize,
+    /// Bytes mapped for the index memory of a split store, zero for a unit one.
+    pub index_bytes: usize,
+    /// Trees indexing links by source.
+    pub sources: TreeStats,
+    /// Trees indexing links by target.
+    pub targets: TreeStats,
+    /// Most used links as `(index, usages)` in descending order of usages,
+    /// where usages are other links having the link as source or target.
+    pub top_usages: Vec<(T, T)>,
+}
+
+/// Shape of the trees indexing one part of links.
+///
+/// A unit store has a single tree per part, while a split store has a tree for each
+/// link used as that part and one for references to external links.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct TreeStats {
+    /// Non-empty trees.
+    pub trees: usize,
+    /// Nodes in all trees.
+    pub nodes: usize,
+    /// Height of the highest tree.
+    pub height: usize,
+    /// Largest difference between the height of a tree and
+    /// the height of a perfectly balanced tree with the same nodes.
+    pub excess_height: usize,
+}
+
+impl TreeStats {
+    /// Measures the tree under the `root` node.
+    pub(crate) unsafe fn of<T, Tree>(tree: &Tree, root: T) -> Self
+    where
+        T: LinkType,
+        Tree: SzbTree<T> + ?Sized,
+    {
+        if root == T::funty(0) {
+            return Self::default();
+        }
+        let nodes = tree.get_size(root).as_usize();
+        let height = height(tree, root);
+        let balanced = (usize::BITS - nodes.leading_zeros()) as usize;
+        Self {
+            trees: 1,
+            nodes,
+            height,
+            excess_height: height.saturating_sub(balanced),
+        }
+    }
+
+    #[must_use]
+    pub fn merge(self, other: Self) -> Self {
+        Self {
+            trees: self.trees + other.trees,
+            nodes: self.nodes + other.nodes,
+            height: cmp::max(self.height, other.height),
+            excess_height: cmp::max(self.excess_height, other.excess_height),
+        }
+    }
+}
+
+// trees are size-balanced, so recursion depth is logarithmic
+unsafe fn height<T, Tree>(tree: &Tree, node: T) -> usize
+where
+    T: LinkType,
+    Tree: SzbTree<T> + ?Sized,
+{
+    if node == T::funty(0) {
+        0
+    } else {
+        1 + cmp::max(
+            height(tree, tree.get_left(node)),
+            height(tree, tree.get_right(node)),
+        )
+    }
+}
+
+// counts usages of every link with index queries and keeps the `top` most used ones
+pub(crate) fn top_usages<T: LinkType>(store: &impl Links<T>, top: usize) -> Vec<(T, T)> {
+    if top == 0 {
+        return Vec::new();
+    }
+    let any = store.constants().any;
+    let mut heap = BinaryHeap::with_capacity(top + 1);
+    store.each_links(&[any; 3], &mut |link| {
+        let index = link.index;
+        let referring = store.count_links(&[any, index, any]).as_usize()
+            + store.count_links(&[any, any, index]).as_usize()
+            - store.count_links(&[any, index, index]).as_usize();
+        let usages = referring - usize::from(link.source == index || link.target == index);
+        if usages > 0 {
+            heap.push(Reverse((usages, Reverse(index))));
+            if heap.len() > top {
+                heap.pop();
+            }
+        }
+        Flow::Continue
+    });
+    heap.into_sorted_vec()
+        .into_iter()
+        .map(|Reverse((usages, Reverse(index)))| (index, T::try_from(usages).expect("always ok")))
+        .collect()
+}
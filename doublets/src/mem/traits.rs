@@ -1,10 +1,10 @@
 use crate::{
-    mem::unit::LinkPart,
+    mem::{unit::LinkPart, TreeStats},
     split::{DataPart, IndexPart},
     Link,
 };
+use core::{ops::Bound, ptr::NonNull};
 use data::{Flow, LinkType};
-use std::ptr::NonNull;
 
 pub trait LinksTree<T: LinkType> {
     fn count_usages(&self, root: T) -> T;
@@ -13,6 +13,41 @@ pub trait LinksTree<T: LinkType> {
 
     fn each_usages<H: FnMut(Link<T>) -> Flow + ?Sized>(&self, root: T, handler: &mut H) -> Flow;
 
+    /// Returns the `n`-th (zero-based) usage of `root` in tree order
+    /// or zero if `root` has no more than `n` usages.
+    ///
+    /// Usages with equal key parts are ordered by index.
+    fn nth_usage(&self, root: T, n: T) -> T;
+
+    /// Returns the number of `root` usages that precede `index` in tree order.
+    fn usage_rank(&self, root: T, index: T) -> T;
+
+    /// Visits usages of `root` whose key part (target for sources
+    /// and source for targets) lies in `range`, in tree order.
+    fn each_usages_range<H: FnMut(Link<T>) -> Flow + ?Sized>(
+        &self,
+        root: T,
+        range: (Bound<T>, Bound<T>),
+        handler: &mut H,
+    ) -> Flow;
+
+    /// Returns the number of links whose base part lies in `range`.
+    fn count_range(&self, range: (Bound<T>, Bound<T>)) -> T;
+
+    /// Visits links whose base part lies in `range`, in ascending base order.
+    fn each_range<H: FnMut(Link<T>) -> Flow + ?Sized>(
+        &self,
+        range: (Bound<T>, Bound<T>),
+        handler: &mut H,
+    ) -> Flow;
+
+    /// Replaces the tree under `root` with a balanced one of `nodes`,
+    /// which must be sorted in tree order.
+    fn rebuild(&mut self, root: &mut T, nodes: &[T]);
+
+    /// Measures the tree under the `root` node.
+    fn stats(&self, root: T) -> TreeStats;
+
     fn detach(&mut self, root: &mut T, index: T);
 
     fn attach(&mut self, root: &mut T, index: T);
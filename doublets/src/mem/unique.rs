@@ -0,0 +1,58 @@
+use alloc::collections::BTreeSet;
+
+use crate::{Doublet, Link, LinksError, WriteHandler};
+use data::{Flow, LinkType};
+
+/// How a store treats changes making a link with the same source and target as another one.
+///
+/// Links with a zero source or target are never checked.
+/// `create_link` passes `[source, target]` as the query of `create_links`,
+/// so a duplicate is found before a link is created and a failed change never changes the store.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Duplicates {
+    /// Duplicates are made as any other links.
+    #[default]
+    Allow,
+    /// Changes fail with `Error::AlreadyExists`.
+    Reject,
+    /// Changes are not made and the existing link is passed to the handler instead,
+    /// so `create_link` and `update` return its index.
+    Redirect,
+}
+
+impl Duplicates {
+    // a duplicate is not allowed, so `existing` is reported instead of changing `link`,
+    // which is nothing if the duplicate would be created
+    pub(crate) fn resolve<T: LinkType>(
+        self,
+        link: Link<T>,
+        existing: Link<T>,
+        handler: WriteHandler<'_, T>,
+    ) -> Result<Flow, LinksError<T>> {
+        match self {
+            Self::Redirect => Ok(handler(link, existing)),
+            Self::Allow | Self::Reject => Err(LinksError::AlreadyExists(Doublet::new(
+                existing.source,
+                existing.target,
+            ))),
+        }
+    }
+}
+
+// fails on the first pair already found by `search` or repeated in `links`
+pub(crate) fn check_unique<T: LinkType>(
+    links: &[(T, T)],
+    search: impl Fn(T, T) -> T,
+) -> Result<(), LinksError<T>> {
+    let zero = T::funty(0);
+    let mut seen = BTreeSet::new();
+    for &(source, target) in links {
+        if source == zero || target == zero {
+            continue;
+        }
+        if search(source, target) != zero || !seen.insert((source, target)) {
+            return Err(LinksError::AlreadyExists(Doublet::new(source, target)));
+        }
+    }
+    Ok(())
+}
@@ -1,10 +1,14 @@
-use std::{default::default, marker::PhantomData, ptr::NonNull};
+use core::{
+    marker::PhantomData,
+    ops::{Bound, RangeBounds},
+    ptr::NonNull,
+};
 
 use crate::{
     mem::{header::LinksHeader, unit::raw_link::LinkPart, LinksTree},
     Link,
 };
-use data::{LinkType, LinksConstants};
+use data::{Flow, LinkType, LinksConstants};
 use trees::NoRecurSzbTree;
 
 // TODO: why is there so much duplication in OOP!!! FIXME
@@ -22,7 +26,7 @@ impl<T: LinkType> LinksRecursionlessSizeBalancedTreeBase<T> {
             mem,
             r#break: constants.r#break,
             r#continue: constants.r#continue,
-            _phantom: default(),
+            _phantom: PhantomData,
         }
     }
 }
@@ -42,6 +46,8 @@ pub trait LinkRecursionlessSizeBalancedTreeBaseAbstract<T: LinkType>:
 
     fn get_base_part(&self, link: T) -> T;
 
+    fn get_key_part(&self, link: T) -> T;
+
     // TODO: rename
     fn first_is_to_the_left_of_second_4(
         &self,
@@ -59,8 +65,187 @@ pub trait LinkRecursionlessSizeBalancedTreeBaseAbstract<T: LinkType>:
         root_target: T,
     ) -> bool;
 
+    // position of the node in the tree: equal links are ordered by index
+    fn node_order(&self, link: T) -> (T, T, T) {
+        (self.get_base_part(link), self.get_key_part(link), link)
+    }
+
     fn get_link_value(&self, index: T) -> Link<T> {
         let link = self.get_link(index);
         Link::new(index, link.source, link.target)
     }
+
+    // count of links which base part is less than `base`
+    fn usages_offset_core(&self, base: T) -> T {
+        unsafe {
+            let mut root = self.get_tree_root();
+            let mut offset = T::funty(0);
+            while root != T::funty(0) {
+                if self.get_base_part(root) < base {
+                    offset += self.get_left_size(root) + T::funty(1);
+                    root = self.get_right_or_default(root);
+                } else {
+                    root = self.get_left_or_default(root);
+                }
+            }
+            offset
+        }
+    }
+
+    // count of links which base part lies before the `end` bound
+    fn count_before_core(&self, end: Bound<T>) -> T {
+        match end {
+            Bound::Unbounded => unsafe { self.get_size_or_zero(self.get_tree_root()) },
+            Bound::Excluded(base) => self.usages_offset_core(base),
+            Bound::Included(base) => unsafe {
+                let mut root = self.get_tree_root();
+                let mut count = T::funty(0);
+                while root != T::funty(0) {
+                    if self.get_base_part(root) <= base {
+                        count += self.get_left_size(root) + T::funty(1);
+                        root = self.get_right_or_default(root);
+                    } else {
+                        root = self.get_left_or_default(root);
+                    }
+                }
+                count
+            },
+        }
+    }
+
+    fn count_range_core(&self, (start, end): (Bound<T>, Bound<T>)) -> T {
+        let before = match start {
+            Bound::Unbounded => T::funty(0),
+            Bound::Included(base) => self.count_before_core(Bound::Excluded(base)),
+            Bound::Excluded(base) => self.count_before_core(Bound::Included(base)),
+        };
+        let to = self.count_before_core(end);
+        if to > before {
+            to - before
+        } else {
+            T::funty(0)
+        }
+    }
+
+    fn each_range_core<H: FnMut(Link<T>) -> Flow + ?Sized>(
+        &self,
+        link: T,
+        range: (Bound<T>, Bound<T>),
+        handler: &mut H,
+    ) -> Flow {
+        if link == T::funty(0) {
+            return Flow::Continue;
+        }
+        let base = self.get_base_part(link);
+        // equal base parts may lie on both sides of the node
+        let to_left = match range.0 {
+            Bound::Unbounded => true,
+            Bound::Included(from) => base >= from,
+            Bound::Excluded(from) => base > from,
+        };
+        let to_right = match range.1 {
+            Bound::Unbounded => true,
+            Bound::Included(to) => base <= to,
+            Bound::Excluded(to) => base < to,
+        };
+        unsafe {
+            if to_left {
+                let left = self.get_left_or_default(link);
+                self.each_range_core(left, range, handler)?;
+            }
+            if range.contains(&base) {
+                handler(self.get_link_value(link))?;
+            }
+            if to_right {
+                let right = self.get_right_or_default(link);
+                self.each_range_core(right, range, handler)?;
+            }
+        }
+        Flow::Continue
+    }
+
+    fn select_core(&self, mut n: T) -> T {
+        unsafe {
+            let mut root = self.get_tree_root();
+            while root != T::funty(0) {
+                let left = self.get_left_size(root);
+                if n < left {
+                    root = self.get_left_or_default(root);
+                } else if n > left {
+                    n -= left + T::funty(1);
+                    root = self.get_right_or_default(root);
+                } else {
+                    return root;
+                }
+            }
+            T::funty(0)
+        }
+    }
+
+    fn nth_usage_core(&self, base: T, n: T) -> T {
+        if n < self.count_usages(base) {
+            self.select_core(self.usages_offset_core(base) + n)
+        } else {
+            T::funty(0)
+        }
+    }
+
+    fn usage_rank_core(&self, base: T, link: T) -> T {
+        let node = self.node_order(link);
+        let mut rank = T::funty(0);
+        unsafe {
+            let mut root = self.get_tree_root();
+            while root != T::funty(0) {
+                if self.node_order(root) < node {
+                    rank += self.get_left_size(root) + T::funty(1);
+                    root = self.get_right_or_default(root);
+                } else {
+                    root = self.get_left_or_default(root);
+                }
+            }
+        }
+        rank - self.usages_offset_core(base)
+    }
+
+    fn each_usages_range_core<H: FnMut(Link<T>) -> Flow + ?Sized>(
+        &self,
+        link: T,
+        base: T,
+        range: (Bound<T>, Bound<T>),
+        handler: &mut H,
+    ) -> Flow {
+        if link == T::funty(0) {
+            return Flow::Continue;
+        }
+        let (link_base, key) = (self.get_base_part(link), self.get_key_part(link));
+        // nodes are ordered by base part first, so keys are compared only under `base`
+        let to_left = link_base > base
+            || (link_base == base
+                && match range.0 {
+                    Bound::Unbounded => true,
+                    Bound::Included(from) => key >= from,
+                    Bound::Excluded(from) => key > from,
+                });
+        let to_right = link_base < base
+            || (link_base == base
+                && match range.1 {
+                    Bound::Unbounded => true,
+                    Bound::Included(to) => key <= to,
+                    Bound::Excluded(to) => key < to,
+                });
+        unsafe {
+            if to_left {
+                let left = self.get_left_or_default(link);
+                self.each_usages_range_core(left, base, range, handler)?;
+            }
+            if link_base == base && range.contains(&key) {
+                handler(self.get_link_value(link))?;
+            }
+            if to_right {
+                let right = self.get_right_or_default(link);
+                self.each_usages_range_core(right, base, range, handler)?;
+            }
+        }
+        Flow::Continue
+    }
 }
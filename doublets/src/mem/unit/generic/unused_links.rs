@@ -1,4 +1,4 @@
-use std::{mem::transmute, ptr::NonNull};
+use core::{mem::transmute, ptr::NonNull};
 
 use crate::mem::{
     header::LinksHeader, traits::UnitList, unit::raw_link::LinkPart, LinksList, UnitUpdateMem,
@@ -1,20 +1,25 @@
 use crate::{
+    data::{is_any, matches_link},
     mem::{
+        bulk::{check_load, create_many_reserved, sort_nodes},
         header::LinksHeader,
+        stats::top_usages,
         traits::UnitList,
+        unique::check_unique,
         unit::{
             LinkPart, LinksSourcesRecursionlessSizeBalancedTree,
             LinksTargetsRecursionlessSizeBalancedTree, UnusedLinks,
         },
-        UnitTree,
+        Duplicates, Stats, UnitTree,
     },
-    Doublets, Link, Links, LinksError, ReadHandler, WriteHandler,
+    Doublets, Link, Links, LinksError, PartRange, ReadHandler, WriteHandler,
 };
+use alloc::vec::Vec;
 use data::{Flow, LinkType, LinksConstants, ToQuery};
 use leak_slice::LeakSliceExt;
 use mem::{RawMem, DEFAULT_PAGE_SIZE};
 
-use std::{cmp, cmp::Ordering, error::Error, mem::transmute, ptr::NonNull};
+use core::{cmp, cmp::Ordering, mem::transmute, ops::Range, ptr::NonNull};
 
 pub struct Store<
     T: LinkType,
@@ -27,6 +32,8 @@ pub struct Store<
     mem_ptr: NonNull<[LinkPart<T>]>,
     reserve_step: usize,
     constants: LinksConstants<T>,
+    read_only: bool,
+    duplicates: Duplicates,
 
     sources: TS,
     targets: TT,
@@ -49,7 +56,35 @@ impl<T: LinkType, M: RawMem<LinkPart<T>>, TS: UnitTree<T>, TT: UnitTree<T>, TU:
         mem: M,
         constants: LinksConstants<T>,
     ) -> Result<Store<T, M>, LinksError<T>> {
-        let dangling_mem = NonNull::slice_from_raw_parts(NonNull::dangling(), 0);
+        Self::open(mem, constants, false)
+    }
+
+    /// Opens links of `mem` without changing it, e.g. of [`ReadOnlyMapped`] file.
+    ///
+    /// The header is neither written nor the memory grown,
+    /// and changing methods return [`LinksError::ReadOnly`].
+    /// Files with trees of an older version fail with [`LinksError::ReadOnly`],
+    /// since their trees are rebuilt only when opened with [`Store::new`].
+    ///
+    /// [`ReadOnlyMapped`]: crate::mem::ReadOnlyMapped
+    pub fn read_only(mem: M) -> Result<Store<T, M>, LinksError<T>> {
+        Self::read_only_with_constants(mem, LinksConstants::new())
+    }
+
+    /// Opens links of `mem` with `constants` without changing it, as [`Store::read_only`] does.
+    pub fn read_only_with_constants(
+        mem: M,
+        constants: LinksConstants<T>,
+    ) -> Result<Store<T, M>, LinksError<T>> {
+        Self::open(mem, constants, true)
+    }
+
+    fn open(
+        mem: M,
+        constants: LinksConstants<T>,
+        read_only: bool,
+    ) -> Result<Store<T, M>, LinksError<T>> {
+        let dangling_mem = NonNull::from(&mut [][..]);
         let sources =
             LinksSourcesRecursionlessSizeBalancedTree::new(constants.clone(), dangling_mem);
         let targets =
@@ -66,6 +101,8 @@ impl<T: LinkType, M: RawMem<LinkPart<T>>, TS: UnitTree<T>, TT: UnitTree<T>, TU:
             mem_ptr: dangling_mem,
             reserve_step: Self::SIZE_STEP,
             constants,
+            read_only,
+            duplicates: Duplicates::Allow,
             sources,
             targets,
             unused,
@@ -73,7 +110,11 @@ impl<T: LinkType, M: RawMem<LinkPart<T>>, TS: UnitTree<T>, TT: UnitTree<T>, TU:
 
         // SAFETY: Without this, the code will become unsafe
         unsafe {
-            new.init()?;
+            if read_only {
+                new.init_read_only()?;
+            } else {
+                new.init()?;
+            }
         }
         Ok(new)
     }
@@ -91,9 +132,76 @@ impl<T: LinkType, M: RawMem<LinkPart<T>>, TS: UnitTree<T>, TT: UnitTree<T>, TU:
 
         let header = self.mut_header();
         header.reserved = T::try_from(reserved - 1).expect("always ok");
+        self.upgrade_trees();
         Ok(())
     }
 
+    // maps only the memory that is already there, since it may not grow
+    unsafe fn init_read_only(&mut self) -> Result<(), LinksError<T>> {
+        let mem = self.mem.alloc(cmp::max(self.mem.allocated(), 1))?.leak();
+        self.update_mem(mem);
+
+        let required = self.get_header().allocated.as_usize() + 1;
+        if self.mem_ptr.len() < required {
+            let mem = self.mem.alloc(required)?.leak();
+            self.update_mem(mem);
+        }
+
+        // trees of older versions can only be rebuilt by opening the store writable
+        let header = self.get_header();
+        if !header.is_current() && header.allocated != T::funty(0) {
+            return Err(LinksError::ReadOnly);
+        }
+        Ok(())
+    }
+
+    /// Returns `true` if the store is opened with `read_only`.
+    #[must_use]
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Sets how changes making a link with the same source and target as another one are treated.
+    #[must_use]
+    pub const fn with_duplicates(mut self, duplicates: Duplicates) -> Self {
+        self.duplicates = duplicates;
+        self
+    }
+
+    /// Returns how duplicates are treated, [`Duplicates::Allow`] by default.
+    #[must_use]
+    pub const fn duplicates(&self) -> Duplicates {
+        self.duplicates
+    }
+
+    // another link with the same parts if duplicates are not allowed
+    fn find_duplicate(&self, index: T, source: T, target: T) -> Option<T> {
+        let zero = T::funty(0);
+        if self.duplicates == Duplicates::Allow || source == zero || target == zero {
+            return None;
+        }
+        let existing = self.sources.search(source, target);
+        (existing != zero && existing != index).then_some(existing)
+    }
+
+    // the duplicate of the link created by a `[source, target]` query
+    fn created_duplicate(&self, query: &[T]) -> Option<Link<T>> {
+        match *query {
+            [source, target] => self
+                .find_duplicate(T::funty(0), source, target)
+                .and_then(|index| self.get_link(index)),
+            _ => None,
+        }
+    }
+
+    fn check_writable(&self) -> Result<(), LinksError<T>> {
+        if self.read_only {
+            Err(LinksError::ReadOnly)
+        } else {
+            Ok(())
+        }
+    }
+
     fn mut_from_mem<'a, U>(mut ptr: NonNull<[U]>, index: usize) -> Option<&'a mut U> {
         if index < ptr.len() {
             // SAFETY: `ptr` is non-dangling slice
@@ -110,7 +218,8 @@ impl<T: LinkType, M: RawMem<LinkPart<T>>, TS: UnitTree<T>, TT: UnitTree<T>, TU:
         Self::mut_from_mem(mem, index).map(|v| &*v)
     }
 
-    fn get_header(&self) -> &LinksHeader<T> {
+    /// Returns the header with counters of allocated, reserved and free links.
+    pub fn get_header(&self) -> &LinksHeader<T> {
         // SAFETY: `LinksHeader` and `IndexPart` layout are equivalent
         unsafe {
             Self::get_from_mem(self.mem_ptr, 0)
@@ -119,6 +228,22 @@ impl<T: LinkType, M: RawMem<LinkPart<T>>, TS: UnitTree<T>, TT: UnitTree<T>, TU:
         }
     }
 
+    /// Reports how full the store is with the `top` most used links.
+    #[must_use]
+    pub fn stats(&self, top: usize) -> Stats<T> {
+        let header = self.get_header();
+        Stats {
+            allocated: header.allocated,
+            reserved: header.reserved,
+            free: header.free,
+            data_bytes: self.mem.allocated() * core::mem::size_of::<LinkPart<T>>(),
+            index_bytes: 0,
+            sources: self.sources.stats(header.root_as_source),
+            targets: self.targets.stats(header.root_as_target),
+            top_usages: top_usages(self, top),
+        }
+    }
+
     fn mut_header(&mut self) -> &mut LinksHeader<T> {
         // SAFETY: `LinksHeader` and `IndexPart` layout are equivalent
         unsafe {
@@ -318,6 +443,117 @@ impl<T: LinkType, M: RawMem<LinkPart<T>>, TS: UnitTree<T>, TT: UnitTree<T>, TU:
         }
         todo!()
     }
+
+    /// Appends links with the given `(source, target)` parts and returns their indices.
+    ///
+    /// Records are written sequentially after the allocated ones, so free links are not reused.
+    /// When the batch is at least as large as the store, both trees are built bottom-up
+    /// from all links in one pass, which is much faster than attaching links one by one,
+    /// otherwise only the new links are attached.
+    /// Nothing is written unless all links can be loaded: the capacity, the parts and,
+    /// unless duplicates are allowed, the uniqueness of the links are checked first,
+    /// and a duplicate fails with [`LinksError::AlreadyExists`] even with [`Duplicates::Redirect`].
+    pub fn bulk_load(
+        &mut self,
+        links: impl IntoIterator<Item = (T, T)>,
+    ) -> Result<Range<T>, LinksError<T>> {
+        self.check_writable()?;
+        let links: Vec<_> = links.into_iter().collect();
+        let allocated = self.get_header().allocated;
+        check_load(&links, allocated, &self.constants)?;
+        if self.duplicates != Duplicates::Allow {
+            check_unique(&links, |source, target| self.sources.search(source, target))?;
+        }
+        self.reserve_links(links.len())?;
+
+        let start = allocated + T::funty(1);
+        if links.len() >= allocated.as_usize() {
+            let mut nodes: Vec<_> = (T::funty(1)..start)
+                .filter(|&index| self.exists(index))
+                .collect();
+            for (source, target) in links {
+                let index = self.push_link(LinkPart {
+                    source,
+                    target,
+                    ..LinkPart::default()
+                });
+                nodes.push(index);
+            }
+            self.rebuild_trees(nodes);
+        } else {
+            for (source, target) in links {
+                let index = self.push_link(LinkPart::default());
+                // the link exists and is not a duplicate, so it is updated as is
+                self.update(index, source, target)?;
+            }
+        }
+        Ok(start..self.get_header().allocated + T::funty(1))
+    }
+
+    // writes the link after the allocated ones, which must be reserved
+    fn push_link(&mut self, part: LinkPart<T>) -> T {
+        let header = self.mut_header();
+        header.allocated += T::funty(1);
+        let index = header.allocated;
+        *self.mut_link_part(index) = part;
+        index
+    }
+
+    // grows memory so that `count` more links fit in it
+    fn reserve_links(&mut self, count: usize) -> Result<(), LinksError<T>> {
+        let header = self.get_header();
+        let required = header.allocated.as_usize() + count;
+        if required >= header.reserved.as_usize() {
+            let capacity = cmp::max(self.mem.allocated() + self.reserve_step, required + 1);
+            let mem = self.mem.alloc(capacity)?.leak();
+            self.update_mem(mem);
+            let reserved = self.mem.allocated();
+            let header = self.mut_header();
+            header.reserved = T::try_from(reserved).expect("always ok");
+        }
+        Ok(())
+    }
+
+    // trees of older versions order equal links differently, so they are built again
+    fn upgrade_trees(&mut self) {
+        if !self.get_header().is_current() {
+            let nodes = (T::funty(1)..=self.get_header().allocated)
+                .filter(|&index| self.exists(index))
+                .collect();
+            self.rebuild_trees(nodes);
+            self.mut_header().set_current();
+        }
+    }
+
+    fn rebuild_trees(&mut self, nodes: Vec<T>) {
+        let (mut sources, mut targets): (Vec<_>, Vec<_>) = (
+            nodes
+                .iter()
+                .copied()
+                .filter(|&index| self.get_link_part(index).source != T::funty(0))
+                .collect(),
+            nodes
+                .into_iter()
+                .filter(|&index| self.get_link_part(index).target != T::funty(0))
+                .collect(),
+        );
+        sort_nodes(&mut sources, |index| {
+            let part = self.get_link_part(index);
+            (part.source, part.target)
+        });
+        sort_nodes(&mut targets, |index| {
+            let part = self.get_link_part(index);
+            (part.target, part.source)
+        });
+
+        // SAFETY: roots are in the header, which is never a tree node
+        unsafe {
+            let root = self.mut_source_root();
+            self.sources.rebuild(&mut *root, &sources);
+            let root = self.mut_target_root();
+            self.targets.rebuild(&mut *root, &targets);
+        }
+    }
 }
 
 impl<T: LinkType, M: RawMem<LinkPart<T>>, TS: UnitTree<T>, TT: UnitTree<T>, TU: UnitList<T>>
@@ -428,9 +664,13 @@ impl<T: LinkType, M: RawMem<LinkPart<T>>, TS: UnitTree<T>, TT: UnitTree<T>, TU:
 
     fn create_links(
         &mut self,
-        _query: &[T],
+        query: &[T],
         handler: WriteHandler<'_, T>,
     ) -> Result<Flow, LinksError<T>> {
+        self.check_writable()?;
+        if let Some(existing) = self.created_duplicate(query) {
+            return self.duplicates.resolve(Link::nothing(), existing, handler);
+        }
         let constants = self.constants();
         let header = self.get_header();
         let mut free = header.first_free;
@@ -472,6 +712,7 @@ impl<T: LinkType, M: RawMem<LinkPart<T>>, TS: UnitTree<T>, TT: UnitTree<T>, TU:
         change: &[T],
         handler: WriteHandler<'_, T>,
     ) -> Result<Flow, LinksError<T>> {
+        self.check_writable()?;
         let index = query[0];
         let source = change[1];
         let target = change[2];
@@ -479,6 +720,10 @@ impl<T: LinkType, M: RawMem<LinkPart<T>>, TS: UnitTree<T>, TT: UnitTree<T>, TU:
         let old_target = target;
 
         let link = self.try_get_link(index)?;
+        if let Some(existing) = self.find_duplicate(index, source, target) {
+            let existing = self.try_get_link(existing)?;
+            return self.duplicates.resolve(link, existing, handler);
+        }
 
         if link.source != T::funty(0) {
             // SAFETY: Here index detach from sources
@@ -519,11 +764,22 @@ impl<T: LinkType, M: RawMem<LinkPart<T>>, TS: UnitTree<T>, TT: UnitTree<T>, TU:
         ))
     }
 
+    fn create_many(
+        &mut self,
+        count: T,
+        handler: WriteHandler<'_, T>,
+    ) -> Result<Flow, LinksError<T>> {
+        self.check_writable()?;
+        let header = self.get_header().clone();
+        create_many_reserved(self, header, count, Self::reserve_links, handler)
+    }
+
     fn delete_links(
         &mut self,
         query: &[T],
         handler: WriteHandler<'_, T>,
     ) -> Result<Flow, LinksError<T>> {
+        self.check_writable()?;
         let index = query[0];
 
         let link = self.try_get_link(index)?;
@@ -565,6 +821,94 @@ impl<T: LinkType, M: RawMem<LinkPart<T>>, TS: UnitTree<T>, TT: UnitTree<T>, TU:
             None
         }
     }
+
+    fn each_from(&self, start: T, handler: ReadHandler<'_, T>) -> Flow {
+        for index in start.max(T::funty(1))..=self.get_header().allocated {
+            if let Some(link) = self.get_link(index) {
+                handler(link)?;
+            }
+        }
+        Flow::Continue
+    }
+
+    fn nth_usage_as_source(&self, source: T, n: T) -> Option<Link<T>> {
+        self.get_link(self.sources.nth_usage(source, n))
+    }
+
+    fn nth_usage_as_target(&self, target: T, n: T) -> Option<Link<T>> {
+        self.get_link(self.targets.nth_usage(target, n))
+    }
+
+    fn usage_rank_as_source(&self, index: T) -> Option<T> {
+        let link = self.get_link(index)?;
+        (link.source != T::funty(0)).then(|| self.sources.usage_rank(link.source, index))
+    }
+
+    fn usage_rank_as_target(&self, index: T) -> Option<T> {
+        let link = self.get_link(index)?;
+        (link.target != T::funty(0)).then(|| self.targets.usage_rank(link.target, index))
+    }
+
+    fn each_usages_as_source_in(
+        &self,
+        source: T,
+        targets: PartRange<T>,
+        handler: ReadHandler<'_, T>,
+    ) -> Flow {
+        self.sources.each_usages_range(source, targets, handler)
+    }
+
+    fn each_usages_as_target_in(
+        &self,
+        target: T,
+        sources: PartRange<T>,
+        handler: ReadHandler<'_, T>,
+    ) -> Flow {
+        self.targets.each_usages_range(target, sources, handler)
+    }
+
+    fn count_in_ranges(&self, sources: PartRange<T>, targets: PartRange<T>) -> T {
+        match (is_any(&sources), is_any(&targets)) {
+            (true, true) => self.get_total(),
+            (false, true) => self.sources.count_range(sources),
+            (true, false) => self.targets.count_range(targets),
+            (false, false) => {
+                let mut count = T::funty(0);
+                self.each_in_ranges(sources, targets, &mut |_| {
+                    count += T::funty(1);
+                    Flow::Continue
+                });
+                count
+            }
+        }
+    }
+
+    fn each_in_ranges(
+        &self,
+        sources: PartRange<T>,
+        targets: PartRange<T>,
+        handler: ReadHandler<'_, T>,
+    ) -> Flow {
+        if is_any(&sources) && is_any(&targets) {
+            return self.each_links(&[], handler);
+        }
+        let handler = &mut |link: Link<T>| {
+            if matches_link(&sources, &targets, &link) {
+                handler(link)
+            } else {
+                Flow::Continue
+            }
+        };
+        // walk the index of the more selective part and filter by the other one
+        if is_any(&targets)
+            || (!is_any(&sources)
+                && self.sources.count_range(sources) <= self.targets.count_range(targets))
+        {
+            self.sources.each_range(sources, handler)
+        } else {
+            self.targets.each_range(targets, handler)
+        }
+    }
 }
 
 // SAFETY: No read operations result in a write
@@ -0,0 +1,451 @@
+//! Metrics and tracing of store operations.
+//!
+//! [`Metered`] wraps a store, counts calls and errors of its operations,
+//! records their latency in histograms and runs each of them in a `doublets`
+//! span of the `tracing` crate at the debug level.
+
+use std::{
+    array,
+    fmt::{self, Display, Formatter},
+    marker::PhantomData,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+
+use tracing::span::EnteredSpan;
+
+use crate::{Doublets, Error, Link, Links, PartRange, ReadHandler, WriteHandler};
+use data::{Flow, LinkType, LinksConstants};
+
+/// Number of latency buckets, the bucket `i` counts calls faster than `2^i` nanoseconds
+/// and the last one counts all slower calls.
+pub const BUCKETS: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Operation {
+    Count,
+    Each,
+    Get,
+    Create,
+    Update,
+    Delete,
+    CreateMany,
+    UpdateMany,
+    DeleteMany,
+}
+
+impl Operation {
+    pub const ALL: [Self; 9] = [
+        Self::Count,
+        Self::Each,
+        Self::Get,
+        Self::Create,
+        Self::Update,
+        Self::Delete,
+        Self::CreateMany,
+        Self::UpdateMany,
+        Self::DeleteMany,
+    ];
+
+    #[must_use]
+    pub const fn name(self) -> &'static str {
+        match self {
+            Self::Count => "count",
+            Self::Each => "each",
+            Self::Get => "get",
+            Self::Create => "create",
+            Self::Update => "update",
+            Self::Delete => "delete",
+            Self::CreateMany => "create_many",
+            Self::UpdateMany => "update_many",
+            Self::DeleteMany => "delete_many",
+        }
+    }
+}
+
+impl Display for Operation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Metrics of one operation since the creation or the last reset.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OperationMetrics {
+    pub calls: u64,
+    pub errors: u64,
+    /// Total latency of all calls.
+    pub total: Duration,
+    /// Calls by latency, see [`BUCKETS`].
+    pub buckets: [u64; BUCKETS],
+}
+
+impl OperationMetrics {
+    /// Returns the upper bound of the bucket `i`, `Duration::MAX` for the last one.
+    #[must_use]
+    pub fn bucket_bound(i: usize) -> Duration {
+        if i + 1 < BUCKETS {
+            Duration::from_nanos(1 << i)
+        } else {
+            Duration::MAX
+        }
+    }
+
+    #[must_use]
+    pub fn mean(&self) -> Duration {
+        match u32::try_from(self.calls) {
+            Ok(0) => Duration::ZERO,
+            Ok(calls) => self.total / calls,
+            Err(_) => Duration::from_nanos(self.nanos() / self.calls),
+        }
+    }
+
+    /// Returns the latency which at least `quantile` (from `0.0` to `1.0`) of calls
+    /// did not exceed, rounded up to the bound of its bucket.
+    #[must_use]
+    pub fn quantile(&self, quantile: f64) -> Duration {
+        // calls are far less than 2^52, so the rank is exact
+        #[allow(clippy::cast_precision_loss, clippy::cast_possible_truncation)]
+        #[allow(clippy::cast_sign_loss)]
+        let rank = (quantile.clamp(0.0, 1.0) * self.calls as f64).ceil() as u64;
+        let mut seen = 0;
+        for (i, &count) in self.buckets.iter().enumerate() {
+            seen += count;
+            if seen >= rank.max(1) {
+                return Self::bucket_bound(i);
+            }
+        }
+        Duration::ZERO
+    }
+
+    fn nanos(&self) -> u64 {
+        u64::try_from(self.total.as_nanos()).unwrap_or(u64::MAX)
+    }
+}
+
+/// Snapshot of metrics of all operations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Metrics {
+    operations: [OperationMetrics; Operation::ALL.len()],
+}
+
+impl Metrics {
+    #[must_use]
+    pub fn get(&self, operation: Operation) -> &OperationMetrics {
+        &self.operations[operation as usize]
+    }
+
+    #[must_use]
+    pub fn iter(&self) -> impl Iterator<Item = (Operation, &OperationMetrics)> {
+        Operation::ALL.into_iter().zip(&self.operations)
+    }
+}
+
+#[derive(Default)]
+struct Counters {
+    calls: AtomicU64,
+    errors: AtomicU64,
+    nanos: AtomicU64,
+    buckets: [AtomicU64; BUCKETS],
+}
+
+impl Counters {
+    fn record(&self, latency: Duration, failed: bool) {
+        let nanos = u64::try_from(latency.as_nanos()).unwrap_or(u64::MAX);
+        let bucket = (u64::BITS - nanos.leading_zeros()) as usize;
+        self.calls.fetch_add(1, Ordering::Relaxed);
+        self.errors.fetch_add(u64::from(failed), Ordering::Relaxed);
+        self.nanos.fetch_add(nanos, Ordering::Relaxed);
+        self.buckets[bucket.min(BUCKETS - 1)].fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn snapshot(&self) -> OperationMetrics {
+        OperationMetrics {
+            calls: self.calls.load(Ordering::Relaxed),
+            errors: self.errors.load(Ordering::Relaxed),
+            total: Duration::from_nanos(self.nanos.load(Ordering::Relaxed)),
+            buckets: array::from_fn(|i| self.buckets[i].load(Ordering::Relaxed)),
+        }
+    }
+
+    fn reset(&self) {
+        for counter in [&self.calls, &self.errors, &self.nanos]
+            .into_iter()
+            .chain(&self.buckets)
+        {
+            counter.store(0, Ordering::Relaxed);
+        }
+    }
+}
+
+// `any` or `value` for each part, so spans do not leak data by default
+struct Shape<'a, T: LinkType> {
+    query: &'a [T],
+    any: T,
+}
+
+impl<T: LinkType> Display for Shape<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("[")?;
+        for (i, &part) in self.query.iter().enumerate() {
+            if i > 0 {
+                f.write_str(", ")?;
+            }
+            f.write_str(if part == self.any { "any" } else { "value" })?;
+        }
+        f.write_str("]")
+    }
+}
+
+/// Store recording metrics and `tracing` spans of operations of the inner one.
+///
+/// Latency of reads includes the time spent in handlers.
+pub struct Metered<T: LinkType, S: Links<T>> {
+    store: S,
+    counters: [Counters; Operation::ALL.len()],
+    values: bool,
+    _phantom: PhantomData<T>,
+}
+
+impl<T: LinkType, S: Links<T>> Metered<T, S> {
+    #[must_use]
+    pub fn new(store: S) -> Self {
+        Self {
+            store,
+            counters: Default::default(),
+            values: false,
+            _phantom: PhantomData,
+        }
+    }
+
+    /// Records query values instead of their shape in spans.
+    #[must_use]
+    pub fn with_values(mut self, values: bool) -> Self {
+        self.values = values;
+        self
+    }
+
+    #[must_use]
+    pub fn store(&self) -> &S {
+        &self.store
+    }
+
+    #[must_use]
+    pub fn into_inner(self) -> S {
+        self.store
+    }
+
+    #[must_use]
+    pub fn metrics(&self) -> Metrics {
+        Metrics {
+            operations: array::from_fn(|i| self.counters[i].snapshot()),
+        }
+    }
+
+    pub fn reset(&self) {
+        self.counters.iter().for_each(Counters::reset);
+    }
+
+    fn start(&self, operation: Operation, query: &[T]) -> (EnteredSpan, Instant) {
+        let span = if self.values {
+            tracing::debug_span!("doublets", op = operation.name(), query = ?query)
+        } else {
+            let shape = Shape {
+                query,
+                any: self.any(),
+            };
+            tracing::debug_span!("doublets", op = operation.name(), query = %shape)
+        };
+        (span.entered(), Instant::now())
+    }
+
+    fn finish(&self, operation: Operation, (span, start): (EnteredSpan, Instant), failed: bool) {
+        self.counters[operation as usize].record(start.elapsed(), failed);
+        drop(span);
+    }
+
+    fn finish_with<R>(
+        &self,
+        operation: Operation,
+        started: (EnteredSpan, Instant),
+        result: Result<R, Error<T>>,
+    ) -> Result<R, Error<T>> {
+        if let Err(err) = &result {
+            tracing::debug!(error = %err, "operation failed");
+        }
+        self.finish(operation, started, result.is_err());
+        result
+    }
+
+    fn any(&self) -> T {
+        self.store.constants().any
+    }
+}
+
+impl<T: LinkType, S: Links<T>> Links<T> for Metered<T, S> {
+    fn constants(&self) -> &LinksConstants<T> {
+        self.store.constants()
+    }
+
+    fn count_links(&self, query: &[T]) -> T {
+        let started = self.start(Operation::Count, query);
+        let count = self.store.count_links(query);
+        self.finish(Operation::Count, started, false);
+        count
+    }
+
+    fn create_links(
+        &mut self,
+        query: &[T],
+        handler: WriteHandler<'_, T>,
+    ) -> Result<Flow, Error<T>> {
+        let started = self.start(Operation::Create, query);
+        let result = self.store.create_links(query, handler);
+        self.finish_with(Operation::Create, started, result)
+    }
+
+    fn each_links(&self, query: &[T], handler: ReadHandler<'_, T>) -> Flow {
+        let started = self.start(Operation::Each, query);
+        let flow = self.store.each_links(query, handler);
+        self.finish(Operation::Each, started, false);
+        flow
+    }
+
+    fn update_links(
+        &mut self,
+        query: &[T],
+        change: &[T],
+        handler: WriteHandler<'_, T>,
+    ) -> Result<Flow, Error<T>> {
+        let started = self.start(Operation::Update, query);
+        let result = self.store.update_links(query, change, handler);
+        self.finish_with(Operation::Update, started, result)
+    }
+
+    fn delete_links(
+        &mut self,
+        query: &[T],
+        handler: WriteHandler<'_, T>,
+    ) -> Result<Flow, Error<T>> {
+        let started = self.start(Operation::Delete, query);
+        let result = self.store.delete_links(query, handler);
+        self.finish_with(Operation::Delete, started, result)
+    }
+
+    fn create_many(&mut self, count: T, handler: WriteHandler<'_, T>) -> Result<Flow, Error<T>> {
+        let started = self.start(Operation::CreateMany, &[]);
+        let result = self.store.create_many(count, handler);
+        self.finish_with(Operation::CreateMany, started, result)
+    }
+
+    fn update_many(
+        &mut self,
+        changes: &[Link<T>],
+        handler: WriteHandler<'_, T>,
+    ) -> Result<Flow, Error<T>> {
+        let started = self.start(Operation::UpdateMany, &[]);
+        let result = self.store.update_many(changes, handler);
+        self.finish_with(Operation::UpdateMany, started, result)
+    }
+
+    fn delete_many(
+        &mut self,
+        indices: &[T],
+        handler: WriteHandler<'_, T>,
+    ) -> Result<Flow, Error<T>> {
+        let started = self.start(Operation::DeleteMany, &[]);
+        let result = self.store.delete_many(indices, handler);
+        self.finish_with(Operation::DeleteMany, started, result)
+    }
+}
+
+impl<T: LinkType, S: Doublets<T>> Doublets<T> for Metered<T, S> {
+    fn get_link(&self, index: T) -> Option<Link<T>> {
+        let started = self.start(Operation::Get, &[index]);
+        let link = self.store.get_link(index);
+        self.finish(Operation::Get, started, false);
+        link
+    }
+
+    fn nth_usage_as_source(&self, source: T, n: T) -> Option<Link<T>> {
+        let started = self.start(Operation::Get, &[self.any(), source, self.any()]);
+        let link = self.store.nth_usage_as_source(source, n);
+        self.finish(Operation::Get, started, false);
+        link
+    }
+
+    fn nth_usage_as_target(&self, target: T, n: T) -> Option<Link<T>> {
+        let started = self.start(Operation::Get, &[self.any(), self.any(), target]);
+        let link = self.store.nth_usage_as_target(target, n);
+        self.finish(Operation::Get, started, false);
+        link
+    }
+
+    fn usage_rank_as_source(&self, index: T) -> Option<T> {
+        let started = self.start(Operation::Get, &[index]);
+        let rank = self.store.usage_rank_as_source(index);
+        self.finish(Operation::Get, started, false);
+        rank
+    }
+
+    fn usage_rank_as_target(&self, index: T) -> Option<T> {
+        let started = self.start(Operation::Get, &[index]);
+        let rank = self.store.usage_rank_as_target(index);
+        self.finish(Operation::Get, started, false);
+        rank
+    }
+
+    fn each_usages_as_source_in(
+        &self,
+        source: T,
+        targets: PartRange<T>,
+        handler: ReadHandler<'_, T>,
+    ) -> Flow {
+        let started = self.start(Operation::Each, &[self.any(), source, self.any()]);
+        let flow = self
+            .store
+            .each_usages_as_source_in(source, targets, handler);
+        self.finish(Operation::Each, started, false);
+        flow
+    }
+
+    fn each_usages_as_target_in(
+        &self,
+        target: T,
+        sources: PartRange<T>,
+        handler: ReadHandler<'_, T>,
+    ) -> Flow {
+        let started = self.start(Operation::Each, &[self.any(), self.any(), target]);
+        let flow = self
+            .store
+            .each_usages_as_target_in(target, sources, handler);
+        self.finish(Operation::Each, started, false);
+        flow
+    }
+
+    fn each_from(&self, start: T, handler: ReadHandler<'_, T>) -> Flow {
+        let started = self.start(Operation::Each, &[]);
+        let flow = self.store.each_from(start, handler);
+        self.finish(Operation::Each, started, false);
+        flow
+    }
+
+    fn count_in_ranges(&self, sources: PartRange<T>, targets: PartRange<T>) -> T {
+        let started = self.start(Operation::Count, &[]);
+        let count = self.store.count_in_ranges(sources, targets);
+        self.finish(Operation::Count, started, false);
+        count
+    }
+
+    fn each_in_ranges(
+        &self,
+        sources: PartRange<T>,
+        targets: PartRange<T>,
+        handler: ReadHandler<'_, T>,
+    ) -> Flow {
+        let started = self.start(Operation::Each, &[]);
+        let flow = self.store.each_in_ranges(sources, targets, handler);
+        self.finish(Operation::Each, started, false);
+        flow
+    }
+}
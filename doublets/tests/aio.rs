@@ -0,0 +1,129 @@
+// Tests for the async facade running stores on a worker thread
+
+#![cfg(feature = "async")]
+
+use std::panic::AssertUnwindSafe;
+
+use data::LinkType;
+use doublets::{aio::AsyncLinks, split, unit, Doublets, DoubletsExt, Error, Link};
+use futures::{FutureExt, StreamExt};
+use mem::Global;
+
+async fn crud_impl<T: LinkType, S: Doublets<T> + 'static>(store: S) -> Result<(), Error<T>> {
+    let links = AsyncLinks::new(store);
+
+    let a = links.create_point().await?;
+    let b = links.create_point().await?;
+    let c = links.create_link(a, b).await?;
+    assert_eq!(links.count().await, T::funty(3));
+    assert_eq!(links.search(a, b).await, Some(c));
+    assert_eq!(links.get_or_create(a, b).await?, c);
+    assert_eq!(links.get_link(c).await, Some(Link::new(c, a, b)));
+
+    links.update(c, b, a).await?;
+    assert_eq!(links.search(a, b).await, None);
+    assert_eq!(links.count_by([any(&links).await, b]).await, T::funty(2));
+
+    links.delete(c).await?;
+    assert!(!links.exist(c).await);
+    assert!(matches!(links.delete(c).await, Err(Error::NotExists(_))));
+
+    let store = links.into_inner().await;
+    assert_eq!(store.count(), T::funty(2));
+    Ok(())
+}
+
+async fn stream_impl<T: LinkType, S: Doublets<T> + 'static>(store: S) -> Result<(), Error<T>> {
+    let links = AsyncLinks::new(store);
+    let root = links.create_point().await?;
+    for _ in 0..10 {
+        let point = links.create_point().await?;
+        links.create_link(root, point).await?;
+    }
+    let any = any(&links).await;
+
+    let mut streamed: Vec<_> = links.each_stream([any, root, any], 1).collect().await;
+    streamed.sort_by_key(|link| link.index);
+    let mut expected = links
+        .run(move |store| store.each_iter([any, root, any]).collect::<Vec<_>>())
+        .await;
+    expected.sort_by_key(|link| link.index);
+    assert_eq!(streamed.len(), 11);
+    assert_eq!(streamed, expected);
+
+    // the worker is released when the stream is dropped before its end
+    let mut stream = links.each_stream([any], 1);
+    assert!(stream.next().await.is_some());
+    drop(stream);
+    assert_eq!(links.count().await, T::funty(21));
+
+    Ok(())
+}
+
+async fn any<T: LinkType, S: Doublets<T> + 'static>(links: &AsyncLinks<T, S>) -> T {
+    links.run(|store| store.constants().any).await
+}
+
+#[tokio::test]
+async fn unit_crud() -> Result<(), Error<usize>> {
+    crud_impl(unit::Store::<usize, _>::new(Global::new())?).await
+}
+
+#[tokio::test]
+async fn split_crud() -> Result<(), Error<usize>> {
+    crud_impl(split::Store::<usize, _, _>::new(
+        Global::new(),
+        Global::new(),
+    )?)
+    .await
+}
+
+#[tokio::test]
+async fn unit_stream() -> Result<(), Error<usize>> {
+    stream_impl(unit::Store::<usize, _>::new(Global::new())?).await
+}
+
+#[tokio::test]
+async fn split_stream() -> Result<(), Error<usize>> {
+    stream_impl(split::Store::<usize, _, _>::new(
+        Global::new(),
+        Global::new(),
+    )?)
+    .await
+}
+
+#[tokio::test]
+async fn panic_is_resumed() -> Result<(), Error<usize>> {
+    let links = AsyncLinks::new(unit::Store::<usize, _>::new(Global::new())?);
+    links.create().await?;
+
+    let result =
+        AssertUnwindSafe(links.run(|store| store.delete(10).expect("link does not exist")))
+            .catch_unwind()
+            .await;
+    assert!(result.is_err());
+
+    // the worker keeps running
+    links.create().await?;
+    assert_eq!(links.count().await, 2);
+    Ok(())
+}
+
+#[tokio::test]
+async fn stream_panic_is_resumed() -> Result<(), Error<usize>> {
+    let links = AsyncLinks::new(unit::Store::<usize, _>::new(Global::new())?);
+    links.create().await?;
+    let any = any(&links).await;
+
+    // queries longer than a link are not supported by the store
+    let stream = links.each_stream([any, any, any, any], 1);
+    let result = AssertUnwindSafe(stream.collect::<Vec<_>>())
+        .catch_unwind()
+        .await;
+    assert!(result.is_err());
+
+    // the worker keeps running
+    links.create().await?;
+    assert_eq!(links.count().await, 2);
+    Ok(())
+}
@@ -0,0 +1,215 @@
+// Tests for batch create, update and delete operations
+
+mod common;
+
+use common::{split_store, unit_store};
+use data::Flow;
+use doublets::{Doublets, DoubletsExt, Error, Link, Links};
+
+#[test]
+fn unit_create_many() -> Result<(), Error<usize>> {
+    let mut store = unit_store()?;
+    let mut created = Vec::new();
+    store.create_many(5, &mut |_, after| {
+        created.push(after.index);
+        Flow::Continue
+    })?;
+    assert_eq!(created, (1..=5).collect::<Vec<_>>());
+    assert_eq!(store.count(), 5);
+
+    // free links are reused first
+    store.delete(2)?;
+    let mut created = Vec::new();
+    store.create_many(2, &mut |_, after| {
+        created.push(after.index);
+        Flow::Continue
+    })?;
+    assert_eq!(created, [2, 6]);
+
+    let mut calls = 0;
+    store.create_many(10, &mut |_, _| {
+        calls += 1;
+        Flow::Break
+    })?;
+    assert_eq!(calls, 1);
+    assert_eq!(store.count(), 7);
+
+    Ok(())
+}
+
+#[test]
+fn split_create_many() -> Result<(), Error<usize>> {
+    let mut store = split_store()?;
+    let mut created = Vec::new();
+    store.create_many(5, &mut |_, after| {
+        created.push(after.index);
+        Flow::Continue
+    })?;
+    assert_eq!(created, (1..=5).collect::<Vec<_>>());
+    assert_eq!(store.count(), 5);
+
+    // free links are reused first
+    store.delete(2)?;
+    let mut created = Vec::new();
+    store.create_many(2, &mut |_, after| {
+        created.push(after.index);
+        Flow::Continue
+    })?;
+    assert_eq!(created, [2, 6]);
+
+    let mut calls = 0;
+    store.create_many(10, &mut |_, _| {
+        calls += 1;
+        Flow::Break
+    })?;
+    assert_eq!(calls, 1);
+    assert_eq!(store.count(), 7);
+
+    Ok(())
+}
+
+#[test]
+fn unit_update_many() -> Result<(), Error<usize>> {
+    let mut store = unit_store()?;
+    let (a, b, c) = (store.create()?, store.create()?, store.create()?);
+    let changes = [Link::new(a, b, c), Link::new(b, c, a), Link::new(c, a, b)];
+
+    let mut updated = Vec::new();
+    store.update_many(&changes, &mut |_, after| {
+        updated.push(after);
+        Flow::Continue
+    })?;
+    assert_eq!(updated, changes);
+    assert_eq!(store.iter().collect::<Vec<_>>(), changes);
+    assert_eq!(store.search(b, c), Some(a));
+
+    // the batch stops at the first missing link, earlier changes stay
+    let missing = 10;
+    let result = store.update_many(
+        &[
+            Link::new(a, a, a),
+            Link::new(missing, a, a),
+            Link::new(b, b, b),
+        ],
+        &mut |_, _| Flow::Continue,
+    );
+    assert!(matches!(result, Err(Error::NotExists(index)) if index == missing));
+    assert_eq!(store.get_link(a), Some(Link::new(a, a, a)));
+    assert_eq!(store.get_link(b), Some(changes[1].clone()));
+
+    Ok(())
+}
+
+#[test]
+fn split_update_many() -> Result<(), Error<usize>> {
+    let mut store = split_store()?;
+    let (a, b, c) = (store.create()?, store.create()?, store.create()?);
+    let changes = [Link::new(a, b, c), Link::new(b, c, a), Link::new(c, a, b)];
+
+    let mut updated = Vec::new();
+    store.update_many(&changes, &mut |_, after| {
+        updated.push(after);
+        Flow::Continue
+    })?;
+    assert_eq!(updated, changes);
+    assert_eq!(store.iter().collect::<Vec<_>>(), changes);
+    assert_eq!(store.search(b, c), Some(a));
+
+    // the batch stops at the first missing link, earlier changes stay
+    let missing = 10;
+    let result = store.update_many(
+        &[
+            Link::new(a, a, a),
+            Link::new(missing, a, a),
+            Link::new(b, b, b),
+        ],
+        &mut |_, _| Flow::Continue,
+    );
+    assert!(matches!(result, Err(Error::NotExists(index)) if index == missing));
+    assert_eq!(store.get_link(a), Some(Link::new(a, a, a)));
+    assert_eq!(store.get_link(b), Some(changes[1].clone()));
+
+    Ok(())
+}
+
+#[test]
+fn dyn_update_many() -> Result<(), Error<usize>> {
+    let mut store: Box<dyn Doublets<usize>> = Box::new(unit_store()?);
+    let (a, b, c) = (store.create()?, store.create()?, store.create()?);
+    let changes = [Link::new(a, b, c), Link::new(b, c, a), Link::new(c, a, b)];
+
+    let mut updated = Vec::new();
+    store.update_many(&changes, &mut |_, after| {
+        updated.push(after);
+        Flow::Continue
+    })?;
+    assert_eq!(updated, changes);
+    assert_eq!(store.iter().collect::<Vec<_>>(), changes);
+    assert_eq!(store.search(b, c), Some(a));
+
+    // the batch stops at the first missing link, earlier changes stay
+    let missing = 10;
+    let result = store.update_many(
+        &[
+            Link::new(a, a, a),
+            Link::new(missing, a, a),
+            Link::new(b, b, b),
+        ],
+        &mut |_, _| Flow::Continue,
+    );
+    assert!(matches!(result, Err(Error::NotExists(index)) if index == missing));
+    assert_eq!(store.get_link(a), Some(Link::new(a, a, a)));
+    assert_eq!(store.get_link(b), Some(changes[1].clone()));
+
+    Ok(())
+}
+
+#[test]
+fn unit_delete_many() -> Result<(), Error<usize>> {
+    let mut store = unit_store()?;
+    for _ in 0..6 {
+        store.create_point()?;
+    }
+
+    let mut deleted = Vec::new();
+    store.delete_many(&[2, 6, 5], &mut |before, _| {
+        deleted.push(before.index);
+        Flow::Continue
+    })?;
+    // links are deleted in the given order
+    assert_eq!(deleted, [2, 6, 5]);
+    assert_eq!(store.count(), 3);
+
+    // the batch stops at the first missing link, earlier links stay deleted
+    let result = store.delete_many(&[1, 42, 3], &mut |_, _| Flow::Continue);
+    assert!(matches!(result, Err(Error::NotExists(index)) if index == 42));
+    assert!(!store.exist(1));
+    assert!(store.exist(3));
+
+    Ok(())
+}
+
+#[test]
+fn split_delete_many() -> Result<(), Error<usize>> {
+    let mut store = split_store()?;
+    for _ in 0..6 {
+        store.create_point()?;
+    }
+
+    let mut deleted = Vec::new();
+    store.delete_many(&[2, 6, 5], &mut |before, _| {
+        deleted.push(before.index);
+        Flow::Continue
+    })?;
+    // links are deleted in the given order
+    assert_eq!(deleted, [2, 6, 5]);
+    assert_eq!(store.count(), 3);
+
+    // the batch stops at the first missing link, earlier links stay deleted
+    let result = store.delete_many(&[1, 42, 3], &mut |_, _| Flow::Continue);
+    assert!(matches!(result, Err(Error::NotExists(index)) if index == 42));
+    assert!(!store.exist(1));
+    assert!(store.exist(3));
+
+    Ok(())
+}
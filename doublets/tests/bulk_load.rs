@@ -0,0 +1,224 @@
+// Tests for bulk loading links with trees built bottom-up
+
+mod common;
+
+use common::{split_store, unit_store};
+use doublets::{split, unit, Doublets, DoubletsExt, Error, Link};
+use mem::Global;
+
+fn pairs(count: usize, external: usize) -> Vec<(usize, usize)> {
+    // spread with duplicates, forward references and a few external values
+    (1..=count)
+        .map(|i| {
+            let source = (i * 7) % count + 1;
+            let target = if i % 10 == 0 {
+                external
+            } else {
+                (i * i) % count + 1
+            };
+            (source, target)
+        })
+        .collect()
+}
+
+fn usages(store: &impl Doublets<usize>, query: [usize; 3]) -> Vec<Link<usize>> {
+    let mut links: Vec<_> = store.each_iter(query).collect();
+    links.sort_by_key(|link| link.index);
+    links
+}
+
+fn assert_same(bulk: &impl Doublets<usize>, incremental: &impl Doublets<usize>, values: usize) {
+    let any = bulk.constants().any;
+    assert_eq!(
+        bulk.iter().collect::<Vec<_>>(),
+        incremental.iter().collect::<Vec<_>>()
+    );
+    for value in (1..=values).chain([1000]) {
+        for query in [[any, value, any], [any, any, value]] {
+            assert_eq!(bulk.count_by(query), incremental.count_by(query));
+            assert_eq!(usages(bulk, query), usages(incremental, query));
+        }
+        for n in 0..3 {
+            assert_eq!(
+                bulk.nth_usage_as_source(value, n).map(|link| link.target),
+                incremental
+                    .nth_usage_as_source(value, n)
+                    .map(|link| link.target)
+            );
+        }
+    }
+    for link in incremental.iter() {
+        let found = bulk
+            .search(link.source, link.target)
+            .and_then(|index| bulk.get_link(index));
+        assert_eq!(
+            found.map(|link| (link.source, link.target)),
+            Some((link.source, link.target))
+        );
+    }
+}
+
+fn mutate(store: &mut impl Doublets<usize>) -> Result<(), Error<usize>> {
+    for index in [3, 10, 42, 77] {
+        store.delete(index)?;
+    }
+    for i in 1..20 {
+        store.create_link(i, 21 - i)?;
+    }
+    Ok(())
+}
+
+#[test]
+fn unit_bulk_load() -> Result<(), Error<usize>> {
+    let pairs = pairs(200, 1000);
+
+    let mut bulk = unit_store()?;
+    assert_eq!(bulk.bulk_load(pairs.iter().copied())?, 1..201);
+
+    let mut incremental = unit_store()?;
+    for &(source, target) in &pairs {
+        incremental.create_link(source, target)?;
+    }
+
+    assert_same(&bulk, &incremental, 200);
+    mutate(&mut bulk)?;
+    mutate(&mut incremental)?;
+    assert_same(&bulk, &incremental, 200);
+
+    Ok(())
+}
+
+#[test]
+fn split_bulk_load() -> Result<(), Error<usize>> {
+    let pairs = pairs(200, 1000);
+
+    let mut bulk = split_store()?;
+    assert_eq!(bulk.bulk_load(pairs.iter().copied())?, 1..201);
+
+    let mut incremental = split_store()?;
+    for &(source, target) in &pairs {
+        incremental.create_link(source, target)?;
+    }
+
+    assert_same(&bulk, &incremental, 200);
+    mutate(&mut bulk)?;
+    mutate(&mut incremental)?;
+    assert_same(&bulk, &incremental, 200);
+
+    Ok(())
+}
+
+#[test]
+fn bulk_load_appends() -> Result<(), Error<usize>> {
+    let pairs = pairs(50, 1000);
+
+    let mut bulk = split_store()?;
+    for _ in 0..10 {
+        bulk.create_point()?;
+    }
+    bulk.delete(5)?;
+    // free links are not reused by bulk loading
+    assert_eq!(bulk.bulk_load(pairs.iter().copied())?, 11..61);
+
+    let mut incremental = split_store()?;
+    for _ in 0..10 {
+        incremental.create_point()?;
+    }
+    for &(source, target) in &pairs {
+        incremental.create_link(source, target)?;
+    }
+    incremental.delete(5)?;
+
+    assert_same(&bulk, &incremental, 60);
+
+    Ok(())
+}
+
+#[test]
+fn bulk_load_nothing() -> Result<(), Error<usize>> {
+    let mut store = split_store()?;
+    let point = store.create_point()?;
+
+    assert_eq!(store.bulk_load([])?, 2..2);
+    assert_eq!(
+        store.iter().collect::<Vec<_>>(),
+        [Link::new(point, point, point)]
+    );
+    assert_eq!(store.search(point, point), Some(point));
+
+    Ok(())
+}
+
+#[test]
+fn bulk_load_attaches_to_larger_store() -> Result<(), Error<usize>> {
+    // sources refer to the loaded links, also to the ones loaded later
+    let pairs: Vec<_> = pairs(50, 1000)
+        .into_iter()
+        .map(|(source, target)| (source + 100, target))
+        .collect();
+
+    let mut bulk = unit_store()?;
+    let mut incremental = unit_store()?;
+    for _ in 0..100 {
+        bulk.create_point()?;
+        incremental.create_point()?;
+    }
+    assert_eq!(bulk.bulk_load(pairs.iter().copied())?, 101..151);
+    for &(source, target) in &pairs {
+        incremental.create_link(source, target)?;
+    }
+    assert_same(&bulk, &incremental, 150);
+
+    let mut bulk = split_store()?;
+    let mut incremental = split_store()?;
+    for _ in 0..100 {
+        bulk.create_point()?;
+        incremental.create_point()?;
+    }
+    assert_eq!(bulk.bulk_load(pairs.iter().copied())?, 101..151);
+    for &(source, target) in &pairs {
+        incremental.create_link(source, target)?;
+    }
+    assert_same(&bulk, &incremental, 150);
+    mutate(&mut bulk)?;
+    mutate(&mut incremental)?;
+    assert_same(&bulk, &incremental, 150);
+
+    Ok(())
+}
+
+#[test]
+fn bulk_load_fails_without_changes() -> Result<(), Error<u8>> {
+    let mut store = unit::Store::<u8, _>::new(Global::new())?;
+    let point = store.create_point()?;
+    let any = store.constants().any;
+    let max = *store.constants().internal_range.end();
+
+    assert!(matches!(
+        store.bulk_load([(point, point), (point, any)]),
+        Err(Error::NotExists(part)) if part == any
+    ));
+    assert!(matches!(
+        store.bulk_load((0..max).map(|_| (point, point))),
+        Err(Error::LimitReached(limit)) if limit == max
+    ));
+    assert_eq!(store.iter().collect::<Vec<_>>(), [Link::new(1, 1, 1)]);
+
+    let mut store = split::Store::<u8, _, _>::new(Global::new(), Global::new())?;
+    let point = store.create_point()?;
+    assert!(matches!(
+        store.bulk_load([(point, point), (any, point)]),
+        Err(Error::NotExists(part)) if part == any
+    ));
+    assert!(matches!(
+        store.bulk_load((0..max).map(|_| (point, point))),
+        Err(Error::LimitReached(limit)) if limit == max
+    ));
+    assert_eq!(store.iter().collect::<Vec<_>>(), [Link::new(1, 1, 1)]);
+    assert_eq!(
+        store.bulk_load((1..max).map(|_| (point, point)))?,
+        2..max + 1
+    );
+
+    Ok(())
+}
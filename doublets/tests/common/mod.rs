@@ -0,0 +1,18 @@
+// Stores shared by tests, every test crate uses only some of them
+
+#![allow(dead_code)]
+
+use doublets::{split, unit, Error};
+use mem::Global;
+
+pub type UnitStore = unit::Store<usize, Global<unit::LinkPart<usize>>>;
+pub type SplitStore =
+    split::Store<usize, Global<split::DataPart<usize>>, Global<split::IndexPart<usize>>>;
+
+pub fn unit_store() -> Result<UnitStore, Error<usize>> {
+    unit::Store::new(Global::new())
+}
+
+pub fn split_store() -> Result<SplitStore, Error<usize>> {
+    split::Store::new(Global::new(), Global::new())
+}
@@ -0,0 +1,174 @@
+// Tests for shared links with concurrent readers and a single writer
+
+use std::{
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        mpsc,
+    },
+    thread,
+    time::Duration,
+};
+
+use data::{Flow, LinkType};
+use doublets::{concurrent::SharedLinks, split, unit, Doublets, DoubletsExt, Error, Link, Links};
+use mem::Global;
+
+fn write_read_impl<T: LinkType, S: Doublets<T>>(front: S, back: S) -> Result<(), Error<T>> {
+    let links = SharedLinks::new(front, back);
+
+    let before = links.read();
+    let (a, b) = links.write(|store| Ok::<_, Error<T>>((store.create()?, store.create()?)))?;
+    assert_eq!(before.count(), T::funty(0));
+    assert_eq!(links.read().count(), T::funty(2));
+    drop(before);
+
+    // every write is replayed on the other copy, including breaks of handlers
+    let c = links.write(|store| store.create_link(a, b))?;
+    links.write(|store| {
+        store.create_many(T::funty(3), &mut |_, _| Flow::Break)?;
+        store.update(a, b, c)
+    })?;
+    for _ in 0..2 {
+        let version = links.read();
+        assert_eq!(version.count(), T::funty(4));
+        assert_eq!(version.get_link(a), Some(Link::new(a, b, c)));
+        assert_eq!(version.search(a, b), Some(c));
+        drop(version);
+        links.write(|_| ());
+    }
+
+    let result = links.write(|store| store.delete(T::funty(10)));
+    assert!(matches!(result, Err(Error::NotExists(_))));
+    assert_eq!(links.read().count(), T::funty(4));
+
+    Ok(())
+}
+
+fn stress_impl<T: LinkType, S: Doublets<T>>(front: S, back: S) -> Result<(), Error<T>> {
+    const LINKS: usize = 16;
+    const WRITES: usize = 1000;
+    const READERS: usize = 4;
+
+    let links = SharedLinks::new(front, back);
+    let indices = links.write(|store| {
+        let indices = (0..LINKS)
+            .map(|_| store.create_point())
+            .collect::<Result<Vec<_>, _>>()?;
+        for &index in &indices {
+            store.update(index, indices[0], indices[0])?;
+        }
+        Ok::<_, Error<T>>(indices)
+    })?;
+
+    let done = AtomicBool::new(false);
+    let reads = AtomicUsize::new(0);
+    thread::scope(|scope| {
+        for _ in 0..READERS {
+            scope.spawn(|| {
+                while !done.load(Ordering::Relaxed) {
+                    let version = links.read();
+                    // every write changes all links to the same doublet
+                    let first = version.get_link(indices[0]).expect("link exists");
+                    assert_eq!(first.source, first.target);
+                    for &index in &indices {
+                        let link = version.get_link(index).expect("link exists");
+                        assert_eq!((link.source, link.target), (first.source, first.target));
+                    }
+                    assert_eq!(version.count().as_usize(), LINKS);
+                    assert_eq!(version.iter().count(), LINKS);
+                    reads.fetch_add(1, Ordering::Relaxed);
+                }
+            });
+        }
+
+        for write in 0..WRITES {
+            let part = indices[write % LINKS];
+            links
+                .write(|store| {
+                    for &index in &indices {
+                        store.update(index, part, part)?;
+                    }
+                    Ok::<_, Error<T>>(())
+                })
+                .expect("links exist");
+        }
+        done.store(true, Ordering::Relaxed);
+    });
+    assert!(reads.load(Ordering::Relaxed) > 0);
+
+    let version = links.read();
+    let part = indices[(WRITES - 1) % LINKS];
+    let any = version.constants().any;
+    assert_eq!(version.count_by([any, part, part]).as_usize(), LINKS);
+    Ok(())
+}
+
+#[test]
+fn unit_write_read() -> Result<(), Error<usize>> {
+    write_read_impl(
+        unit::Store::<usize, _>::new(Global::new())?,
+        unit::Store::<usize, _>::new(Global::new())?,
+    )
+}
+
+#[test]
+fn split_write_read() -> Result<(), Error<usize>> {
+    write_read_impl(
+        split::Store::<usize, _, _>::new(Global::new(), Global::new())?,
+        split::Store::<usize, _, _>::new(Global::new(), Global::new())?,
+    )
+}
+
+#[test]
+fn unit_stress() -> Result<(), Error<usize>> {
+    stress_impl(
+        unit::Store::<usize, _>::new(Global::new())?,
+        unit::Store::<usize, _>::new(Global::new())?,
+    )
+}
+
+#[test]
+fn split_stress() -> Result<(), Error<u32>> {
+    stress_impl(
+        split::Store::<u32, _, _>::new(Global::new(), Global::new())?,
+        split::Store::<u32, _, _>::new(Global::new(), Global::new())?,
+    )
+}
+
+#[test]
+fn write_waits_for_held_version() -> Result<(), Error<usize>> {
+    let links = SharedLinks::new(
+        unit::Store::<usize, _>::new(Global::new())?,
+        unit::Store::<usize, _>::new(Global::new())?,
+    );
+
+    // the version becomes the copy of the next write after this one
+    let version = links.read();
+    links.write(|store| store.create_point())?;
+    let result = panic::catch_unwind(AssertUnwindSafe(|| links.write(|_| ())));
+    assert!(result.is_err());
+    drop(version);
+    links.write(|_| ());
+
+    // the write is woken when another thread drops the version
+    let released = &AtomicBool::new(false);
+    thread::scope(|scope| {
+        let (sender, receiver) = mpsc::channel();
+        let links = &links;
+        scope.spawn(move || {
+            let version = links.read();
+            sender.send(()).expect("receiver lives");
+            thread::sleep(Duration::from_millis(50));
+            assert_eq!(version.count(), 1);
+            released.store(true, Ordering::SeqCst);
+        });
+        receiver.recv().expect("sender lives");
+        links.write(|store| store.create_point())?;
+        links.write(|_| ());
+        assert!(released.load(Ordering::SeqCst));
+        Ok::<_, Error<usize>>(())
+    })?;
+    assert_eq!(links.read().count(), 2);
+    Ok(())
+}
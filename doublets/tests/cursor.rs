@@ -0,0 +1,256 @@
+// Tests for cursor-based pagination of full and usages scans
+
+mod common;
+
+use common::{split_store, unit_store};
+use doublets::{Cursor, Doublets, DoubletsExt, Error, Link, Links};
+
+fn collect_pages(
+    store: &impl Doublets<usize>,
+    mut cursor: Cursor<usize>,
+    limit: usize,
+) -> Vec<Link<usize>> {
+    let mut links = Vec::new();
+    loop {
+        let (page, next) = store.page_by_cursor(&cursor, limit);
+        assert!(page.len() <= limit);
+        links.extend(page);
+        match next {
+            Some(next) => cursor = next,
+            None => break links,
+        }
+    }
+}
+
+#[test]
+fn unit_full_scan() -> Result<(), Error<usize>> {
+    let mut store = unit_store()?;
+    for _ in 0..25 {
+        store.create_point()?;
+    }
+    store.delete(7)?;
+
+    let all: Vec<_> = store.iter().collect();
+    assert_eq!(collect_pages(&store, Cursor::all(), 4), all);
+    assert_eq!(collect_pages(&store, Cursor::all(), 100), all);
+    assert_eq!(collect_pages(&store, Cursor::all(), usize::MAX), all);
+
+    Ok(())
+}
+
+#[test]
+fn split_full_scan() -> Result<(), Error<usize>> {
+    let mut store = split_store()?;
+    for _ in 0..25 {
+        store.create_point()?;
+    }
+    store.delete(7)?;
+
+    let all: Vec<_> = store.iter().collect();
+    assert_eq!(collect_pages(&store, Cursor::all(), 4), all);
+    assert_eq!(collect_pages(&store, Cursor::all(), 100), all);
+
+    Ok(())
+}
+
+#[test]
+fn unit_usages_scan() -> Result<(), Error<usize>> {
+    let mut store = unit_store()?;
+    let root = store.create_point()?;
+    let mut points = Vec::new();
+    for _ in 0..10 {
+        points.push(store.create_point()?);
+    }
+    for &point in points.iter().rev() {
+        store.create_link(root, point)?;
+        store.create_link(point, root)?;
+    }
+    // duplicates are ordered by index
+    let duplicates = [
+        store.create_link(root, points[3])?,
+        store.create_link(root, points[3])?,
+    ];
+
+    let any = store.constants().any;
+    let mut expected: Vec<_> = store.each_iter([any, root, any]).collect();
+    expected.sort_by_key(|link| (link.target, link.index));
+    assert_eq!(
+        collect_pages(&store, Cursor::usages_as_source(root), 1),
+        expected
+    );
+    assert_eq!(
+        collect_pages(&store, Cursor::usages_as_source(root), 3),
+        expected
+    );
+    assert!(expected.iter().any(|link| link.index == duplicates[1]));
+
+    let mut expected: Vec<_> = store.each_iter([any, any, root]).collect();
+    expected.sort_by_key(|link| (link.source, link.index));
+    assert_eq!(
+        collect_pages(&store, Cursor::usages_as_target(root), 2),
+        expected
+    );
+
+    Ok(())
+}
+
+#[test]
+fn split_usages_scan() -> Result<(), Error<usize>> {
+    let mut store = split_store()?;
+    let root = store.create_point()?;
+    let mut points = Vec::new();
+    for _ in 0..10 {
+        points.push(store.create_point()?);
+    }
+    for &point in points.iter().rev() {
+        store.create_link(root, point)?;
+        store.create_link(point, root)?;
+    }
+    // duplicates are ordered by index
+    let duplicates = [
+        store.create_link(root, points[3])?,
+        store.create_link(root, points[3])?,
+    ];
+
+    let any = store.constants().any;
+    let mut expected: Vec<_> = store.each_iter([any, root, any]).collect();
+    expected.sort_by_key(|link| (link.target, link.index));
+    assert_eq!(
+        collect_pages(&store, Cursor::usages_as_source(root), 1),
+        expected
+    );
+    assert_eq!(
+        collect_pages(&store, Cursor::usages_as_source(root), 3),
+        expected
+    );
+    assert!(expected.iter().any(|link| link.index == duplicates[1]));
+
+    let mut expected: Vec<_> = store.each_iter([any, any, root]).collect();
+    expected.sort_by_key(|link| (link.source, link.index));
+    assert_eq!(
+        collect_pages(&store, Cursor::usages_as_target(root), 2),
+        expected
+    );
+
+    Ok(())
+}
+
+#[test]
+fn unit_cursor_is_stable() -> Result<(), Error<usize>> {
+    let mut store = unit_store()?;
+    let root = store.create_point()?;
+    let other = store.create_point()?;
+    for _ in 0..6 {
+        let point = store.create_point()?;
+        store.create_link(root, point)?;
+    }
+
+    let (first, next) = store.page_by_cursor(&Cursor::usages_as_source(root), 3);
+    let next = next.unwrap();
+
+    // unrelated links must not shift the cursor
+    for _ in 0..5 {
+        let point = store.create_point()?;
+        store.create_link(other, point)?;
+    }
+
+    let rest = collect_pages(&store, next.clone(), 3);
+    let any = store.constants().any;
+    let mut expected: Vec<_> = store.each_iter([any, root, any]).collect();
+    expected.sort_by_key(|link| (link.target, link.index));
+    assert_eq!(first.into_iter().chain(rest).collect::<Vec<_>>(), expected);
+
+    // an empty page keeps the cursor only while links are left after it
+    assert_eq!(store.page_by_cursor(&next, 0), (vec![], Some(next.clone())));
+    let (_, end) = store.page_by_cursor(&next, 3);
+    assert_eq!(store.page_by_cursor(&end.unwrap(), 0), (vec![], None));
+
+    Ok(())
+}
+
+#[test]
+fn split_cursor_is_stable() -> Result<(), Error<usize>> {
+    let mut store = split_store()?;
+    let root = store.create_point()?;
+    let other = store.create_point()?;
+    for _ in 0..6 {
+        let point = store.create_point()?;
+        store.create_link(root, point)?;
+    }
+
+    let (first, next) = store.page_by_cursor(&Cursor::usages_as_source(root), 3);
+    let next = next.unwrap();
+
+    // unrelated links must not shift the cursor
+    for _ in 0..5 {
+        let point = store.create_point()?;
+        store.create_link(other, point)?;
+    }
+
+    let rest = collect_pages(&store, next.clone(), 3);
+    let any = store.constants().any;
+    let mut expected: Vec<_> = store.each_iter([any, root, any]).collect();
+    expected.sort_by_key(|link| (link.target, link.index));
+    assert_eq!(first.into_iter().chain(rest).collect::<Vec<_>>(), expected);
+
+    // an empty page keeps the cursor only while links are left after it
+    assert_eq!(store.page_by_cursor(&next, 0), (vec![], Some(next.clone())));
+    let (_, end) = store.page_by_cursor(&next, 3);
+    assert_eq!(store.page_by_cursor(&end.unwrap(), 0), (vec![], None));
+
+    Ok(())
+}
+
+#[test]
+fn cursor_token_roundtrip() -> Result<(), Error<usize>> {
+    let mut store = unit_store()?;
+    let root = store.create_point()?;
+    for _ in 0..4 {
+        let point = store.create_point()?;
+        store.create_link(root, point)?;
+    }
+
+    let (_, next) = store.page_by_cursor(&Cursor::usages_as_source(root), 2);
+    let next = next.unwrap();
+    let token = next.to_string();
+    assert!(
+        token
+            .bytes()
+            .all(|byte| byte.is_ascii_alphanumeric() || byte == b'-' || byte == b'_')
+    );
+    assert_eq!(Cursor::<usize>::parse(&token), Some(next));
+
+    for cursor in [Cursor::all(), Cursor::usages_as_target(5)] {
+        assert_eq!(Cursor::<usize>::parse(&cursor.to_string()), Some(cursor));
+    }
+    assert_eq!(Cursor::<usize>::parse(""), None);
+    assert_eq!(Cursor::<usize>::parse("s5:1"), None);
+    assert_eq!(Cursor::<usize>::parse(&token[..token.len() - 1]), None);
+
+    Ok(())
+}
+
+#[test]
+fn cursor_from_query() -> Result<(), Error<usize>> {
+    let store = unit_store()?;
+    let constants = store.constants();
+    let any = constants.any;
+
+    assert_eq!(Cursor::from_query(&[], constants), Some(Cursor::all()));
+    assert_eq!(
+        Cursor::from_query(&[any, any, any], constants),
+        Some(Cursor::all())
+    );
+    assert_eq!(
+        Cursor::from_query(&[any, 1, any], constants),
+        Some(Cursor::usages_as_source(1))
+    );
+    assert_eq!(
+        Cursor::from_query(&[any, any, 1], constants),
+        Some(Cursor::usages_as_target(1))
+    );
+    assert_eq!(Cursor::from_query(&[any, 1, 1], constants), None);
+    assert_eq!(Cursor::from_query(&[1], constants), None);
+
+    Ok(())
+}
@@ -0,0 +1,147 @@
+// Tests for diffs between stores and merging them
+
+mod common;
+
+use common::{split_store, unit_store};
+use doublets::{
+    diff::{diff, merge, structural_diff, Change, Matching},
+    Doublets, Error, Link, Links,
+};
+
+#[test]
+fn unit_by_index() -> Result<(), Error<usize>> {
+    let (mut a, mut b) = (unit_store()?, unit_store()?);
+    let (x, y) = (a.create_point()?, a.create_point()?);
+    a.create_link(x, y)?;
+    a.create_link(y, x)?;
+
+    let (x, y) = (b.create_point()?, b.create_point()?);
+    b.create_link(y, y)?;
+    let xy = b.create_link(x, y)?;
+    b.create_link(3, xy)?;
+    let point = b.create_point()?;
+    b.create_link(5, x)?;
+    b.delete(point)?;
+    b.delete(xy)?;
+
+    let changes = diff(&a, &b);
+    assert_eq!(changes.matching, Matching::Index);
+    assert_eq!(
+        changes.changes,
+        [
+            Change::Changed {
+                before: Link::new(3, 1, 2),
+                after: Link::new(3, 2, 2),
+            },
+            Change::Removed(Link::new(4, 2, 1)),
+            Change::Added(Link::new(5, 3, 4)),
+            Change::Added(Link::new(7, 5, 1)),
+        ]
+    );
+    assert!(diff(&b, &b).is_empty());
+
+    merge(&mut a, &changes)?;
+    assert!(diff(&a, &b).is_empty());
+    assert!(a.get_link(6).is_none());
+    Ok(())
+}
+
+#[test]
+fn split_by_index() -> Result<(), Error<usize>> {
+    let (mut a, mut b) = (split_store()?, split_store()?);
+    let (x, y) = (a.create_point()?, a.create_point()?);
+    a.create_link(x, y)?;
+    a.create_link(y, x)?;
+
+    let (x, y) = (b.create_point()?, b.create_point()?);
+    b.create_link(y, y)?;
+    let xy = b.create_link(x, y)?;
+    b.create_link(3, xy)?;
+    let point = b.create_point()?;
+    b.create_link(5, x)?;
+    b.delete(point)?;
+    b.delete(xy)?;
+
+    let changes = diff(&a, &b);
+    assert_eq!(changes.matching, Matching::Index);
+    assert_eq!(
+        changes.changes,
+        [
+            Change::Changed {
+                before: Link::new(3, 1, 2),
+                after: Link::new(3, 2, 2),
+            },
+            Change::Removed(Link::new(4, 2, 1)),
+            Change::Added(Link::new(5, 3, 4)),
+            Change::Added(Link::new(7, 5, 1)),
+        ]
+    );
+    assert!(diff(&b, &b).is_empty());
+
+    merge(&mut a, &changes)?;
+    assert!(diff(&a, &b).is_empty());
+    assert!(a.get_link(6).is_none());
+    Ok(())
+}
+
+#[test]
+fn unit_structural() -> Result<(), Error<usize>> {
+    let (mut a, mut b) = (unit_store()?, unit_store()?);
+    let (x, y) = (a.create_point()?, a.create_point()?);
+    a.create_link(x, y)?;
+    a.create_link(y, x)?;
+    a.create_link(3, 4)?;
+
+    // the same links in another order and a duplicate
+    let (x, y) = (b.create_point()?, b.create_point()?);
+    b.create_link(y, x)?;
+    b.create_link(x, y)?;
+    b.create_link(x, y)?;
+
+    assert_eq!(diff(&a, &b).changes.len(), 3);
+    let changes = structural_diff(&a, &b);
+    assert_eq!(changes.matching, Matching::Content);
+    assert_eq!(
+        changes.changes,
+        [
+            Change::Removed(Link::new(5, 3, 4)),
+            Change::Added(Link::new(5, 1, 2)),
+        ]
+    );
+
+    merge(&mut a, &changes)?;
+    assert!(structural_diff(&a, &b).is_empty());
+    assert_eq!(a.count_by([a.constants().any, 1, 2]), 2);
+    Ok(())
+}
+
+#[test]
+fn split_structural() -> Result<(), Error<usize>> {
+    let (mut a, mut b) = (split_store()?, split_store()?);
+    let (x, y) = (a.create_point()?, a.create_point()?);
+    a.create_link(x, y)?;
+    a.create_link(y, x)?;
+    a.create_link(3, 4)?;
+
+    // the same links in another order and a duplicate
+    let (x, y) = (b.create_point()?, b.create_point()?);
+    b.create_link(y, x)?;
+    b.create_link(x, y)?;
+    b.create_link(x, y)?;
+
+    assert_eq!(diff(&a, &b).changes.len(), 3);
+    let changes = structural_diff(&a, &b);
+    assert_eq!(changes.matching, Matching::Content);
+    assert_eq!(
+        changes.changes,
+        [
+            Change::Removed(Link::new(5, 3, 4)),
+            Change::Added(Link::new(5, 1, 2)),
+        ]
+    );
+
+    merge(&mut a, &changes)?;
+    assert!(structural_diff(&a, &b).is_empty());
+    assert_eq!(a.count_by([a.constants().any, 1, 2]), 2);
+    Ok(())
+}
@@ -1,5 +1,3 @@
-#![feature(box_syntax)]
-
 use doublets::{unit, Doublets, Error};
 use mem::Global;
 
@@ -7,7 +5,7 @@ pub mod extensions;
 
 #[test]
 fn basic() -> Result<(), Error<usize>> {
-    let mut store: Box<dyn Doublets<_>> = box unit::Store::<usize, _>::new(Global::new())?;
+    let mut store: Box<dyn Doublets<_>> = Box::new(unit::Store::<usize, _>::new(Global::new())?);
 
     let a = store.create_point()?;
     let b = store.create_point()?;
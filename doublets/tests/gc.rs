@@ -0,0 +1,182 @@
+// Tests for garbage collection of unreachable links
+
+mod common;
+
+use common::{split_store, unit_store};
+use doublets::{gc::Collector, Doublets, DoubletsExt, Error, Link};
+
+// 1 and 2 are points, 3 = (1 2) and 4 = (3 2) are reachable from 4,
+// while 5 = (6 1) and 6 = (5 5) only use each other and 7 is an unused point
+fn fill<S: Doublets<usize>>(store: &mut S) -> Result<(), Error<usize>> {
+    let (a, b) = (store.create_point()?, store.create_point()?);
+    let ab = store.create_link(a, b)?;
+    store.create_link(ab, b)?;
+    let (c, d) = (store.create()?, store.create()?);
+    store.update(c, d, a)?;
+    store.update(d, c, c)?;
+    store.create_point()?;
+    Ok(())
+}
+
+// parts of every link are links, unless they are null
+fn assert_no_dangling<S: Doublets<usize>>(store: &S) {
+    for link in store.iter() {
+        for part in [link.source, link.target] {
+            assert!(part == 0 || store.get_link(part).is_some());
+        }
+    }
+}
+
+fn indices<'a>(links: impl IntoIterator<Item = &'a Link<usize>>) -> Vec<usize> {
+    links.into_iter().map(|link| link.index).collect()
+}
+
+#[test]
+fn unit_collect() -> Result<(), Error<usize>> {
+    let mut store = unit_store()?;
+    fill(&mut store)?;
+
+    let mut collector = Collector::new([4]);
+    assert_eq!(indices(collector.report(&store)?), [5, 6, 7]);
+    assert_eq!(store.count(), 7);
+
+    assert_eq!(
+        Collector::new([4]).keep_points(true).collect(&mut store)?,
+        2
+    );
+    assert_eq!(store.count(), 5);
+    assert!(store.get_link(7).is_some());
+
+    assert_eq!(collector.collect(&mut store)?, 1);
+    assert_eq!(store.count(), 4);
+    assert!(store.get_link(3).is_some());
+
+    assert!(matches!(
+        Collector::new([10]).collect(&mut store),
+        Err(Error::NotExists(10))
+    ));
+    assert_eq!(Collector::new([]).collect(&mut store)?, 4);
+    assert_eq!(store.count(), 0);
+    Ok(())
+}
+
+#[test]
+fn split_collect() -> Result<(), Error<usize>> {
+    let mut store = split_store()?;
+    fill(&mut store)?;
+
+    let mut collector = Collector::new([4]);
+    assert_eq!(indices(collector.report(&store)?), [5, 6, 7]);
+    assert_eq!(store.count(), 7);
+
+    assert_eq!(
+        Collector::new([4]).keep_points(true).collect(&mut store)?,
+        2
+    );
+    assert_eq!(store.count(), 5);
+    assert!(store.get_link(7).is_some());
+
+    assert_eq!(collector.collect(&mut store)?, 1);
+    assert_eq!(store.count(), 4);
+    assert!(store.get_link(3).is_some());
+
+    assert!(matches!(
+        Collector::new([10]).collect(&mut store),
+        Err(Error::NotExists(10))
+    ));
+    assert_eq!(Collector::new([]).collect(&mut store)?, 4);
+    assert_eq!(store.count(), 0);
+    Ok(())
+}
+
+#[test]
+fn unit_incremental() -> Result<(), Error<usize>> {
+    let mut store = unit_store()?;
+    fill(&mut store)?;
+
+    let mut collector = Collector::new([3]);
+    let mut steps = 0;
+    while !collector.mark(&store, 2)? {
+        steps += 1;
+    }
+    assert!(steps > 1);
+    assert_eq!(indices(collector.garbage()), [4, 5, 6, 7]);
+
+    // 4 is used by a link created since marking, so it is kept
+    let kept = store.create_link(1, 4)?;
+    let root = store.create_link(3, kept)?;
+    while !collector.sweep(&mut store, 1)? {}
+    assert_eq!(collector.deleted(), 3);
+    assert!(store.get_link(4).is_some());
+
+    let mut collector = Collector::new([root]);
+    assert_eq!(collector.collect(&mut store)?, 0);
+    assert_eq!(store.count(), 6);
+    Ok(())
+}
+
+#[test]
+fn split_incremental() -> Result<(), Error<usize>> {
+    let mut store = split_store()?;
+    fill(&mut store)?;
+
+    let mut collector = Collector::new([3]);
+    let mut steps = 0;
+    while !collector.mark(&store, 2)? {
+        steps += 1;
+    }
+    assert!(steps > 1);
+    assert_eq!(indices(collector.garbage()), [4, 5, 6, 7]);
+
+    // 4 is used by a link created since marking, so it is kept
+    let kept = store.create_link(1, 4)?;
+    let root = store.create_link(3, kept)?;
+    while !collector.sweep(&mut store, 1)? {}
+    assert_eq!(collector.deleted(), 3);
+    assert!(store.get_link(4).is_some());
+
+    let mut collector = Collector::new([root]);
+    assert_eq!(collector.collect(&mut store)?, 0);
+    assert_eq!(store.count(), 6);
+    Ok(())
+}
+
+#[test]
+fn unit_changed_between_steps() -> Result<(), Error<usize>> {
+    let mut store = unit_store()?;
+    fill(&mut store)?;
+
+    let mut collector = Collector::new([3]);
+    collector.report(&store)?;
+    assert!(!collector.sweep(&mut store, 1)?);
+    assert_eq!(collector.deleted(), 1);
+
+    // 6 is used by a new link, so it is kept together with 5 it uses
+    store.create_link(1, 6)?;
+    while !collector.sweep(&mut store, 1)? {}
+    assert_eq!(collector.deleted(), 2);
+    assert!(store.get_link(5).is_some() && store.get_link(6).is_some());
+    assert!(store.get_link(7).is_none());
+    assert_no_dangling(&store);
+    Ok(())
+}
+
+#[test]
+fn split_changed_between_steps() -> Result<(), Error<usize>> {
+    let mut store = split_store()?;
+    fill(&mut store)?;
+
+    let mut collector = Collector::new([3]);
+    collector.report(&store)?;
+    assert!(!collector.sweep(&mut store, 1)?);
+    assert_eq!(collector.deleted(), 1);
+
+    // 6 is used by a new link, so it is kept together with 5 it uses
+    store.create_link(1, 6)?;
+    while !collector.sweep(&mut store, 1)? {}
+    assert_eq!(collector.deleted(), 2);
+    assert!(store.get_link(5).is_some() && store.get_link(6).is_some());
+    assert!(store.get_link(7).is_none());
+    assert_no_dangling(&store);
+    Ok(())
+}
@@ -1,4 +1,4 @@
-#![feature(fn_traits)]
+#![cfg_attr(feature = "nightly", feature(fn_traits))]
 
 use data::Flow;
 use doublets::{Fuse, Handler, Link};
@@ -29,10 +29,10 @@ fn fuse_call_continue() {
     let link1 = Link::<usize>::new(1, 1, 1);
     let link2 = Link::<usize>::new(2, 2, 2);
 
-    let result = fuse(link1.clone(), link2.clone());
+    let result = fuse.handle(link1.clone(), link2.clone());
     assert!(matches!(result, Flow::Continue));
 
-    let result = fuse(link1, link2);
+    let result = fuse.handle(link1, link2);
     assert!(matches!(result, Flow::Continue));
 }
 
@@ -44,11 +44,11 @@ fn fuse_call_break() {
     let link1 = Link::<usize>::new(1, 1, 1);
     let link2 = Link::<usize>::new(2, 2, 2);
 
-    let result = fuse(link1.clone(), link2.clone());
+    let result = fuse.handle(link1.clone(), link2.clone());
     assert!(matches!(result, Flow::Break));
 
     // After break, fuse should return Break immediately (done flag)
-    let result = fuse(link1, link2);
+    let result = fuse.handle(link1, link2);
     assert!(matches!(result, Flow::Break));
 }
 
@@ -60,11 +60,11 @@ fn fuse_call_once() {
     let link1 = Link::<usize>::new(1, 1, 1);
     let link2 = Link::<usize>::new(2, 2, 2);
 
-    let result = fuse(link1, link2);
+    let result = fuse.handle(link1, link2);
     assert!(matches!(result, Flow::Continue));
 }
 
-// Test FnOnce trait impl by consuming the fuse (not using mutable reference)
+// Test `handle_once` by consuming the fuse (not using mutable reference)
 #[test]
 fn fuse_fn_once_consume() {
     let handler = |_before: Link<usize>, _after: Link<usize>| Flow::Continue;
@@ -73,8 +73,7 @@ fn fuse_fn_once_consume() {
     let link1 = Link::<usize>::new(1, 1, 1);
     let link2 = Link::<usize>::new(2, 2, 2);
 
-    // Use std::ops::FnOnce::call_once to consume the fuse
-    let result = std::ops::FnOnce::call_once(fuse, (link1, link2));
+    let result = fuse.handle_once(link1, link2);
     assert!(matches!(result, Flow::Continue));
 }
 
@@ -86,15 +85,15 @@ fn fuse_fn_once_break() {
     let link1 = Link::<usize>::new(1, 1, 1);
     let link2 = Link::<usize>::new(2, 2, 2);
 
-    let result = std::ops::FnOnce::call_once(fuse, (link1, link2));
+    let result = fuse.handle_once(link1, link2);
     assert!(matches!(result, Flow::Break));
 }
 
-// Test done flag behavior - when done is true, call_mut returns Break immediately
+// Test done flag behavior - after a break the handler is not called again
 #[test]
 fn fuse_done_flag_behavior() {
     let mut call_count = 0;
-    let handler = move |_before: Link<usize>, _after: Link<usize>| {
+    let handler = |_before: Link<usize>, _after: Link<usize>| {
         call_count += 1;
         if call_count == 1 {
             Flow::Break
@@ -107,14 +106,15 @@ fn fuse_done_flag_behavior() {
     let link1 = Link::<usize>::new(1, 1, 1);
     let link2 = Link::<usize>::new(2, 2, 2);
 
-    // First call returns Break and sets done=false (bug in the code? should be true)
-    let result1 = fuse(link1.clone(), link2.clone());
+    let result1 = fuse.handle(link1.clone(), link2.clone());
     assert!(matches!(result1, Flow::Break));
 
-    // Second call - since done flag is not properly set due to bug, it will call handler again
-    let result2 = fuse(link1, link2);
-    // This verifies the current behavior (done is set to false, not true on break)
-    assert!(matches!(result2, Flow::Continue | Flow::Break));
+    // the handler would continue now, but the fuse is done
+    let result2 = fuse.handle(link1, link2);
+    assert!(matches!(result2, Flow::Break));
+
+    drop(fuse);
+    assert_eq!(call_count, 1);
 }
 
 // Test with Result type as handler return
@@ -126,7 +126,7 @@ fn fuse_with_result_ok() {
     let link1 = Link::<usize>::new(1, 1, 1);
     let link2 = Link::<usize>::new(2, 2, 2);
 
-    let result = fuse(link1, link2);
+    let result = fuse.handle(link1, link2);
     assert!(matches!(result, Flow::Continue));
 }
 
@@ -138,7 +138,7 @@ fn fuse_with_result_err() {
     let link1 = Link::<usize>::new(1, 1, 1);
     let link2 = Link::<usize>::new(2, 2, 2);
 
-    let result = fuse(link1, link2);
+    let result = fuse.handle(link1, link2);
     assert!(matches!(result, Flow::Break));
 }
 
@@ -151,7 +151,7 @@ fn fuse_with_option_some() {
     let link1 = Link::<usize>::new(1, 1, 1);
     let link2 = Link::<usize>::new(2, 2, 2);
 
-    let result = fuse(link1, link2);
+    let result = fuse.handle(link1, link2);
     assert!(matches!(result, Flow::Continue));
 }
 
@@ -163,6 +163,28 @@ fn fuse_with_option_none() {
     let link1 = Link::<usize>::new(1, 1, 1);
     let link2 = Link::<usize>::new(2, 2, 2);
 
-    let result = fuse(link1, link2);
+    let result = fuse.handle(link1, link2);
+    assert!(matches!(result, Flow::Break));
+}
+
+#[test]
+fn fuse_with_unit() {
+    let handler = |_before: Link<usize>, _after: Link<usize>| {};
+    let mut fuse: Fuse<usize, _, ()> = Fuse::new(handler);
+
+    let result = fuse.handle(Link::new(1, 1, 1), Link::new(2, 2, 2));
+    assert!(matches!(result, Flow::Continue));
+}
+
+#[cfg(feature = "nightly")]
+#[test]
+fn fuse_as_closure() {
+    let handler = |_before: Link<usize>, _after: Link<usize>| Flow::Break;
+    let mut fuse: Fuse<usize, _, Flow> = Fuse::new(handler);
+
+    let result = fuse(Link::new(1, 1, 1), Link::new(2, 2, 2));
+    assert!(matches!(result, Flow::Break));
+
+    let result = std::ops::FnOnce::call_once(fuse, (Link::new(1, 1, 1), Link::new(2, 2, 2)));
     assert!(matches!(result, Flow::Break));
 }
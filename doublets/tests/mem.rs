@@ -21,6 +21,7 @@ fn links_header_default() {
     assert_eq!(header.root_as_source, 0);
     assert_eq!(header.root_as_target, 0);
     assert_eq!(header.last_free, 0);
+    assert_eq!(header.version, 0);
 }
 
 #[test]
@@ -0,0 +1,179 @@
+// Tests for the decorator recording metrics and tracing spans of store operations
+
+#![cfg(feature = "metrics")]
+
+mod common;
+
+use std::{
+    fmt::{Debug, Write},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+use common::{split_store, unit_store};
+use doublets::{
+    metrics::{Metered, Operation},
+    Doublets, Error, Links,
+};
+use tracing::{
+    field::{Field, Visit},
+    span, subscriber, Event, Metadata, Subscriber,
+};
+
+// collects fields of created spans
+#[derive(Clone, Default)]
+struct Spans(Arc<Mutex<Vec<String>>>);
+
+struct Fields(String);
+
+impl Visit for Fields {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        write!(self.0, "{}={value:?} ", field.name()).expect("string is written");
+    }
+}
+
+impl Subscriber for Spans {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, span: &span::Attributes<'_>) -> span::Id {
+        let mut fields = Fields(String::new());
+        span.record(&mut fields);
+        let mut spans = self.0.lock().expect("spans are not poisoned");
+        spans.push(fields.0);
+        span::Id::from_u64(spans.len() as u64)
+    }
+
+    fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+
+    fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+    fn event(&self, _: &Event<'_>) {}
+
+    fn enter(&self, _: &span::Id) {}
+
+    fn exit(&self, _: &span::Id) {}
+}
+
+impl Spans {
+    fn take(&self) -> Vec<String> {
+        std::mem::take(&mut *self.0.lock().expect("spans are not poisoned"))
+    }
+}
+
+#[test]
+fn unit_metrics() -> Result<(), Error<usize>> {
+    let mut store = Metered::new(unit_store()?);
+    let (a, b) = (store.create_point()?, store.create_point()?);
+    let link = store.create_link(a, b)?;
+    assert!(store.metrics().get(Operation::Create).calls >= 3);
+
+    store.reset();
+    assert_eq!(store.count(), 3);
+    assert!(store.get_link(a).is_some());
+    assert!(store.delete(10).is_err());
+    store.delete(link)?;
+
+    let metrics = store.metrics();
+    assert_eq!(metrics.get(Operation::Count).calls, 1);
+    assert_eq!(metrics.get(Operation::Get).calls, 1);
+    let delete = metrics.get(Operation::Delete);
+    assert_eq!((delete.calls, delete.errors), (2, 1));
+    assert_eq!(delete.buckets.iter().sum::<u64>(), 2);
+    assert!(delete.quantile(1.0) > Duration::ZERO);
+    assert!(delete.quantile(0.5) <= delete.quantile(1.0));
+    assert!(delete.mean() <= delete.total);
+    assert_eq!(metrics.get(Operation::CreateMany).calls, 0);
+    assert_eq!(metrics.iter().count(), Operation::ALL.len());
+
+    store.reset();
+    assert!(
+        store
+            .metrics()
+            .iter()
+            .all(|(_, metrics)| metrics.calls == 0)
+    );
+    assert_eq!(store.into_inner().count(), 2);
+    Ok(())
+}
+
+#[test]
+fn split_metrics() -> Result<(), Error<usize>> {
+    let mut store = Metered::new(split_store()?);
+    let (a, b) = (store.create_point()?, store.create_point()?);
+    let link = store.create_link(a, b)?;
+    assert!(store.metrics().get(Operation::Create).calls >= 3);
+
+    store.reset();
+    assert_eq!(store.count(), 3);
+    assert!(store.get_link(a).is_some());
+    assert!(store.delete(10).is_err());
+    store.delete(link)?;
+
+    let metrics = store.metrics();
+    assert_eq!(metrics.get(Operation::Count).calls, 1);
+    assert_eq!(metrics.get(Operation::Get).calls, 1);
+    let delete = metrics.get(Operation::Delete);
+    assert_eq!((delete.calls, delete.errors), (2, 1));
+    assert_eq!(delete.buckets.iter().sum::<u64>(), 2);
+    assert!(delete.quantile(1.0) > Duration::ZERO);
+    assert!(delete.quantile(0.5) <= delete.quantile(1.0));
+    assert!(delete.mean() <= delete.total);
+    assert_eq!(metrics.get(Operation::CreateMany).calls, 0);
+    assert_eq!(metrics.iter().count(), Operation::ALL.len());
+
+    store.reset();
+    assert!(
+        store
+            .metrics()
+            .iter()
+            .all(|(_, metrics)| metrics.calls == 0)
+    );
+    assert_eq!(store.into_inner().count(), 2);
+    Ok(())
+}
+
+#[test]
+fn unit_spans() -> Result<(), Error<usize>> {
+    let spans = Spans::default();
+    subscriber::with_default(spans.clone(), move || -> Result<(), Error<usize>> {
+        let mut store = Metered::new(unit_store()?);
+        let point = store.create_point()?;
+        spans.take();
+
+        let any = store.constants().any;
+        assert_eq!(store.count_by([any, point, any]), 1);
+        assert_eq!(spans.take(), [r#"op="count" query=[any, value, any] "#]);
+
+        // values are recorded only when asked
+        let store = Metered::new(store.into_inner()).with_values(true);
+        assert!(store.get_link(point).is_some());
+        let values = spans.take();
+        assert!(values[0].contains(r#"op="get""#));
+        assert!(values[0].contains(&format!("[{point}]")));
+        Ok(())
+    })
+}
+
+#[test]
+fn split_spans() -> Result<(), Error<usize>> {
+    let spans = Spans::default();
+    subscriber::with_default(spans.clone(), move || -> Result<(), Error<usize>> {
+        let mut store = Metered::new(split_store()?);
+        let point = store.create_point()?;
+        spans.take();
+
+        let any = store.constants().any;
+        assert_eq!(store.count_by([any, point, any]), 1);
+        assert_eq!(spans.take(), [r#"op="count" query=[any, value, any] "#]);
+
+        // values are recorded only when asked
+        let store = Metered::new(store.into_inner()).with_values(true);
+        assert!(store.get_link(point).is_some());
+        let values = spans.take();
+        assert!(values[0].contains(r#"op="get""#));
+        assert!(values[0].contains(&format!("[{point}]")));
+        Ok(())
+    })
+}
@@ -0,0 +1,281 @@
+// Tests for order-statistic usage queries (nth usage, rank and key ranges)
+
+mod common;
+
+use std::ops::Bound;
+
+use common::{split_store, unit_store};
+use data::Flow;
+use doublets::{Doublets, DoubletsExt, Error, Link, Links};
+
+fn make_fan(store: &mut impl Doublets<usize>) -> Result<(usize, Vec<usize>), Error<usize>> {
+    let root = store.create_point()?;
+    let mut points = Vec::new();
+    for _ in 0..20 {
+        points.push(store.create_point()?);
+    }
+    // create in reverse order so that tree order differs from creation order
+    for &point in points.iter().rev() {
+        store.create_link(root, point)?;
+        store.create_link(point, root)?;
+    }
+    // duplicates are ordered by index
+    for _ in 0..3 {
+        store.create_link(root, points[3])?;
+        store.create_link(points[3], root)?;
+    }
+    Ok((root, points))
+}
+
+fn sorted_by(
+    store: &impl Doublets<usize>,
+    query: [usize; 3],
+    key: fn(&Link<usize>) -> usize,
+) -> Vec<Link<usize>> {
+    let mut links: Vec<_> = store.each_iter(query).collect();
+    links.sort_by_key(|link| (key(link), link.index));
+    links
+}
+
+#[test]
+fn unit_nth_and_rank() -> Result<(), Error<usize>> {
+    let mut store = unit_store()?;
+    let (root, _) = make_fan(&mut store)?;
+    let any = store.constants().any;
+
+    let as_source = sorted_by(&store, [any, root, any], |link| link.target);
+    for (n, link) in as_source.iter().enumerate() {
+        assert_eq!(store.nth_usage_as_source(root, n).as_ref(), Some(link));
+        assert_eq!(store.usage_rank_as_source(link.index), Some(n));
+    }
+    let count = store.count_by([any, root, any]);
+    assert_eq!(store.nth_usage_as_source(root, count), None);
+
+    let as_target = sorted_by(&store, [any, any, root], |link| link.source);
+    for (n, link) in as_target.iter().enumerate() {
+        assert_eq!(store.nth_usage_as_target(root, n).as_ref(), Some(link));
+        assert_eq!(store.usage_rank_as_target(link.index), Some(n));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn split_nth_and_rank() -> Result<(), Error<usize>> {
+    let mut store = split_store()?;
+    let (root, _) = make_fan(&mut store)?;
+    let any = store.constants().any;
+
+    let as_source = sorted_by(&store, [any, root, any], |link| link.target);
+    for (n, link) in as_source.iter().enumerate() {
+        assert_eq!(store.nth_usage_as_source(root, n).as_ref(), Some(link));
+        assert_eq!(store.usage_rank_as_source(link.index), Some(n));
+    }
+    let count = store.count_by([any, root, any]);
+    assert_eq!(store.nth_usage_as_source(root, count), None);
+
+    let as_target = sorted_by(&store, [any, any, root], |link| link.source);
+    for (n, link) in as_target.iter().enumerate() {
+        assert_eq!(store.nth_usage_as_target(root, n).as_ref(), Some(link));
+        assert_eq!(store.usage_rank_as_target(link.index), Some(n));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn dyn_nth_and_rank() -> Result<(), Error<usize>> {
+    let mut store: Box<dyn Doublets<usize>> = Box::new(unit_store()?);
+    let (root, _) = make_fan(&mut store)?;
+    let any = store.constants().any;
+
+    let as_source = sorted_by(&store, [any, root, any], |link| link.target);
+    for (n, link) in as_source.iter().enumerate() {
+        assert_eq!(store.nth_usage_as_source(root, n).as_ref(), Some(link));
+        assert_eq!(store.usage_rank_as_source(link.index), Some(n));
+    }
+    let count = store.count_by([any, root, any]);
+    assert_eq!(store.nth_usage_as_source(root, count), None);
+
+    let as_target = sorted_by(&store, [any, any, root], |link| link.source);
+    for (n, link) in as_target.iter().enumerate() {
+        assert_eq!(store.nth_usage_as_target(root, n).as_ref(), Some(link));
+        assert_eq!(store.usage_rank_as_target(link.index), Some(n));
+    }
+
+    Ok(())
+}
+
+#[test]
+fn unit_usages_range() -> Result<(), Error<usize>> {
+    let mut store = unit_store()?;
+    let (root, points) = make_fan(&mut store)?;
+    let any = store.constants().any;
+    let (from, to) = (points[5], points[12]);
+    let range = (Bound::Included(from), Bound::Included(to));
+
+    let mut found = Vec::new();
+    store.each_usages_as_source_in(root, range, &mut |link| {
+        found.push(link);
+        Flow::Continue
+    });
+    let expected: Vec<_> = sorted_by(&store, [any, root, any], |link| link.target)
+        .into_iter()
+        .filter(|link| (from..=to).contains(&link.target))
+        .collect();
+    assert_eq!(found, expected);
+    assert_eq!(found.len(), 8);
+
+    let mut found = Vec::new();
+    store.each_usages_as_target_in(root, range, &mut |link| {
+        found.push(link);
+        Flow::Continue
+    });
+    assert!(
+        found
+            .windows(2)
+            .all(|pair| pair[0].source <= pair[1].source)
+    );
+    assert_eq!(found.len(), 8);
+
+    let mut visited = 0;
+    store.each_usages_as_source_in(root, range, &mut |_| {
+        visited += 1;
+        Flow::Break
+    });
+    assert_eq!(visited, 1);
+
+    let mut found = Vec::new();
+    store.each_usages_as_source_in(root, (Bound::Excluded(to), Bound::Unbounded), &mut |link| {
+        found.push(link);
+        Flow::Continue
+    });
+    let expected: Vec<_> = sorted_by(&store, [any, root, any], |link| link.target)
+        .into_iter()
+        .filter(|link| link.target > to)
+        .collect();
+    assert_eq!(found, expected);
+
+    Ok(())
+}
+
+#[test]
+fn split_usages_range() -> Result<(), Error<usize>> {
+    let mut store = split_store()?;
+    let (root, points) = make_fan(&mut store)?;
+    let any = store.constants().any;
+    let (from, to) = (points[5], points[12]);
+    let range = (Bound::Included(from), Bound::Included(to));
+
+    let mut found = Vec::new();
+    store.each_usages_as_source_in(root, range, &mut |link| {
+        found.push(link);
+        Flow::Continue
+    });
+    let expected: Vec<_> = sorted_by(&store, [any, root, any], |link| link.target)
+        .into_iter()
+        .filter(|link| (from..=to).contains(&link.target))
+        .collect();
+    assert_eq!(found, expected);
+    assert_eq!(found.len(), 8);
+
+    let mut found = Vec::new();
+    store.each_usages_as_target_in(root, range, &mut |link| {
+        found.push(link);
+        Flow::Continue
+    });
+    assert!(
+        found
+            .windows(2)
+            .all(|pair| pair[0].source <= pair[1].source)
+    );
+    assert_eq!(found.len(), 8);
+
+    let mut visited = 0;
+    store.each_usages_as_source_in(root, range, &mut |_| {
+        visited += 1;
+        Flow::Break
+    });
+    assert_eq!(visited, 1);
+
+    let mut found = Vec::new();
+    store.each_usages_as_source_in(root, (Bound::Excluded(to), Bound::Unbounded), &mut |link| {
+        found.push(link);
+        Flow::Continue
+    });
+    let expected: Vec<_> = sorted_by(&store, [any, root, any], |link| link.target)
+        .into_iter()
+        .filter(|link| link.target > to)
+        .collect();
+    assert_eq!(found, expected);
+
+    Ok(())
+}
+
+#[test]
+fn unit_usages_page() -> Result<(), Error<usize>> {
+    let mut store = unit_store()?;
+    let (root, _) = make_fan(&mut store)?;
+    let any = store.constants().any;
+
+    let all = sorted_by(&store, [any, root, any], |link| link.target);
+    let mut paged = Vec::new();
+    let mut offset = 0;
+    loop {
+        let page = store.usages_as_source_page(root, offset, 6);
+        if page.is_empty() {
+            break;
+        }
+        offset += page.len();
+        paged.extend(page);
+    }
+    assert_eq!(paged, all);
+    assert!(store.usages_as_source_page(root, 0, 0).is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn split_usages_page() -> Result<(), Error<usize>> {
+    let mut store = split_store()?;
+    let (root, _) = make_fan(&mut store)?;
+    let any = store.constants().any;
+
+    let all = sorted_by(&store, [any, root, any], |link| link.target);
+    let mut paged = Vec::new();
+    let mut offset = 0;
+    loop {
+        let page = store.usages_as_source_page(root, offset, 6);
+        if page.is_empty() {
+            break;
+        }
+        offset += page.len();
+        paged.extend(page);
+    }
+    assert_eq!(paged, all);
+    assert!(store.usages_as_source_page(root, 0, 0).is_empty());
+
+    Ok(())
+}
+
+#[test]
+fn split_external_nth_usage() -> Result<(), Error<usize>> {
+    let mut store = split_store()?;
+    let external = 1000;
+
+    let a = store.create_point()?;
+    let b = store.create_link(external, a)?;
+    let c = store.create_link(external, external)?;
+
+    assert_eq!(
+        store.nth_usage_as_source(external, 0),
+        Some(Link::new(b, external, a))
+    );
+    assert_eq!(
+        store.nth_usage_as_source(external, 1),
+        Some(Link::new(c, external, external))
+    );
+    assert_eq!(store.usage_rank_as_source(c), Some(1));
+
+    Ok(())
+}
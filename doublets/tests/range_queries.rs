@@ -0,0 +1,243 @@
+// Tests for range constraints on source and target values
+
+mod common;
+
+use std::ops::{Bound, RangeBounds};
+
+use common::{split_store, unit_store};
+use data::Flow;
+use doublets::{Doublets, DoubletsExt, Error, Link, PartRange};
+
+fn make_grid(store: &mut impl Doublets<usize>) -> Result<Vec<usize>, Error<usize>> {
+    let mut points = Vec::new();
+    for _ in 0..12 {
+        points.push(store.create_point()?);
+    }
+    // create in reverse order so that tree order differs from creation order
+    for &source in points.iter().rev() {
+        for &target in points.iter().step_by(3) {
+            store.create_link(source, target)?;
+        }
+    }
+    Ok(points)
+}
+
+fn bounds(range: impl RangeBounds<usize>) -> PartRange<usize> {
+    (range.start_bound().cloned(), range.end_bound().cloned())
+}
+
+fn expected(
+    store: &impl Doublets<usize>,
+    sources: PartRange<usize>,
+    targets: PartRange<usize>,
+) -> Vec<Link<usize>> {
+    let matches = |range: &PartRange<usize>, part: usize| {
+        *range == (Bound::Unbounded, Bound::Unbounded) || (part != 0 && range.contains(&part))
+    };
+    let mut links: Vec<_> = store
+        .iter()
+        .filter(|link| matches(&sources, link.source) && matches(&targets, link.target))
+        .collect();
+    links.sort_by_key(|link| link.index);
+    links
+}
+
+fn found(
+    store: &impl Doublets<usize>,
+    sources: PartRange<usize>,
+    targets: PartRange<usize>,
+) -> Vec<Link<usize>> {
+    let mut links = Vec::new();
+    store.each_in_ranges(sources, targets, &mut |link| {
+        links.push(link);
+        Flow::Continue
+    });
+    links
+}
+
+#[test]
+fn unit_ranges() -> Result<(), Error<usize>> {
+    let mut store = unit_store()?;
+    let points = make_grid(&mut store)?;
+    let (a, b) = (points[2], points[7]);
+    let queries = [
+        (bounds(..), bounds(..)),
+        (bounds(a..=b), bounds(..)),
+        (bounds(..), bounds(a..=b)),
+        (bounds(a..b), bounds(..)),
+        (bounds(b..), bounds(..)),
+        (bounds(..), bounds(..a)),
+        (bounds(a..=b), bounds(b..)),
+        (bounds(a..=a), bounds(a..=a)),
+        ((Bound::Excluded(a), Bound::Excluded(b)), bounds(..)),
+        (bounds(b..=a), bounds(..)),
+    ];
+    for (sources, targets) in queries {
+        let all = expected(&store, sources, targets);
+        let mut links = found(&store, sources, targets);
+        assert_eq!(store.count_in_ranges(sources, targets), all.len());
+        links.sort_by_key(|link| link.index);
+        assert_eq!(links, all);
+    }
+
+    // every point is also a link from and to itself
+    assert_eq!(store.count_in(a..=b, ..), 6 * 4 + 6);
+    assert_eq!(store.count_in(.., a..=b), 12 * 2 + 6);
+
+    Ok(())
+}
+
+#[test]
+fn split_ranges() -> Result<(), Error<usize>> {
+    let mut store = split_store()?;
+    let points = make_grid(&mut store)?;
+    let (a, b) = (points[2], points[7]);
+    let queries = [
+        (bounds(..), bounds(..)),
+        (bounds(a..=b), bounds(..)),
+        (bounds(..), bounds(a..=b)),
+        (bounds(a..b), bounds(..)),
+        (bounds(b..), bounds(..)),
+        (bounds(..), bounds(..a)),
+        (bounds(a..=b), bounds(b..)),
+        (bounds(a..=a), bounds(a..=a)),
+        ((Bound::Excluded(a), Bound::Excluded(b)), bounds(..)),
+        (bounds(b..=a), bounds(..)),
+    ];
+    for (sources, targets) in queries {
+        let all = expected(&store, sources, targets);
+        let mut links = found(&store, sources, targets);
+        assert_eq!(store.count_in_ranges(sources, targets), all.len());
+        links.sort_by_key(|link| link.index);
+        assert_eq!(links, all);
+    }
+
+    // every point is also a link from and to itself
+    assert_eq!(store.count_in(a..=b, ..), 6 * 4 + 6);
+    assert_eq!(store.count_in(.., a..=b), 12 * 2 + 6);
+
+    Ok(())
+}
+
+#[test]
+fn dyn_ranges() -> Result<(), Error<usize>> {
+    let mut store: Box<dyn Doublets<usize>> = Box::new(unit_store()?);
+    let points = make_grid(&mut store)?;
+    let (a, b) = (points[2], points[7]);
+    let queries = [
+        (bounds(..), bounds(..)),
+        (bounds(a..=b), bounds(..)),
+        (bounds(..), bounds(a..=b)),
+        (bounds(a..b), bounds(..)),
+        (bounds(b..), bounds(..)),
+        (bounds(..), bounds(..a)),
+        (bounds(a..=b), bounds(b..)),
+        (bounds(a..=a), bounds(a..=a)),
+        ((Bound::Excluded(a), Bound::Excluded(b)), bounds(..)),
+        (bounds(b..=a), bounds(..)),
+    ];
+    for (sources, targets) in queries {
+        let all = expected(&store, sources, targets);
+        let mut links = found(&store, sources, targets);
+        assert_eq!(store.count_in_ranges(sources, targets), all.len());
+        links.sort_by_key(|link| link.index);
+        assert_eq!(links, all);
+    }
+
+    // every point is also a link from and to itself
+    assert_eq!(store.count_in(a..=b, ..), 6 * 4 + 6);
+    assert_eq!(store.count_in(.., a..=b), 12 * 2 + 6);
+
+    Ok(())
+}
+
+#[test]
+fn unit_ranges_order() -> Result<(), Error<usize>> {
+    let mut store = unit_store()?;
+    let points = make_grid(&mut store)?;
+    let (a, b) = (points[1], points[9]);
+
+    let by_source = found(&store, bounds(a..=b), bounds(..));
+    assert!(
+        by_source
+            .windows(2)
+            .all(|pair| pair[0].source <= pair[1].source)
+    );
+
+    let by_target = found(&store, bounds(..), bounds(a..=b));
+    assert!(
+        by_target
+            .windows(2)
+            .all(|pair| pair[0].target <= pair[1].target)
+    );
+
+    let mut visited = 0;
+    store.each_in(a..=b, .., |_| {
+        visited += 1;
+        Flow::Break
+    });
+    assert_eq!(visited, 1);
+
+    Ok(())
+}
+
+#[test]
+fn split_ranges_order() -> Result<(), Error<usize>> {
+    let mut store = split_store()?;
+    let points = make_grid(&mut store)?;
+    let (a, b) = (points[1], points[9]);
+
+    let by_source = found(&store, bounds(a..=b), bounds(..));
+    assert!(
+        by_source
+            .windows(2)
+            .all(|pair| pair[0].source <= pair[1].source)
+    );
+
+    let by_target = found(&store, bounds(..), bounds(a..=b));
+    assert!(
+        by_target
+            .windows(2)
+            .all(|pair| pair[0].target <= pair[1].target)
+    );
+
+    let mut visited = 0;
+    store.each_in(a..=b, .., |_| {
+        visited += 1;
+        Flow::Break
+    });
+    assert_eq!(visited, 1);
+
+    Ok(())
+}
+
+#[test]
+fn split_external_ranges() -> Result<(), Error<usize>> {
+    let mut store = split_store()?;
+    let external = 1000;
+
+    let a = store.create_point()?;
+    let b = store.create_point()?;
+    let x = store.create_link(external, a)?;
+    let y = store.create_link(b, a)?;
+    let z = store.create_link(a, external)?;
+
+    let links = found(&store, bounds(a..), bounds(..));
+    assert_eq!(links.last(), Some(&Link::new(x, external, a)));
+    assert!(links.contains(&Link::new(y, b, a)));
+    assert!(
+        links
+            .windows(2)
+            .all(|pair| pair[0].source <= pair[1].source)
+    );
+    assert_eq!(store.count_in(a.., ..), links.len());
+
+    assert_eq!(store.count_in(.., external..), 1);
+    assert_eq!(store.count_in(external.., a..=a), 1);
+    store.each_in(.., external.., |link| {
+        assert_eq!(link, Link::new(z, a, external));
+        Flow::Continue
+    });
+
+    Ok(())
+}
@@ -0,0 +1,138 @@
+#![cfg(feature = "mmap")]
+
+// Tests for stores opened read-only
+
+use std::{env, fs, path::PathBuf};
+
+use doublets::{
+    data::{Flow, LinksConstants},
+    mem::{FileMapped, Global, ReadOnlyMapped},
+    split, unit, Doublets, Error, Link,
+};
+
+fn temp_file(name: &str) -> PathBuf {
+    env::temp_dir().join(format!("doublets-read-only-{name}-{}", std::process::id()))
+}
+
+fn fill<S: Doublets<usize>>(mut store: S) -> Result<(), Error<usize>> {
+    let (a, b) = (store.create_point()?, store.create_point()?);
+    store.create_link(a, b)?;
+    let deleted = store.create_point()?;
+    store.create_link(deleted, a)?;
+    store.delete(5)?;
+    store.delete(deleted)?;
+    Ok(())
+}
+
+fn read_only_impl<S: Doublets<usize>>(mut store: S) -> Result<(), Error<usize>> {
+    assert_eq!(store.count(), 3);
+    assert_eq!(store.count_by([3, 1, 2]), 1);
+    assert!(store.get_link(4).is_none());
+
+    assert!(matches!(store.create_point(), Err(Error::ReadOnly)));
+    assert!(matches!(store.update(3, 2, 1), Err(Error::ReadOnly)));
+    assert!(matches!(store.delete(3), Err(Error::ReadOnly)));
+    let mut handler = |_: Link<usize>, _: Link<usize>| Flow::Continue;
+    assert!(matches!(
+        store.create_many(2, &mut handler),
+        Err(Error::ReadOnly)
+    ));
+    assert!(matches!(
+        store.delete_many(&[1], &mut handler),
+        Err(Error::ReadOnly)
+    ));
+    assert_eq!(store.count(), 3);
+    Ok(())
+}
+
+#[test]
+fn unit_read_only() -> Result<(), Error<usize>> {
+    let path = temp_file("unit");
+    fill(unit::Store::<usize, _>::new(FileMapped::from_path(&path)?)?)?;
+    let bytes = fs::read(&path)?;
+
+    let store = unit::Store::<usize, _>::read_only(ReadOnlyMapped::from_path(&path)?)?;
+    assert!(store.is_read_only());
+    read_only_impl(store)?;
+    assert_eq!(fs::read(&path)?, bytes);
+
+    fs::remove_file(path)?;
+    Ok(())
+}
+
+#[test]
+fn split_read_only() -> Result<(), Error<usize>> {
+    let (data, index) = (temp_file("data"), temp_file("index"));
+    fill(split::Store::<usize, _, _>::new(
+        FileMapped::from_path(&data)?,
+        FileMapped::from_path(&index)?,
+    )?)?;
+    let bytes = (fs::read(&data)?, fs::read(&index)?);
+
+    let mut store = split::Store::<usize, _, _>::read_only(
+        ReadOnlyMapped::from_path(&data)?,
+        ReadOnlyMapped::from_path(&index)?,
+    )?;
+    assert!(store.is_read_only());
+    assert!(matches!(store.bulk_load([(1, 2)]), Err(Error::ReadOnly)));
+    read_only_impl(store)?;
+    assert_eq!((fs::read(&data)?, fs::read(&index)?), bytes);
+
+    fs::remove_file(data)?;
+    fs::remove_file(index)?;
+    Ok(())
+}
+
+#[test]
+fn read_only_with_constants() -> Result<(), Error<usize>> {
+    let (unit, data, index) = (
+        temp_file("unit-external"),
+        temp_file("data-external"),
+        temp_file("index-external"),
+    );
+    fill(unit::Store::<usize, _>::with_constants(
+        FileMapped::from_path(&unit)?,
+        LinksConstants::external(),
+    )?)?;
+    fill(split::Store::<usize, _, _>::with_constants(
+        FileMapped::from_path(&data)?,
+        FileMapped::from_path(&index)?,
+        LinksConstants::external(),
+    )?)?;
+
+    let store = unit::Store::<usize, _>::read_only_with_constants(
+        ReadOnlyMapped::from_path(&unit)?,
+        LinksConstants::external(),
+    )?;
+    assert!(store.constants().external_range.is_some());
+    read_only_impl(store)?;
+
+    let store = split::Store::<usize, _, _>::read_only_with_constants(
+        ReadOnlyMapped::from_path(&data)?,
+        ReadOnlyMapped::from_path(&index)?,
+        LinksConstants::external(),
+    )?;
+    assert!(store.constants().external_range.is_some());
+    read_only_impl(store)?;
+
+    fs::remove_file(unit)?;
+    fs::remove_file(data)?;
+    fs::remove_file(index)?;
+    Ok(())
+}
+
+#[test]
+fn missing_and_short_files() -> Result<(), Error<usize>> {
+    assert!(ReadOnlyMapped::<usize>::from_path(temp_file("missing")).is_err());
+
+    let path = temp_file("empty");
+    fs::write(&path, [])?;
+    let store = unit::Store::<usize, _>::read_only(ReadOnlyMapped::from_path(&path)?);
+    assert!(matches!(store, Err(Error::AllocFailed(_))));
+    fs::remove_file(path)?;
+
+    // in-memory stores can be read-only too, but stay empty
+    let store = unit::Store::<usize, _>::read_only(Global::new())?;
+    assert_eq!(store.count(), 0);
+    Ok(())
+}
@@ -0,0 +1,268 @@
+// Tests for the sharded store spreading links across stores by index ranges
+
+use data::LinkType;
+use doublets::{
+    mem::Duplicates, sharded, split, unit, Doublet, Doublets, DoubletsExt, Error, Link,
+};
+use mem::Global;
+
+fn spread_impl<T: LinkType, S: Doublets<T>>(shards: Vec<S>) -> Result<(), Error<T>> {
+    let mut store = sharded::Store::new(shards, T::funty(4))?;
+
+    let points: Vec<_> = (0..10)
+        .map(|_| store.create_point())
+        .collect::<Result<_, _>>()?;
+    assert_eq!(points, (1..=10).map(T::funty).collect::<Vec<_>>());
+    assert_eq!(store.locate(T::funty(4)), Some((0, T::funty(4))));
+    assert_eq!(store.locate(T::funty(5)), Some((1, T::funty(1))));
+    assert_eq!(store.locate(T::funty(13)), None);
+
+    let counts: Vec<_> = store.shards().iter().map(Doublets::count).collect();
+    assert_eq!(counts, [T::funty(4), T::funty(4), T::funty(2)]);
+    assert_eq!(store.count(), T::funty(10));
+
+    // links refer to other shards by global indices
+    let (a, b) = (points[0], points[9]);
+    let link = store.create_link(a, b)?;
+    assert_eq!(link, T::funty(11));
+    assert_eq!(store.get_link(link), Some(Link::new(link, a, b)));
+    assert_eq!(store.search(a, b), Some(link));
+    assert_eq!(store.search(b, a), None);
+
+    assert_eq!(store.create_point()?, T::funty(12));
+    assert!(matches!(store.create_point(), Err(Error::LimitReached(_))));
+
+    Ok(())
+}
+
+fn usages_impl<T: LinkType, S: Doublets<T>>(shards: Vec<S>) -> Result<(), Error<T>> {
+    let mut store = sharded::Store::new(shards, T::funty(4))?;
+    let any = store.constants().any;
+
+    let root = store.create_point()?;
+    let mut links = Vec::new();
+    for _ in 0..6 {
+        let point = store.create_point()?;
+        links.push(store.create_link(root, point)?);
+    }
+
+    // usages are merged from all shards
+    let mut usages: Vec<_> = store
+        .each_iter([any, root, any])
+        .map(|link| link.index)
+        .collect();
+    usages.sort_unstable();
+    let mut expected = links.clone();
+    expected.push(root);
+    expected.sort_unstable();
+    assert_eq!(usages, expected);
+    assert_eq!(store.count_by([any, root, any]), T::funty(7));
+    assert_eq!(store.count_by([any, any, root]), T::funty(1));
+    assert_eq!(store.count_by([links[5]]), T::funty(1));
+
+    Ok(())
+}
+
+fn write_impl<T: LinkType, S: Doublets<T>>(shards: Vec<S>) -> Result<(), Error<T>> {
+    let mut store = sharded::Store::new(shards, T::funty(2))?;
+    let (a, b, c) = (store.create()?, store.create()?, store.create()?);
+
+    store.update(c, a, b)?;
+    assert_eq!(store.get_link(c), Some(Link::new(c, a, b)));
+    assert_eq!(store.search(a, b), Some(c));
+
+    // errors report global indices
+    let missing = T::funty(4);
+    assert!(matches!(store.delete(missing), Err(Error::NotExists(index)) if index == missing));
+    assert!(matches!(
+        store.update(T::funty(7), a, b),
+        Err(Error::NotExists(_))
+    ));
+
+    // free links of the first shards are reused
+    store.delete(b)?;
+    assert_eq!(store.create()?, b);
+    assert_eq!(store.create()?, missing);
+    assert_eq!(store.into_shards().len(), 2);
+
+    Ok(())
+}
+
+fn cross_shard_impl<T: LinkType, S: Doublets<T>>(shards: Vec<S>) -> Result<(), Error<T>> {
+    let mut store = sharded::Store::new(shards, T::funty(4))?;
+    let any = store.constants().any;
+
+    let points: Vec<_> = (0..5)
+        .map(|_| store.create_point())
+        .collect::<Result<_, _>>()?;
+    let (a, b, e) = (points[0], points[1], points[4]);
+    // `e` is the first link of the second shard, so its local index is the one of `a`
+    assert_eq!(store.locate(a), Some((0, T::funty(1))));
+    assert_eq!(store.locate(e), Some((1, T::funty(1))));
+    let link = store.create_link(a, b)?;
+    assert_eq!(store.locate(link).map(|(shard, _)| shard), Some(1));
+    assert_eq!(store.search(a, b), Some(link));
+
+    // changes of `e` do not move usages of `a`
+    store.update(e, a, T::funty(0))?;
+    assert_eq!(store.search(a, b), Some(link));
+    assert_eq!(store.count_by([any, a, any]), T::funty(3));
+    assert_eq!(store.count_by([any, e, any]), T::funty(0));
+    store.delete(e)?;
+    assert_eq!(store.search(a, b), Some(link));
+    assert_eq!(store.get_link(link), Some(Link::new(link, a, b)));
+
+    // usages of `e` are its own even after it is created again
+    assert_eq!(store.create_point()?, e);
+    let usage = store.create_link(e, e)?;
+    assert_eq!(store.count_by([any, e, any]), T::funty(2));
+    assert_eq!(store.search(e, e), Some(usage));
+    assert_eq!(store.count_by([any, a, any]), T::funty(2));
+
+    Ok(())
+}
+
+fn duplicates_impl<T: LinkType, S: Doublets<T>>(shards: Vec<S>) -> Result<(), Error<T>> {
+    let mut store = sharded::Store::new(shards, T::funty(3))?.with_duplicates(Duplicates::Reject);
+    let points: Vec<_> = (0..5)
+        .map(|_| store.create_point())
+        .collect::<Result<_, _>>()?;
+    let (a, b) = (points[0], points[1]);
+    let link = store.create_link(a, b)?;
+    assert_eq!(store.locate(link).map(|(shard, _)| shard), Some(1));
+
+    // new links go to the last shard, which does not hold the duplicate itself
+    let doublet = Doublet::new(a, b);
+    assert!(matches!(
+        store.create_link(a, b),
+        Err(Error::AlreadyExists(found)) if found == doublet
+    ));
+    let other = store.create_point()?;
+    assert_eq!(store.locate(other).map(|(shard, _)| shard), Some(2));
+    assert!(matches!(
+        store.update(other, a, b),
+        Err(Error::AlreadyExists(found)) if found == doublet
+    ));
+    assert_eq!(store.count(), T::funty(7));
+
+    let mut store = store.with_duplicates(Duplicates::Redirect);
+    assert_eq!(store.create_link(a, b)?, link);
+    assert_eq!(store.update(other, a, b)?, link);
+    assert_eq!(store.get_link(other), Some(Link::new(other, other, other)));
+    assert_eq!(store.count(), T::funty(7));
+
+    Ok(())
+}
+
+fn unit_shards(
+    count: usize,
+) -> Result<Vec<unit::Store<usize, Global<unit::LinkPart<usize>>>>, Error<usize>> {
+    (0..count)
+        .map(|_| unit::Store::new(Global::new()))
+        .collect()
+}
+
+fn split_shards(
+    count: usize,
+) -> Result<
+    Vec<split::Store<usize, Global<split::DataPart<usize>>, Global<split::IndexPart<usize>>>>,
+    Error<usize>,
+> {
+    (0..count)
+        .map(|_| split::Store::new(Global::new(), Global::new()))
+        .collect()
+}
+
+#[test]
+fn unit_spread() -> Result<(), Error<usize>> {
+    spread_impl(unit_shards(3)?)
+}
+
+#[test]
+fn split_spread() -> Result<(), Error<usize>> {
+    spread_impl(split_shards(3)?)
+}
+
+#[test]
+fn unit_usages() -> Result<(), Error<usize>> {
+    usages_impl(unit_shards(4)?)
+}
+
+#[test]
+fn split_usages() -> Result<(), Error<usize>> {
+    usages_impl(split_shards(4)?)
+}
+
+#[test]
+fn unit_write() -> Result<(), Error<usize>> {
+    write_impl(unit_shards(2)?)
+}
+
+#[test]
+fn split_write() -> Result<(), Error<usize>> {
+    write_impl(split_shards(2)?)
+}
+
+#[test]
+fn unit_cross_shard() -> Result<(), Error<usize>> {
+    cross_shard_impl(unit_shards(2)?)
+}
+
+#[test]
+fn split_cross_shard() -> Result<(), Error<usize>> {
+    cross_shard_impl(split_shards(2)?)
+}
+
+#[test]
+fn unit_duplicates() -> Result<(), Error<usize>> {
+    duplicates_impl(unit_shards(3)?)
+}
+
+#[test]
+fn split_duplicates() -> Result<(), Error<usize>> {
+    duplicates_impl(split_shards(3)?)
+}
+
+#[test]
+fn shard_errors_report_global_parts() -> Result<(), Error<usize>> {
+    let shards = unit_shards(2)?
+        .into_iter()
+        .map(|shard| shard.with_duplicates(Duplicates::Reject))
+        .collect();
+    let mut store = sharded::Store::new(shards, 3)?;
+    let (a, b) = (store.create_point()?, store.create_point()?);
+    store.create_point()?;
+    store.create_link(a, b)?;
+
+    // the second shard finds the duplicate itself, with parts of the first shard shifted
+    assert!(matches!(
+        store.create_link(a, b),
+        Err(Error::AlreadyExists(found)) if found == Doublet::new(a, b)
+    ));
+    Ok(())
+}
+
+#[test]
+fn dyn_shards() -> Result<(), Error<usize>> {
+    let shards: Vec<Box<dyn Doublets<usize>>> = vec![
+        Box::new(unit::Store::<usize, _>::new(Global::new())?),
+        Box::new(split::Store::<usize, _, _>::new(
+            Global::new(),
+            Global::new(),
+        )?),
+    ];
+    write_impl(shards)
+}
+
+#[test]
+fn full_shard_is_rejected() -> Result<(), Error<usize>> {
+    let mut shard = unit::Store::<usize, _>::new(Global::new())?;
+    for _ in 0..3 {
+        shard.create_point()?;
+    }
+    assert!(matches!(
+        sharded::Store::new(vec![shard], 2),
+        Err(Error::LimitReached(2))
+    ));
+    Ok(())
+}
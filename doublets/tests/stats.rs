@@ -0,0 +1,107 @@
+// Tests for statistics of unit and split stores
+
+mod common;
+
+use common::{split_store, unit_store};
+use doublets::{Doublets, Error};
+
+#[test]
+fn unit_counters() -> Result<(), Error<usize>> {
+    let mut store = unit_store()?;
+    for _ in 0..10 {
+        store.create_point()?;
+    }
+    for target in 2..=6 {
+        store.create_link(1, target)?;
+    }
+    store.create_link(2, 1)?;
+
+    let report = store.stats(3);
+    assert_eq!(report.allocated, 16);
+    assert_eq!(report.free, 0);
+    assert!(report.reserved >= report.allocated);
+    assert!(report.data_bytes > 0);
+    assert_eq!(report.sources.nodes, 16);
+    assert_eq!(report.targets.nodes, 16);
+    assert!(report.sources.height > 0);
+    assert_eq!(report.top_usages, [(1, 6), (2, 2), (3, 1)]);
+    assert_eq!(report.index_bytes, 0);
+    assert_eq!(report.sources.trees, 1);
+
+    store.delete(11)?;
+    let report = store.stats(0);
+    assert_eq!(report.free, 1);
+    assert_eq!(report.sources.nodes, 15);
+    assert!(report.top_usages.is_empty());
+    Ok(())
+}
+
+#[test]
+fn split_counters() -> Result<(), Error<usize>> {
+    let mut store = split_store()?;
+    for _ in 0..10 {
+        store.create_point()?;
+    }
+    for target in 2..=6 {
+        store.create_link(1, target)?;
+    }
+    store.create_link(2, 1)?;
+
+    let report = store.stats(3);
+    assert_eq!(report.allocated, 16);
+    assert_eq!(report.free, 0);
+    assert!(report.reserved >= report.allocated);
+    assert!(report.data_bytes > 0);
+    assert_eq!(report.sources.nodes, 16);
+    assert_eq!(report.targets.nodes, 16);
+    assert!(report.sources.height > 0);
+    assert_eq!(report.top_usages, [(1, 6), (2, 2), (3, 1)]);
+    assert!(report.index_bytes > 0);
+    // every point is a tree of its own, while link 1 is the source of 5 other links
+    assert_eq!(report.sources.trees, 10);
+
+    store.delete(11)?;
+    let report = store.stats(0);
+    assert_eq!(report.free, 1);
+    assert_eq!(report.sources.nodes, 15);
+    assert!(report.top_usages.is_empty());
+    Ok(())
+}
+
+#[test]
+fn unit_balance() -> Result<(), Error<usize>> {
+    let mut store = unit_store()?;
+    let root = store.create_point()?;
+    for _ in 0..1000 {
+        let point = store.create_point()?;
+        store.create_link(root, point)?;
+    }
+
+    let report = store.stats(1);
+    assert_eq!(report.top_usages, [(root, 1000)]);
+    assert!(report.sources.height >= 10);
+    for tree in [report.sources, report.targets] {
+        // size-balanced trees are at most about 1.44 times higher than perfectly balanced ones
+        assert!(tree.excess_height <= tree.height / 2, "{tree:?}");
+    }
+    Ok(())
+}
+
+#[test]
+fn split_balance() -> Result<(), Error<usize>> {
+    let mut store = split_store()?;
+    let root = store.create_point()?;
+    for _ in 0..1000 {
+        let point = store.create_point()?;
+        store.create_link(root, point)?;
+    }
+
+    let report = store.stats(1);
+    assert_eq!(report.top_usages, [(root, 1000)]);
+    assert!(report.sources.height >= 10);
+    for tree in [report.sources, report.targets] {
+        // size-balanced trees are at most about 1.44 times higher than perfectly balanced ones
+        assert!(tree.excess_height <= tree.height / 2, "{tree:?}");
+    }
+    Ok(())
+}
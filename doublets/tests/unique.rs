@@ -0,0 +1,187 @@
+// Tests for stores rejecting or redirecting duplicate links
+
+mod common;
+
+use common::{split_store, unit_store};
+use doublets::{mem::Duplicates, Doublet, Doublets, Error, Link, Links};
+
+#[test]
+fn unit_reject() -> Result<(), Error<usize>> {
+    let mut store = unit_store()?.with_duplicates(Duplicates::Reject);
+    let (a, b) = (store.create_point()?, store.create_point()?);
+    let ab = store.create_link(a, b)?;
+    assert!(matches!(
+        store.create_link(a, b),
+        Err(Error::AlreadyExists(doublet)) if doublet == Doublet::new(a, b)
+    ));
+    // no link is created for a duplicate
+    assert_eq!(store.count(), 3);
+
+    // an explicit update of a created link fails without deleting it
+    let empty = store.create()?;
+    assert!(matches!(
+        store.update(empty, a, b),
+        Err(Error::AlreadyExists(_))
+    ));
+    assert_eq!(store.get_link(empty), Some(Link::new(empty, 0, 0)));
+    store.delete(empty)?;
+
+    // a duplicate made by update
+    let ba = store.create_link(b, a)?;
+    assert!(matches!(
+        store.update(ba, a, b),
+        Err(Error::AlreadyExists(_))
+    ));
+    assert_eq!(
+        store.get_link(ba).map(|link| (link.source, link.target)),
+        Some((b, a))
+    );
+    assert_eq!(store.update(ab, a, b)?, ab);
+
+    // parts become free for other links once the link is changed
+    let c = store.create_point()?;
+    store.update(ab, a, c)?;
+    assert_eq!(store.update(ba, a, b)?, ba);
+    assert!(matches!(
+        store.create_link(a, c),
+        Err(Error::AlreadyExists(_))
+    ));
+    assert_eq!(store.count(), 5);
+    Ok(())
+}
+
+#[test]
+fn split_reject() -> Result<(), Error<usize>> {
+    let mut store = split_store()?.with_duplicates(Duplicates::Reject);
+    let (a, b) = (store.create_point()?, store.create_point()?);
+    let ab = store.create_link(a, b)?;
+    assert!(matches!(
+        store.create_link(a, b),
+        Err(Error::AlreadyExists(doublet)) if doublet == Doublet::new(a, b)
+    ));
+    // no link is created for a duplicate
+    assert_eq!(store.count(), 3);
+
+    // an explicit update of a created link fails without deleting it
+    let empty = store.create()?;
+    assert!(matches!(
+        store.update(empty, a, b),
+        Err(Error::AlreadyExists(_))
+    ));
+    assert_eq!(store.get_link(empty), Some(Link::new(empty, 0, 0)));
+    store.delete(empty)?;
+
+    // a duplicate made by update
+    let ba = store.create_link(b, a)?;
+    assert!(matches!(
+        store.update(ba, a, b),
+        Err(Error::AlreadyExists(_))
+    ));
+    assert_eq!(
+        store.get_link(ba).map(|link| (link.source, link.target)),
+        Some((b, a))
+    );
+    assert_eq!(store.update(ab, a, b)?, ab);
+
+    // parts become free for other links once the link is changed
+    let c = store.create_point()?;
+    store.update(ab, a, c)?;
+    assert_eq!(store.update(ba, a, b)?, ba);
+    assert!(matches!(
+        store.create_link(a, c),
+        Err(Error::AlreadyExists(_))
+    ));
+    assert_eq!(store.count(), 5);
+    Ok(())
+}
+
+#[test]
+fn unit_redirect() -> Result<(), Error<usize>> {
+    let mut store = unit_store()?.with_duplicates(Duplicates::Redirect);
+    let (a, b) = (store.create_point()?, store.create_point()?);
+    let ab = store.create_link(a, b)?;
+    assert_eq!(store.create_link(a, b)?, ab);
+    assert_eq!(store.get_or_create(a, b)?, ab);
+    assert_eq!(store.count(), 3);
+
+    let ba = store.create_link(b, a)?;
+    assert_eq!(store.update(ba, a, b)?, ab);
+    assert_eq!(
+        store.get_link(ba).map(|link| (link.source, link.target)),
+        Some((b, a))
+    );
+    assert_eq!(store.count_by([store.constants().any, a, b]), 1);
+
+    let empty = store.create()?;
+    assert_eq!(store.update(empty, a, b)?, ab);
+    assert_eq!(store.get_link(empty), Some(Link::new(empty, 0, 0)));
+    Ok(())
+}
+
+#[test]
+fn split_redirect() -> Result<(), Error<usize>> {
+    let mut store = split_store()?.with_duplicates(Duplicates::Redirect);
+    let (a, b) = (store.create_point()?, store.create_point()?);
+    let ab = store.create_link(a, b)?;
+    assert_eq!(store.create_link(a, b)?, ab);
+    assert_eq!(store.get_or_create(a, b)?, ab);
+    assert_eq!(store.count(), 3);
+
+    let ba = store.create_link(b, a)?;
+    assert_eq!(store.update(ba, a, b)?, ab);
+    assert_eq!(
+        store.get_link(ba).map(|link| (link.source, link.target)),
+        Some((b, a))
+    );
+    assert_eq!(store.count_by([store.constants().any, a, b]), 1);
+
+    let empty = store.create()?;
+    assert_eq!(store.update(empty, a, b)?, ab);
+    assert_eq!(store.get_link(empty), Some(Link::new(empty, 0, 0)));
+    Ok(())
+}
+
+#[test]
+fn unit_allow_by_default() -> Result<(), Error<usize>> {
+    let mut store = unit_store()?;
+    assert_eq!(store.duplicates(), Duplicates::Allow);
+    let a = store.create_point()?;
+    let first = store.create_link(a, a)?;
+    assert_ne!(store.create_link(a, a)?, first);
+    Ok(())
+}
+
+#[test]
+fn split_allow_by_default() -> Result<(), Error<usize>> {
+    let mut store = split_store()?;
+    assert_eq!(store.duplicates(), Duplicates::Allow);
+    let a = store.create_point()?;
+    let first = store.create_link(a, a)?;
+    assert_ne!(store.create_link(a, a)?, first);
+    Ok(())
+}
+
+#[test]
+fn unit_bulk_load() -> Result<(), Error<usize>> {
+    let mut store = unit_store()?.with_duplicates(Duplicates::Redirect);
+    store.create_point()?;
+    assert!(store.bulk_load([(1, 1)]).is_err());
+    assert!(store.bulk_load([(1, 2), (2, 1), (1, 2)]).is_err());
+    assert_eq!(store.count(), 1);
+    assert_eq!(store.bulk_load([(1, 2), (2, 1)])?, 2..4);
+    Ok(())
+}
+
+#[test]
+fn split_bulk_load() -> Result<(), Error<usize>> {
+    let mut store = split_store()?.with_duplicates(Duplicates::Reject);
+    store.create_point()?;
+    assert!(matches!(
+        store.bulk_load([(1, 1)]),
+        Err(Error::AlreadyExists(_))
+    ));
+    assert!(store.bulk_load([(1, 2), (2, 1), (1, 2)]).is_err());
+    assert_eq!(store.count(), 1);
+    assert_eq!(store.bulk_load([(1, 2), (2, 1)])?, 2..4);
+    Ok(())
+}
@@ -0,0 +1,131 @@
+#![cfg(feature = "mmap")]
+
+// Tests for files written before equal links were ordered by index
+
+use std::{
+    env,
+    fs::{self, OpenOptions},
+    io::{self, Seek, SeekFrom, Write},
+    mem,
+    path::{Path, PathBuf},
+};
+
+use doublets::{
+    mem::{FileMapped, ReadOnlyMapped},
+    split, unit, Doublets, Error, Link, Links,
+};
+
+fn temp_file(name: &str) -> PathBuf {
+    env::temp_dir().join(format!("doublets-versions-{name}-{}", std::process::id()))
+}
+
+// `root_as_source`, `root_as_target` and `version` of the header
+const HEADER: [usize; 3] = [4, 5, 7];
+
+// clears the version and tree roots as if the file was written by an older version,
+// so its links are found only if the trees are rebuilt
+fn downgrade(path: &Path, words: impl IntoIterator<Item = usize>) -> io::Result<()> {
+    let mut file = OpenOptions::new().write(true).open(path)?;
+    for word in words {
+        file.seek(SeekFrom::Start((word * mem::size_of::<usize>()) as u64))?;
+        file.write_all(&0_usize.to_ne_bytes())?;
+    }
+    Ok(())
+}
+
+// duplicate links are the ones ordered differently by older versions
+fn fill(store: &mut impl Doublets<usize>) -> Result<usize, Error<usize>> {
+    let root = store.create_point()?;
+    for _ in 0..10 {
+        let point = store.create_point()?;
+        store.create_link(root, point)?;
+        store.create_link(root, root)?;
+    }
+    Ok(root)
+}
+
+fn usages(store: &impl Doublets<usize>, root: usize) -> Vec<Link<usize>> {
+    let count = store.count_by([store.constants().any, root, store.constants().any]);
+    (0..count)
+        .map(|n| store.nth_usage_as_source(root, n).unwrap())
+        .collect()
+}
+
+fn check_rebuilt(
+    store: &impl Doublets<usize>,
+    root: usize,
+    expected: &[Link<usize>],
+) -> Result<(), Error<usize>> {
+    assert_eq!(usages(store, root), expected);
+    for (n, link) in expected.iter().enumerate() {
+        assert_eq!(store.usage_rank_as_source(link.index), Some(n));
+        assert!(store.usage_rank_as_target(link.index).is_some());
+    }
+    assert!(store.search(root, root).is_some());
+    Ok(())
+}
+
+#[test]
+fn unit_old_trees_are_rebuilt() -> Result<(), Error<usize>> {
+    let path = temp_file("unit");
+    let mut store = unit::Store::<usize, _>::new(FileMapped::from_path(&path)?)?;
+    let root = fill(&mut store)?;
+    let expected = usages(&store, root);
+    assert_eq!(store.get_header().version, 1);
+    drop(store);
+
+    downgrade(&path, HEADER)?;
+    let store = unit::Store::<usize, _>::read_only(ReadOnlyMapped::from_path(&path)?);
+    assert!(matches!(store, Err(Error::ReadOnly)));
+
+    let store = unit::Store::<usize, _>::new(FileMapped::from_path(&path)?)?;
+    assert_eq!(store.get_header().version, 1);
+    check_rebuilt(&store, root, &expected)?;
+    drop(store);
+
+    // the rebuilt file is read as is
+    let store = unit::Store::<usize, _>::read_only(ReadOnlyMapped::from_path(&path)?)?;
+    check_rebuilt(&store, root, &expected)?;
+
+    fs::remove_file(path)?;
+    Ok(())
+}
+
+#[test]
+fn split_old_trees_are_rebuilt() -> Result<(), Error<usize>> {
+    let (data, index) = (temp_file("data"), temp_file("index"));
+    let mut store = split::Store::<usize, _, _>::new(
+        FileMapped::from_path(&data)?,
+        FileMapped::from_path(&index)?,
+    )?;
+    let root = fill(&mut store)?;
+    let expected = usages(&store, root);
+    assert_eq!(store.get_header().version, 1);
+    drop(store);
+
+    // internal trees are rooted in the index parts of their base links
+    downgrade(&index, HEADER.into_iter().chain([root * 8, root * 8 + 4]))?;
+    let store = split::Store::<usize, _, _>::read_only(
+        ReadOnlyMapped::from_path(&data)?,
+        ReadOnlyMapped::from_path(&index)?,
+    );
+    assert!(matches!(store, Err(Error::ReadOnly)));
+
+    let store = split::Store::<usize, _, _>::new(
+        FileMapped::from_path(&data)?,
+        FileMapped::from_path(&index)?,
+    )?;
+    assert_eq!(store.get_header().version, 1);
+    check_rebuilt(&store, root, &expected)?;
+    drop(store);
+
+    let store = split::Store::<usize, _, _>::read_only(
+        ReadOnlyMapped::from_path(&data)?,
+        ReadOnlyMapped::from_path(&index)?,
+    )?;
+    check_rebuilt(&store, root, &expected)?;
+
+    fs::remove_file(data)?;
+    fs::remove_file(index)?;
+    Ok(())
+}
@@ -0,0 +1,322 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    env,
+    fs::{self, OpenOptions},
+    io::{self, BufRead, Write},
+    path::{Path, PathBuf},
+};
+
+use clap::Parser;
+use doublets::{
+    data::{Flow, LinkType},
+    Doublets, DoubletsExt, Link,
+};
+use integration::{open, open_read_only, parse, parse_query, Result, Store, Width};
+use rustyline::{error::ReadlineError, Editor};
+
+/// Explores a doublets store file interactively.
+#[derive(Parser)]
+#[command(name = "doublets-repl", version)]
+struct Cli {
+    /// Unit store file or data file of a split store
+    file: PathBuf,
+
+    /// Index file of a split store
+    #[arg(long)]
+    index: Option<PathBuf>,
+
+    /// Width of link parts in bits
+    #[arg(long, value_enum, default_value = "64")]
+    width: Width,
+
+    /// Allows commands changing links
+    #[arg(long)]
+    write: bool,
+
+    /// File with `index name` lines, new names are appended to it
+    #[arg(long)]
+    names: Option<PathBuf>,
+
+    /// History file, `~/.doublets_history` by default
+    #[arg(long)]
+    history: Option<PathBuf>,
+}
+
+const HELP: &str = "\
+<index> [<source> <target>]      lists matching links, `*` matches any part
+count [<index> [<source> <target>]]
+create [<source> <target>]       creates a point or a link
+update <index> <source> <target>
+delete <index>
+tree <index> [<depth>]           prints usages of a link as an outline
+name <index> <name>              names a link
+names                            lists names
+history                          lists commands of this session
+help
+exit";
+
+struct Repl<T: LinkType> {
+    store: Box<dyn Store<T>>,
+    writable: bool,
+    names: BTreeMap<T, String>,
+    names_file: Option<PathBuf>,
+    history: Vec<String>,
+}
+
+fn read_names<T: LinkType>(path: Option<&Path>) -> Result<BTreeMap<T, String>> {
+    let mut names = BTreeMap::new();
+    let content = match path {
+        Some(path) if path.exists() => fs::read_to_string(path)?,
+        _ => return Ok(names),
+    };
+    for (number, line) in content.lines().enumerate() {
+        match line.trim().split_once(char::is_whitespace) {
+            Some((index, name)) => {
+                names.insert(parse(index)?, name.trim().to_owned());
+            }
+            None if line.trim().is_empty() => {}
+            None => return Err(format!("line {}: expected `index name`", number + 1).into()),
+        }
+    }
+    Ok(names)
+}
+
+impl<T: LinkType> Repl<T> {
+    fn part(&self, part: T) -> String {
+        match self.names.get(&part) {
+            Some(name) => format!("{part}={name}"),
+            None => part.to_string(),
+        }
+    }
+
+    fn format(&self, link: &Link<T>) -> String {
+        format!(
+            "{} ({} {})",
+            self.part(link.index),
+            self.part(link.source),
+            self.part(link.target)
+        )
+    }
+
+    fn check_writable(&self) -> Result<()> {
+        if self.writable {
+            Ok(())
+        } else {
+            Err("the store is opened read-only, restart with `--write` to change it".into())
+        }
+    }
+
+    fn link(&self, index: T) -> Result<Link<T>> {
+        Ok(self.store.try_get_link(index)?)
+    }
+
+    fn name(&mut self, index: T, name: String) -> Result<()> {
+        if let Some(path) = &self.names_file {
+            let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+            writeln!(file, "{index} {name}")?;
+        }
+        self.names.insert(index, name);
+        Ok(())
+    }
+
+    fn tree(&self, index: T, depth: usize, out: &mut impl Write) -> Result<()> {
+        writeln!(out, "{}", self.format(&self.link(index)?))?;
+        let mut visited = HashSet::from([index]);
+        self.usages(index, 1, depth, &mut visited, out)
+    }
+
+    fn usages(
+        &self,
+        index: T,
+        level: usize,
+        depth: usize,
+        visited: &mut HashSet<T>,
+        out: &mut impl Write,
+    ) -> Result<()> {
+        if level > depth {
+            return Ok(());
+        }
+        let any = self.store.constants().any;
+        let mut usages: Vec<_> = self
+            .store
+            .each_iter([any, index, any])
+            .chain(self.store.each_iter([any, any, index]))
+            .filter(|link| link.index != index)
+            .collect();
+        usages.sort_by_key(|link| link.index);
+        usages.dedup_by_key(|link| link.index);
+
+        let indent = "  ".repeat(level);
+        for usage in usages {
+            // links used more than once, including cycles, are expanded only the first time
+            if visited.insert(usage.index) {
+                writeln!(out, "{indent}{}", self.format(&usage))?;
+                self.usages(usage.index, level + 1, depth, visited, out)?;
+            } else {
+                writeln!(out, "{indent}{} ...", self.format(&usage))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn execute(&mut self, words: &[&str], out: &mut impl Write) -> Result<()> {
+        let any = self.store.constants().any;
+        match *words {
+            [] => {}
+            ["help"] => writeln!(out, "{HELP}")?,
+            ["history"] => {
+                for (number, line) in self.history.iter().enumerate() {
+                    writeln!(out, "{:>4}  {line}", number + 1)?;
+                }
+            }
+            ["names"] => {
+                for (index, name) in &self.names {
+                    writeln!(out, "{index} {name}")?;
+                }
+            }
+            ["name", index, ref name @ ..] if !name.is_empty() => {
+                self.name(parse(index)?, name.join(" "))?;
+            }
+            ["count", ref pattern @ ..] => {
+                writeln!(
+                    out,
+                    "{}",
+                    self.store.count_links(&parse_query(pattern, any)?)
+                )?;
+            }
+            ["create"] => {
+                self.check_writable()?;
+                let index = self.store.create_point()?;
+                writeln!(out, "{}", self.format(&self.link(index)?))?;
+            }
+            ["create", source, target] => {
+                self.check_writable()?;
+                let index = self.store.create_link(parse(source)?, parse(target)?)?;
+                writeln!(out, "{}", self.format(&self.link(index)?))?;
+            }
+            ["update", index, source, target] => {
+                self.check_writable()?;
+                let index = parse(index)?;
+                self.store.update(index, parse(source)?, parse(target)?)?;
+                writeln!(out, "{}", self.format(&self.link(index)?))?;
+            }
+            ["delete", index] => {
+                self.check_writable()?;
+                let link = self.link(parse(index)?)?;
+                self.store.delete(link.index)?;
+                writeln!(out, "deleted {}", self.format(&link))?;
+            }
+            ["tree", index] => self.tree(parse(index)?, 3, out)?,
+            ["tree", index, depth] => self.tree(parse(index)?, depth.parse()?, out)?,
+            [first, ..] if first == "*" || first.starts_with(|c: char| c.is_ascii_digit()) => {
+                let query = parse_query(words, any)?;
+                let mut links = Vec::new();
+                self.store.each_links(&query, &mut |link| {
+                    links.push(link);
+                    Flow::Continue
+                });
+                for link in &links {
+                    writeln!(out, "{}", self.format(link))?;
+                }
+            }
+            _ => return Err("unknown command, type `help` to list commands".into()),
+        }
+        Ok(())
+    }
+
+    /// Runs a line and returns `false` if the session is over.
+    fn step(&mut self, line: &str) -> bool {
+        let words: Vec<_> = line.split_whitespace().collect();
+        if let ["exit" | "quit"] = words[..] {
+            return false;
+        }
+        let mut out = io::stdout().lock();
+        if let Err(err) = self.execute(&words, &mut out) {
+            eprintln!("error: {err}");
+        }
+        out.flush().ok();
+        if !words.is_empty() {
+            self.history.push(line.trim().to_owned());
+        }
+        true
+    }
+}
+
+fn history_file(cli: &Cli) -> Option<PathBuf> {
+    cli.history
+        .clone()
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".doublets_history")))
+}
+
+fn run<T: LinkType>(cli: &Cli) -> Result<()> {
+    if !cli.write {
+        for path in [Some(&cli.file), cli.index.as_ref()].into_iter().flatten() {
+            if !path.exists() {
+                return Err(format!(
+                    "{} does not exist, pass `--write` to create it",
+                    path.display()
+                )
+                .into());
+            }
+        }
+    }
+    let mut repl = Repl {
+        store: if cli.write {
+            open::<T>(&cli.file, cli.index.as_deref())?
+        } else {
+            open_read_only::<T>(&cli.file, cli.index.as_deref())?
+        },
+        writable: cli.write,
+        names: read_names(cli.names.as_deref())?,
+        names_file: cli.names.clone(),
+        history: Vec::new(),
+    };
+
+    // piped commands are run without prompts and history
+    if !atty::is(atty::Stream::Stdin) {
+        for line in io::stdin().lock().lines() {
+            if !repl.step(&line?) {
+                break;
+            }
+        }
+        return Ok(());
+    }
+
+    let mut editor = Editor::<()>::new()?;
+    let history = history_file(cli);
+    if let Some(history) = &history {
+        editor.load_history(history).ok();
+    }
+    let mode = if cli.write { "read-write" } else { "read-only" };
+    println!(
+        "{} links, {mode}, type `help` to list commands",
+        repl.store.count()
+    );
+    loop {
+        match editor.readline("doublets> ") {
+            Ok(line) => {
+                editor.add_history_entry(line.as_str());
+                if !repl.step(&line) {
+                    break;
+                }
+            }
+            Err(ReadlineError::Interrupted) => continue,
+            Err(ReadlineError::Eof) => break,
+            Err(err) => return Err(err.into()),
+        }
+    }
+    if let Some(history) = &history {
+        editor.save_history(history)?;
+    }
+    Ok(())
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    match cli.width {
+        Width::U8 => run::<u8>(&cli),
+        Width::U16 => run::<u16>(&cli),
+        Width::U32 => run::<u32>(&cli),
+        Width::U64 => run::<u64>(&cli),
+    }
+}
@@ -0,0 +1,45 @@
+use std::path::PathBuf;
+
+use clap::Parser;
+use doublets::data::LinkType;
+use integration::{open, server::Server, Result, Width};
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Serves a doublets store file over HTTP with JSON links.
+#[derive(Parser)]
+#[command(name = "doublets-server", version)]
+struct Cli {
+    /// Unit store file or data file of a split store
+    file: PathBuf,
+
+    /// Index file of a split store
+    #[arg(long)]
+    index: Option<PathBuf>,
+
+    /// Width of link parts in bits
+    #[arg(long, value_enum, default_value = "64")]
+    width: Width,
+
+    /// Address to listen on
+    #[arg(long, default_value = "127.0.0.1:8080")]
+    addr: String,
+}
+
+fn run<T: LinkType + Serialize + DeserializeOwned>(cli: &Cli) -> Result<()> {
+    let store = open::<T>(&cli.file, cli.index.as_deref())?;
+    let mut server = Server::bind(&cli.addr, store)?;
+    if let Some(addr) = server.local_addr() {
+        eprintln!("listening on http://{addr}");
+    }
+    server.run()
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    match cli.width {
+        Width::U8 => run::<u8>(&cli),
+        Width::U16 => run::<u16>(&cli),
+        Width::U32 => run::<u32>(&cli),
+        Width::U64 => run::<u64>(&cli),
+    }
+}
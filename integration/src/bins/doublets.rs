@@ -0,0 +1,372 @@
+use std::{
+    collections::HashMap,
+    fs::{self, File},
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::{Path, PathBuf},
+};
+
+use clap::{Parser, Subcommand};
+use doublets::{
+    data::{Flow, LinkType},
+    Doublets, DoubletsExt, Link,
+};
+use integration::{open, open_read_only, parse, parse_query, Result, Store, Width};
+
+/// Inspects and administers doublets store files.
+#[derive(Parser)]
+#[command(name = "doublets", version)]
+struct Cli {
+    /// Unit store file or data file of a split store
+    file: PathBuf,
+
+    /// Index file of a split store
+    #[arg(long)]
+    index: Option<PathBuf>,
+
+    /// Width of link parts in bits
+    #[arg(long, value_enum, default_value = "64")]
+    width: Width,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Shows the header and statistics
+    Info,
+    /// Lists links matching `[index [source target]]`, `*` matches any part
+    Query {
+        pattern: Vec<String>,
+        /// Prints only the number of matching links
+        #[arg(long)]
+        count: bool,
+        /// Prints at most this number of links
+        #[arg(long)]
+        limit: Option<usize>,
+    },
+    /// Creates a point or a link and prints its index
+    Create {
+        source: Option<String>,
+        target: Option<String>,
+    },
+    /// Sets the source and the target of a link
+    Update {
+        index: String,
+        source: String,
+        target: String,
+    },
+    /// Deletes a link
+    Delete { index: String },
+    /// Checks that every link is found by the indexes of the store
+    Verify,
+    /// Writes links as `index source target` lines
+    Dump {
+        /// Output file, the standard output by default
+        output: Option<PathBuf>,
+    },
+    /// Creates links of a dump with the same indices in an empty store
+    Load { input: PathBuf },
+    /// Copies links to a new store without free links, renumbering them
+    Compact {
+        output: PathBuf,
+        /// Index file of the new split store
+        #[arg(long)]
+        output_index: Option<PathBuf>,
+    },
+}
+
+impl Command {
+    // stores of other commands are opened read-only, so their files are never changed
+    fn changes_links(&self) -> bool {
+        matches!(
+            self,
+            Self::Create { .. } | Self::Update { .. } | Self::Delete { .. } | Self::Load { .. }
+        )
+    }
+}
+
+fn write_links<T: LinkType>(
+    out: &mut impl Write,
+    links: impl IntoIterator<Item = Link<T>>,
+) -> io::Result<()> {
+    for link in links {
+        writeln!(out, "{} {} {}", link.index, link.source, link.target)?;
+    }
+    out.flush()
+}
+
+fn info<T: LinkType>(cli: &Cli, store: &dyn Store<T>) -> Result<()> {
+    let header = store.header();
+    let layout = if cli.index.is_some() { "split" } else { "unit" };
+    println!("layout: {layout}");
+    println!("width: {} bits", 8 * std::mem::size_of::<T>());
+    println!("allocated: {}", header.allocated);
+    println!("reserved: {}", header.reserved);
+    println!("free: {}", header.free);
+    println!("first free: {}", header.first_free);
+    println!("last free: {}", header.last_free);
+    println!("root as source: {}", header.root_as_source);
+    println!("root as target: {}", header.root_as_target);
+
+    let (mut links, mut points) = (0_usize, 0_usize);
+    store.each_links(&[], &mut |link| {
+        links += 1;
+        if link.index == link.source && link.index == link.target {
+            points += 1;
+        }
+        Flow::Continue
+    });
+    println!("links: {links}");
+    println!("points: {points}");
+
+    let stats = store.stats(5);
+    for (part, tree) in [("sources", stats.sources), ("targets", stats.targets)] {
+        println!(
+            "{part}: {} trees, {} nodes, height {} ({} above balanced)",
+            tree.trees, tree.nodes, tree.height, tree.excess_height
+        );
+    }
+    for (index, usages) in stats.top_usages {
+        println!("link {index}: {usages} usages");
+    }
+
+    println!("mapped: {} bytes", stats.data_bytes + stats.index_bytes);
+    for path in [Some(&cli.file), cli.index.as_ref()].into_iter().flatten() {
+        println!("{}: {} bytes", path.display(), fs::metadata(path)?.len());
+    }
+    Ok(())
+}
+
+fn verify<T: LinkType>(store: &impl Doublets<T>) -> Vec<String> {
+    let constants = store.constants();
+    let any = constants.any;
+    let mut problems = Vec::new();
+
+    let links: Vec<_> = store.iter().collect();
+    if links.len() != store.count().as_usize() {
+        problems.push(format!(
+            "{} links are found, but the store counts {}",
+            links.len(),
+            store.count()
+        ));
+    }
+
+    let (mut sources, mut targets) = (HashMap::new(), HashMap::new());
+    for link in &links {
+        *sources.entry(link.source).or_insert(0_usize) += 1;
+        *targets.entry(link.target).or_insert(0_usize) += 1;
+
+        if store.get_link(link.index).as_ref() != Some(link) {
+            problems.push(format!("link {} is not found by its index", link.index));
+        }
+        if store.search(link.source, link.target).is_none() {
+            problems.push(format!(
+                "link {} is not found by its source and target",
+                link.index
+            ));
+        }
+        for part in [link.source, link.target] {
+            if part != constants.null
+                && constants.is_internal(part)
+                && store.get_link(part).is_none()
+            {
+                eprintln!("warning: link {} refers to missing link {part}", link.index);
+            }
+        }
+    }
+
+    for (source, count) in sources {
+        let found = store.count_by([any, source, any]).as_usize();
+        if found != count {
+            problems.push(format!(
+                "{count} links have source {source}, but the sources index has {found}"
+            ));
+        }
+    }
+    for (target, count) in targets {
+        let found = store.count_by([any, any, target]).as_usize();
+        if found != count {
+            problems.push(format!(
+                "{count} links have target {target}, but the targets index has {found}"
+            ));
+        }
+    }
+    problems
+}
+
+fn read_dump<T: LinkType>(input: &Path) -> Result<Vec<Link<T>>> {
+    let mut links = Vec::new();
+    for (number, line) in BufReader::new(File::open(input)?).lines().enumerate() {
+        let line = line?;
+        let parts: Vec<_> = line.split_whitespace().collect();
+        match parts[..] {
+            [] => continue,
+            [index, source, target] => {
+                links.push(Link::new(parse(index)?, parse(source)?, parse(target)?));
+            }
+            _ => return Err(format!("line {}: expected `index source target`", number + 1).into()),
+        }
+    }
+    links.sort_by_key(|link| link.index);
+    Ok(links)
+}
+
+fn load<T: LinkType>(store: &mut dyn Store<T>, links: &[Link<T>]) -> Result<()> {
+    let zero = T::funty(0);
+    if store.header().allocated != zero {
+        return Err("links can be loaded only into a new store".into());
+    }
+    if links.first().map_or(false, |link| link.index == zero) {
+        return Err("link 0 cannot be loaded".into());
+    }
+    if let Some(pair) = links.windows(2).find(|pair| pair[0].index == pair[1].index) {
+        return Err(format!("link {} is duplicated", pair[0].index).into());
+    }
+
+    // links are appended sequentially to a new store, so holes between indices are loaded
+    // as empty links and deleted afterwards, and the whole dump is checked before writing
+    let mut parts = Vec::new();
+    let mut holes = Vec::new();
+    for link in links {
+        while parts.len() + 1 < link.index.as_usize() {
+            parts.push((zero, zero));
+            holes.push(parts.len());
+        }
+        parts.push((link.source, link.target));
+    }
+    store.bulk_load(parts)?;
+    for hole in holes {
+        store.delete(T::try_from(hole).expect("holes are below loaded indices"))?;
+    }
+    Ok(())
+}
+
+fn compact<T: LinkType>(store: &impl Doublets<T>, output: &mut impl Doublets<T>) -> Result<()> {
+    if output.count() != T::funty(0) {
+        return Err("links can be compacted only into an empty store".into());
+    }
+    let links: Vec<_> = store.iter().collect();
+    let renumbered = links
+        .iter()
+        .enumerate()
+        .map(|(new, link)| {
+            Ok((
+                link.index,
+                T::try_from(new + 1).map_err(|_| "too many links")?,
+            ))
+        })
+        .collect::<Result<HashMap<_, _>>>()?;
+    let remap = |part| renumbered.get(&part).copied().unwrap_or(part);
+    for link in links {
+        let index = output.create()?;
+        output.update(index, remap(link.source), remap(link.target))?;
+    }
+    Ok(())
+}
+
+fn run<T: LinkType>(cli: &Cli) -> Result<()> {
+    let mut store = if cli.command.changes_links() {
+        open::<T>(&cli.file, cli.index.as_deref())?
+    } else {
+        open_read_only::<T>(&cli.file, cli.index.as_deref())?
+    };
+    let any = store.constants().any;
+    let mut out = BufWriter::new(io::stdout().lock());
+
+    match &cli.command {
+        Command::Info => info(cli, &*store)?,
+        Command::Query {
+            pattern,
+            count,
+            limit,
+        } => {
+            let query = parse_query(pattern, any)?;
+            if *count {
+                writeln!(out, "{}", store.count_links(&query))?;
+            } else {
+                let limit = limit.unwrap_or(usize::MAX);
+                let mut links = Vec::new();
+                if limit > 0 {
+                    store.each_links(&query, &mut |link| {
+                        links.push(link);
+                        if links.len() < limit {
+                            Flow::Continue
+                        } else {
+                            Flow::Break
+                        }
+                    });
+                }
+                write_links(&mut out, links)?;
+            }
+        }
+        Command::Create { source, target } => {
+            let index = match (source, target) {
+                (None, None) => store.create_point()?,
+                (Some(source), Some(target)) => {
+                    store.create_link(parse(source)?, parse(target)?)?
+                }
+                _ => return Err("both source and target are required".into()),
+            };
+            writeln!(out, "{index}")?;
+        }
+        Command::Update {
+            index,
+            source,
+            target,
+        } => {
+            store.update(parse(index)?, parse(source)?, parse(target)?)?;
+        }
+        Command::Delete { index } => {
+            store.delete(parse(index)?)?;
+        }
+        Command::Verify => {
+            let problems = verify(&store);
+            for problem in &problems {
+                writeln!(out, "{problem}")?;
+            }
+            out.flush()?;
+            if !problems.is_empty() {
+                return Err(format!("{} problems are found", problems.len()).into());
+            }
+            writeln!(out, "ok: {} links", store.count())?;
+        }
+        Command::Dump { output } => match output {
+            Some(output) => write_links(&mut BufWriter::new(File::create(output)?), store.iter())?,
+            None => write_links(&mut out, store.iter())?,
+        },
+        Command::Load { input } => {
+            let links = read_dump(input)?;
+            load(&mut *store, &links)?;
+            writeln!(out, "{} links are loaded", links.len())?;
+        }
+        Command::Compact {
+            output,
+            output_index,
+        } => {
+            if cli.index.is_some() != output_index.is_some() {
+                return Err("`--output-index` is required for split stores only".into());
+            }
+            let mut compacted = open::<T>(output, output_index.as_deref())?;
+            compact(&store, &mut compacted)?;
+            writeln!(
+                out,
+                "{} links, {} allocated before and {} after",
+                compacted.count(),
+                store.header().allocated,
+                compacted.header().allocated,
+            )?;
+        }
+    }
+    Ok(out.flush()?)
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+    match cli.width {
+        Width::U8 => run::<u8>(&cli),
+        Width::U16 => run::<u16>(&cli),
+        Width::U32 => run::<u32>(&cli),
+        Width::U64 => run::<u64>(&cli),
+    }
+}
@@ -1 +1,113 @@
+//! Helpers shared by the `doublets`, `doublets-repl` and `doublets-server` binaries.
 
+pub mod server;
+
+use std::{error::Error, ops::Range, path::Path};
+
+use clap::ValueEnum;
+use doublets::{
+    data::LinkType,
+    mem::{FileMapped, LinksHeader, RawMem, ReadOnlyMapped, Stats},
+    parts::{DataPart, IndexPart, LinkPart},
+    split, unit, Doublets, Error as LinksError,
+};
+
+pub type Result<T, E = Box<dyn Error>> = std::result::Result<T, E>;
+
+/// Width of link parts in bits.
+#[derive(Clone, Copy, ValueEnum)]
+pub enum Width {
+    #[value(name = "8")]
+    U8,
+    #[value(name = "16")]
+    U16,
+    #[value(name = "32")]
+    U32,
+    #[value(name = "64")]
+    U64,
+}
+
+/// Unit or split store of any memory.
+pub trait Store<T: LinkType>: Doublets<T> {
+    fn header(&self) -> &LinksHeader<T>;
+
+    fn stats(&self, top: usize) -> Stats<T>;
+
+    fn bulk_load(&mut self, links: Vec<(T, T)>) -> Result<Range<T>, LinksError<T>>;
+}
+
+impl<T: LinkType, M: RawMem<LinkPart<T>>> Store<T> for unit::Store<T, M> {
+    fn header(&self) -> &LinksHeader<T> {
+        self.get_header()
+    }
+
+    fn stats(&self, top: usize) -> Stats<T> {
+        self.stats(top)
+    }
+
+    fn bulk_load(&mut self, links: Vec<(T, T)>) -> Result<Range<T>, LinksError<T>> {
+        self.bulk_load(links)
+    }
+}
+
+impl<T: LinkType, MD: RawMem<DataPart<T>>, MI: RawMem<IndexPart<T>>> Store<T>
+    for split::Store<T, MD, MI>
+{
+    fn header(&self) -> &LinksHeader<T> {
+        self.get_header()
+    }
+
+    fn stats(&self, top: usize) -> Stats<T> {
+        self.stats(top)
+    }
+
+    fn bulk_load(&mut self, links: Vec<(T, T)>) -> Result<Range<T>, LinksError<T>> {
+        self.bulk_load(links)
+    }
+}
+
+/// Opens a split store if `index` is set and a unit store otherwise.
+pub fn open<T: LinkType>(file: &Path, index: Option<&Path>) -> Result<Box<dyn Store<T>>> {
+    Ok(match index {
+        Some(index) => Box::new(split::Store::<T, _, _>::new(
+            FileMapped::from_path(file)?,
+            FileMapped::from_path(index)?,
+        )?),
+        None => Box::new(unit::Store::<T, _>::new(FileMapped::from_path(file)?)?),
+    })
+}
+
+/// Opens existing files like [`open`], but without write permission,
+/// so changing the store fails with `Error::ReadOnly`.
+pub fn open_read_only<T: LinkType>(file: &Path, index: Option<&Path>) -> Result<Box<dyn Store<T>>> {
+    Ok(match index {
+        Some(index) => Box::new(split::Store::<T, _, _>::read_only(
+            ReadOnlyMapped::from_path(file)?,
+            ReadOnlyMapped::from_path(index)?,
+        )?),
+        None => Box::new(unit::Store::<T, _>::read_only(ReadOnlyMapped::from_path(
+            file,
+        )?)?),
+    })
+}
+
+pub fn parse<T: LinkType>(part: &str) -> Result<T> {
+    let value: usize = part
+        .parse()
+        .map_err(|_| format!("invalid link part: `{part}`"))?;
+    Ok(T::try_from(value).map_err(|_| format!("{value} does not fit into the link width"))?)
+}
+
+/// Parses `[index [source target]]` pattern where `*`, `_` or `any` matches any part.
+pub fn parse_query<T: LinkType, S: AsRef<str>>(pattern: &[S], any: T) -> Result<Vec<T>> {
+    if pattern.len() > 3 {
+        return Err("pattern has at most 3 parts: index, source and target".into());
+    }
+    pattern
+        .iter()
+        .map(|part| match part.as_ref() {
+            "*" | "_" | "any" => Ok(any),
+            part => parse(part),
+        })
+        .collect()
+}
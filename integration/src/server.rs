@@ -0,0 +1,238 @@
+//! HTTP server exposing a store with JSON links like `{"index":3,"source":1,"target":2}`.
+//!
+//! | Request | Body | Response |
+//! |---------|------|----------|
+//! | `GET /links?index=&source=&target=&cursor=&limit=` | | page of links |
+//! | `GET /links/count?index=&source=&target=` | | `{"count"}` |
+//! | `GET /links/{index}` | | link |
+//! | `POST /links` | `{"source", "target"}` or nothing for a point | created link |
+//! | `PUT /links/{index}` | `{"source", "target"}` | updated link |
+//! | `DELETE /links/{index}` | | deleted link |
+//!
+//! Pages are `{"total", "limit", "links", "next"}` where `total` counts all matching links
+//! and `next` is the `cursor` of the next page with the same pattern or `null` after the last one.
+//! Cursors keep the last seen link, so a page is found without visiting the previous ones.
+//! Patterns with an index or with both parts match few links and are returned in one page.
+//! Missing pattern parts and `*` match any part. Failed requests get `{"error"}`
+//! with `404` for missing links, `409` for duplicates and links with usages,
+//! `403` for read-only stores and `507` when the store is full.
+
+use std::{
+    io::Read,
+    marker::PhantomData,
+    net::{SocketAddr, ToSocketAddrs},
+};
+
+use doublets::{
+    data::{Flow, LinkType},
+    Cursor, Doublet, Doublets, Error as LinksError, Link,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{json, Value};
+use tiny_http::{Header, Method, Request, Response};
+
+use crate::{parse, Result};
+
+/// Number of links in a page when a query has no `limit`.
+pub const DEFAULT_LIMIT: usize = 100;
+
+/// Largest `limit` of a query.
+pub const MAX_LIMIT: usize = 10_000;
+
+type Failure = (u16, String);
+
+type Reply<R = (u16, Value)> = std::result::Result<R, Failure>;
+
+/// Server handling requests one by one over a store.
+pub struct Server<T: LinkType, S: Doublets<T>> {
+    http: tiny_http::Server,
+    store: S,
+    _phantom: PhantomData<T>,
+}
+
+fn bad_request(message: impl ToString) -> Failure {
+    (400, message.to_string())
+}
+
+fn failure<T: LinkType>(err: LinksError<T>) -> Failure {
+    let status = match err {
+        LinksError::NotExists(_) => 404,
+        LinksError::AlreadyExists(_) | LinksError::HasUsages(_) => 409,
+        LinksError::ReadOnly => 403,
+        LinksError::LimitReached(_) => 507,
+        _ => 500,
+    };
+    (status, err.to_string())
+}
+
+fn param<'a>(params: &[(&'a str, &'a str)], name: &str) -> Option<&'a str> {
+    params
+        .iter()
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| *value)
+}
+
+fn part<T: LinkType>(value: Option<&str>, any: T) -> Reply<T> {
+    match value {
+        None | Some("*" | "%2A" | "_" | "any") => Ok(any),
+        Some(value) => parse(value).map_err(bad_request),
+    }
+}
+
+fn number(value: Option<&str>, default: usize) -> Reply<usize> {
+    value.map_or(Ok(default), |value| {
+        value
+            .parse()
+            .map_err(|_| bad_request(format!("invalid number: `{value}`")))
+    })
+}
+
+impl<T, S> Server<T, S>
+where
+    T: LinkType + Serialize + DeserializeOwned,
+    S: Doublets<T>,
+{
+    /// Listens on `addr`, port `0` picks a free one.
+    pub fn bind(addr: impl ToSocketAddrs, store: S) -> Result<Self> {
+        Ok(Self {
+            http: tiny_http::Server::http(addr)?,
+            store,
+            _phantom: PhantomData,
+        })
+    }
+
+    #[must_use]
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.http.server_addr().to_ip()
+    }
+
+    /// Handles requests until the listener fails.
+    pub fn run(&mut self) -> Result<()> {
+        loop {
+            let request = self.http.recv()?;
+            self.respond(request);
+        }
+    }
+
+    fn respond(&mut self, mut request: Request) {
+        let mut body = String::new();
+        let reply = match request.as_reader().read_to_string(&mut body) {
+            Ok(_) => self.handle(request.method(), request.url(), &body),
+            Err(err) => Err(bad_request(err)),
+        };
+        let (status, value) =
+            reply.unwrap_or_else(|(status, error)| (status, json!({ "error": error })));
+        let header =
+            Header::from_bytes("Content-Type", "application/json").expect("header is valid");
+        // clients that went away do not stop the server
+        request
+            .respond(
+                Response::from_string(value.to_string())
+                    .with_status_code(status)
+                    .with_header(header),
+            )
+            .ok();
+    }
+
+    fn handle(&mut self, method: &Method, url: &str, body: &str) -> Reply {
+        let (path, params) = url.split_once('?').unwrap_or((url, ""));
+        let params: Vec<_> = params
+            .split('&')
+            .filter_map(|param| param.split_once('='))
+            .collect();
+        let segments: Vec<_> = path
+            .split('/')
+            .filter(|segment| !segment.is_empty())
+            .collect();
+
+        match (method, &segments[..]) {
+            (Method::Get, ["links"]) => {
+                let query = self.pattern(&params)?;
+                let cursor = param(&params, "cursor")
+                    .map(|token| {
+                        Cursor::parse(token)
+                            .ok_or_else(|| bad_request(format!("invalid cursor: `{token}`")))
+                    })
+                    .transpose()?;
+                let limit = number(param(&params, "limit"), DEFAULT_LIMIT)?.min(MAX_LIMIT);
+                Ok((200, self.page(&query, cursor, limit)?))
+            }
+            (Method::Get, ["links", "count"]) => {
+                let query = self.pattern(&params)?;
+                Ok((200, json!({ "count": self.store.count_links(&query) })))
+            }
+            (Method::Get, ["links", index]) => self.link(parse(index).map_err(bad_request)?),
+            (Method::Post, ["links"]) => {
+                let index = if body.trim().is_empty() {
+                    self.store.create_point()
+                } else {
+                    let doublet: Doublet<T> = serde_json::from_str(body).map_err(bad_request)?;
+                    self.store.create_link(doublet.source, doublet.target)
+                };
+                self.link(index.map_err(failure)?)
+                    .map(|(_, link)| (201, link))
+            }
+            (Method::Put, ["links", index]) => {
+                let index = parse(index).map_err(bad_request)?;
+                let doublet: Doublet<T> = serde_json::from_str(body).map_err(bad_request)?;
+                self.store
+                    .update(index, doublet.source, doublet.target)
+                    .map_err(failure)?;
+                self.link(index)
+            }
+            (Method::Delete, ["links", index]) => {
+                let index = parse(index).map_err(bad_request)?;
+                let reply = self.link(index)?;
+                self.store.delete(index).map_err(failure)?;
+                Ok(reply)
+            }
+            (_, ["links"] | ["links", _]) => {
+                Err((405, format!("{method} is not allowed for {path}")))
+            }
+            _ => Err((404, format!("{path} is not found"))),
+        }
+    }
+
+    fn link(&self, index: T) -> Reply {
+        let link = self.store.try_get_link(index).map_err(failure)?;
+        Ok((200, json!(link)))
+    }
+
+    fn pattern(&self, params: &[(&str, &str)]) -> Reply<[T; 3]> {
+        let any = self.store.constants().any;
+        Ok([
+            part(param(params, "index"), any)?,
+            part(param(params, "source"), any)?,
+            part(param(params, "target"), any)?,
+        ])
+    }
+
+    fn page(&self, query: &[T], cursor: Option<Cursor<T>>, limit: usize) -> Reply<Value> {
+        let scan = Cursor::from_query(query, self.store.constants());
+        let (links, next) = match (scan, cursor) {
+            (Some(_), Some(cursor)) => self.store.page_by_cursor(&cursor, limit),
+            (Some(scan), None) => self.store.page_by_cursor(&scan, limit),
+            (None, Some(_)) => return Err(bad_request("the pattern has a single page")),
+            (None, None) => {
+                let mut links = Vec::new();
+                if limit > 0 {
+                    self.store.each_links(query, &mut |link: Link<T>| {
+                        links.push(link);
+                        if links.len() < limit {
+                            Flow::Continue
+                        } else {
+                            Flow::Break
+                        }
+                    });
+                }
+                (links, None)
+            }
+        };
+        Ok(json!({
+            "total": self.store.count_links(query),
+            "limit": limit,
+            "links": links,
+            "next": next.map(|cursor| cursor.to_string()),
+        }))
+    }
+}
@@ -0,0 +1,140 @@
+// Tests for the `doublets` command-line tool
+
+use std::{env, fs, path::PathBuf, process::Command};
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("doublets-cli-{name}-{}", std::process::id()));
+    fs::remove_dir_all(&dir).ok();
+    fs::create_dir_all(&dir).expect("temp dir is created");
+    dir
+}
+
+fn doublets(args: &[&str]) -> Result<String, String> {
+    let output = Command::new(env!("CARGO_BIN_EXE_doublets"))
+        .args(args)
+        .output()
+        .expect("doublets runs");
+    if output.status.success() {
+        Ok(String::from_utf8(output.stdout).expect("output is utf-8"))
+    } else {
+        Err(String::from_utf8(output.stderr).expect("output is utf-8"))
+    }
+}
+
+fn lines(output: Result<String, String>) -> Vec<String> {
+    output
+        .expect("command succeeds")
+        .lines()
+        .map(str::to_owned)
+        .collect()
+}
+
+#[test]
+fn crud_and_query() {
+    let dir = temp_dir("crud");
+    let file = dir.join("db.links");
+    let file = file.to_str().expect("path is utf-8");
+    let run = |args: &[&str]| doublets(&[&[file, "--width", "32"][..], args].concat());
+
+    assert_eq!(lines(run(&["create"])), ["1"]);
+    assert_eq!(lines(run(&["create"])), ["2"]);
+    assert_eq!(lines(run(&["create", "1", "2"])), ["3"]);
+
+    assert_eq!(lines(run(&["query", "*", "1", "*"])), ["1 1 1", "3 1 2"]);
+    assert_eq!(lines(run(&["query", "3"])), ["3 1 2"]);
+    assert_eq!(lines(run(&["query", "--limit", "1"])), ["1 1 1"]);
+    assert_eq!(lines(run(&["query", "--count"])), ["3"]);
+
+    run(&["update", "3", "2", "1"]).expect("link is updated");
+    assert_eq!(lines(run(&["query", "*", "*", "1"])), ["1 1 1", "3 2 1"]);
+    run(&["delete", "3"]).expect("link is deleted");
+    assert_eq!(lines(run(&["query", "--count"])), ["2"]);
+
+    assert!(run(&["delete", "3"]).is_err());
+    assert!(run(&["create", "1"]).is_err());
+    assert!(run(&["query", "1", "2", "3", "4"]).is_err());
+    assert!(lines(run(&["verify"])).contains(&"ok: 2 links".to_owned()));
+    assert!(lines(run(&["info"])).contains(&"points: 2".to_owned()));
+
+    fs::remove_dir_all(dir).ok();
+}
+
+#[test]
+fn reading_keeps_files() {
+    let dir = temp_dir("read");
+    let file = dir.join("db.links");
+    let path = file.to_str().expect("path is utf-8");
+
+    assert!(doublets(&[path, "query"]).is_err());
+    assert!(!file.exists());
+
+    doublets(&[path, "create"]).expect("point is created");
+    let bytes = fs::read(&file).expect("file is read");
+    assert_eq!(lines(doublets(&[path, "query"])), ["1 1 1"]);
+    doublets(&[path, "verify"]).expect("store is verified");
+    doublets(&[path, "info"]).expect("info is shown");
+    assert_eq!(fs::read(&file).expect("file is read"), bytes);
+
+    fs::remove_dir_all(dir).ok();
+}
+
+#[test]
+fn width_is_checked() {
+    let dir = temp_dir("width");
+    let file = dir.join("db.links");
+    let file = file.to_str().expect("path is utf-8");
+
+    doublets(&[file, "--width", "8", "create"]).expect("point is created");
+    assert!(doublets(&[file, "--width", "8", "create", "1", "300"]).is_err());
+    assert!(doublets(&[file, "--width", "12", "create"]).is_err());
+
+    fs::remove_dir_all(dir).ok();
+}
+
+#[test]
+fn dump_load_and_compact() {
+    let dir = temp_dir("dump");
+    let path = |name: &str| dir.join(name).to_str().expect("path is utf-8").to_owned();
+    let (data, index) = (path("db.data"), path("db.index"));
+    let split =
+        |args: &[&str]| doublets(&[&[data.as_str(), "--index", index.as_str()][..], args].concat());
+
+    for _ in 0..4 {
+        split(&["create"]).expect("point is created");
+    }
+    split(&["create", "4", "1"]).expect("link is created");
+    split(&["delete", "2"]).expect("link is deleted");
+    let dump = path("dump.txt");
+    split(&["dump", &dump]).expect("links are dumped");
+    let links = ["1 1 1", "3 3 3", "4 4 4", "5 4 1"];
+    assert_eq!(lines(split(&["dump"])), links);
+
+    // indices are kept by load
+    let loaded = path("loaded.data");
+    let loaded_index = path("loaded.index");
+    doublets(&[&loaded, "--index", &loaded_index, "load", &dump]).expect("links are loaded");
+    assert_eq!(
+        lines(doublets(&[&loaded, "--index", &loaded_index, "dump"])),
+        links
+    );
+    assert!(doublets(&[&loaded, "--index", &loaded_index, "load", &dump]).is_err());
+
+    // invalid dumps are rejected before anything is written
+    let (unit, invalid) = (path("loaded.links"), path("invalid.txt"));
+    fs::write(&invalid, "1 1 1\n3 1 1\n3 3 3\n").expect("dump is written");
+    assert!(doublets(&[&unit, "load", &invalid]).is_err());
+    doublets(&[&unit, "load", &dump]).expect("links are loaded");
+    assert_eq!(lines(doublets(&[&unit, "dump"])), links);
+
+    // and renumbered by compact
+    let compacted = path("compacted.data");
+    let compacted_index = path("compacted.index");
+    split(&["compact", &compacted, "--output-index", &compacted_index]).expect("compacted");
+    assert_eq!(
+        lines(doublets(&[&compacted, "--index", &compacted_index, "dump"])),
+        ["1 1 1", "2 2 2", "3 3 3", "4 3 1"]
+    );
+    assert!(split(&["compact", &path("unit.links")]).is_err());
+
+    fs::remove_dir_all(dir).ok();
+}
@@ -0,0 +1,100 @@
+// Tests for the `doublets-repl` binary fed by piped commands
+
+use std::{
+    env, fs,
+    io::Write,
+    path::PathBuf,
+    process::{Command, Output, Stdio},
+};
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("doublets-repl-{name}-{}", std::process::id()));
+    fs::remove_dir_all(&dir).ok();
+    fs::create_dir_all(&dir).expect("temp dir is created");
+    dir
+}
+
+fn repl(args: &[&str], input: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_doublets-repl"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("doublets-repl runs");
+    child
+        .stdin
+        .take()
+        .expect("stdin is piped")
+        .write_all(input.as_bytes())
+        .expect("input is written");
+    child.wait_with_output().expect("doublets-repl exits")
+}
+
+fn stdout(output: &Output) -> Vec<&str> {
+    std::str::from_utf8(&output.stdout)
+        .expect("output is utf-8")
+        .lines()
+        .collect()
+}
+
+#[test]
+fn crud_names_and_tree() {
+    let dir = temp_dir("crud");
+    let file = dir.join("db.links");
+    let file = file.to_str().expect("path is utf-8");
+    let names = dir.join("names.txt");
+    let names = names.to_str().expect("path is utf-8");
+
+    let output = repl(
+        &[file, "--write", "--names", names],
+        "create\ncreate\ncreate 1 2\ncreate 3 2\nname 1 alice\nname 2 bob\n",
+    );
+    assert!(output.status.success());
+    assert_eq!(
+        stdout(&output),
+        ["1 (1 1)", "2 (2 2)", "3 (1 2)", "4 (3 2)"]
+    );
+
+    // names are kept in the names file
+    let output = repl(
+        &[file, "--names", names],
+        "* 1 *\ncount\ntree 1\ntree 1 1\nnames\n",
+    );
+    assert_eq!(
+        stdout(&output),
+        [
+            "1=alice (1=alice 1=alice)",
+            "3 (1=alice 2=bob)",
+            "4",
+            "1=alice (1=alice 1=alice)",
+            "  3 (1=alice 2=bob)",
+            "    4 (3 2=bob)",
+            "1=alice (1=alice 1=alice)",
+            "  3 (1=alice 2=bob)",
+            "1 alice",
+            "2 bob",
+        ]
+    );
+
+    fs::remove_dir_all(dir).ok();
+}
+
+#[test]
+fn read_only_by_default() {
+    let dir = temp_dir("read-only");
+    let file = dir.join("db.links");
+    let file = file.to_str().expect("path is utf-8");
+
+    assert!(!repl(&[file], "").status.success());
+    assert!(repl(&[file, "--write"], "create\n").status.success());
+
+    let output = repl(&[file], "create\ndelete 1\nfoo\nexit\n1\n");
+    assert!(output.status.success());
+    assert!(stdout(&output).is_empty());
+    let errors = String::from_utf8(output.stderr).expect("output is utf-8");
+    assert_eq!(errors.lines().count(), 3);
+    assert!(errors.contains("read-only"));
+
+    fs::remove_dir_all(dir).ok();
+}
@@ -0,0 +1,190 @@
+// Tests for the HTTP server exposing a store, run on a loopback port
+
+use std::thread;
+
+use doublets::{
+    mem::{Duplicates, Global},
+    split, unit, Doublets,
+};
+use integration::server::{Server, MAX_LIMIT};
+use serde_json::{json, Value};
+
+fn serve<S: Doublets<usize> + 'static>(store: S) -> String {
+    let mut server = Server::bind("127.0.0.1:0", store).expect("server is bound");
+    let addr = server.local_addr().expect("server listens on ip");
+    thread::spawn(move || server.run().ok());
+    format!("http://{addr}")
+}
+
+fn call(method: &str, url: &str, body: Option<Value>) -> (u16, Value) {
+    let request = ureq::request(method, url);
+    let result = match body {
+        Some(body) => request.send_string(&body.to_string()),
+        None => request.call(),
+    };
+    let response = match result {
+        Ok(response) | Err(ureq::Error::Status(_, response)) => response,
+        Err(err) => panic!("request fails: {err}"),
+    };
+    let status = response.status();
+    let body = response.into_string().expect("body is read");
+    (status, serde_json::from_str(&body).expect("body is json"))
+}
+
+fn crud_impl<S: Doublets<usize> + 'static>(store: S) {
+    let url = serve(store);
+    let links = format!("{url}/links");
+
+    assert_eq!(
+        call("POST", &links, None),
+        (201, json!({ "index": 1, "source": 1, "target": 1 }))
+    );
+    call("POST", &links, None);
+    let link = json!({ "index": 3, "source": 1, "target": 2 });
+    assert_eq!(
+        call("POST", &links, Some(json!({ "source": 1, "target": 2 }))),
+        (201, link.clone())
+    );
+    assert_eq!(call("GET", &format!("{links}/3"), None), (200, link));
+
+    let updated = json!({ "index": 3, "source": 2, "target": 1 });
+    assert_eq!(
+        call(
+            "PUT",
+            &format!("{links}/3"),
+            Some(json!({ "source": 2, "target": 1 }))
+        ),
+        (200, updated.clone())
+    );
+    assert_eq!(call("DELETE", &format!("{links}/3"), None), (200, updated));
+    assert_eq!(
+        call("GET", &format!("{links}/count"), None),
+        (200, json!({ "count": 2 }))
+    );
+
+    assert_eq!(call("GET", &format!("{links}/3"), None).0, 404);
+    assert_eq!(call("DELETE", &format!("{links}/3"), None).0, 404);
+    assert_eq!(
+        call("PUT", &format!("{links}/1"), Some(json!({ "source": 1 }))).0,
+        400
+    );
+    assert_eq!(call("GET", &format!("{links}/x"), None).0, 400);
+    assert_eq!(call("PATCH", &links, None).0, 405);
+    assert!(call("GET", &format!("{url}/nodes"), None).1["error"].is_string());
+}
+
+fn query_impl<S: Doublets<usize> + 'static>(store: S) {
+    let url = serve(store);
+    let links = format!("{url}/links");
+    for _ in 0..3 {
+        call("POST", &links, None);
+    }
+    for target in 2..=3 {
+        call(
+            "POST",
+            &links,
+            Some(json!({ "source": 1, "target": target })),
+        );
+    }
+
+    // pages of usages are ordered by the other part and continued by cursors
+    let (status, page) = call("GET", &format!("{links}?source=1&limit=2"), None);
+    assert_eq!(status, 200);
+    assert_eq!(page["total"], 3);
+    assert_eq!(
+        page["links"],
+        json!([
+            { "index": 1, "source": 1, "target": 1 },
+            { "index": 4, "source": 1, "target": 2 },
+        ])
+    );
+    let next = page["next"].as_str().expect("next page has a cursor");
+    let (_, page) = call(
+        "GET",
+        &format!("{links}?source=1&limit=2&cursor={next}"),
+        None,
+    );
+    assert_eq!(
+        page["links"],
+        json!([{ "index": 5, "source": 1, "target": 3 }])
+    );
+    assert!(page["next"].is_null());
+
+    let (_, page) = call("GET", &format!("{links}?index=*&target=3"), None);
+    assert_eq!(page["total"], 2);
+    assert_eq!(page["links"].as_array().map(Vec::len), Some(2));
+
+    let (_, page) = call("GET", &format!("{links}?source=1&target=2"), None);
+    assert_eq!(page["total"], 1);
+    assert!(page["next"].is_null());
+    assert_eq!(
+        call("GET", &format!("{links}?index=4&cursor={next}"), None).0,
+        400
+    );
+
+    let (_, page) = call("GET", &format!("{links}?limit={}", MAX_LIMIT + 1), None);
+    assert_eq!(page["limit"], MAX_LIMIT);
+    assert_eq!(page["links"].as_array().map(Vec::len), Some(5));
+
+    assert_eq!(
+        call("GET", &format!("{links}/count?source=1&target=2"), None),
+        (200, json!({ "count": 1 }))
+    );
+    assert_eq!(call("GET", &format!("{links}?cursor=!"), None).0, 400);
+}
+
+fn conflicts_impl<S: Doublets<usize> + 'static>(store: S) {
+    let links = format!("{}/links", serve(store));
+    call("POST", &links, None);
+    let link = Some(json!({ "source": 1, "target": 1 }));
+    let (status, body) = call("POST", &links, link);
+    assert_eq!(status, 409);
+    assert!(body["error"].is_string());
+}
+
+fn unit_store() -> unit::Store<usize, Global<unit::LinkPart<usize>>> {
+    unit::Store::new(Global::new()).expect("store is created")
+}
+
+fn split_store()
+-> split::Store<usize, Global<split::DataPart<usize>>, Global<split::IndexPart<usize>>> {
+    split::Store::new(Global::new(), Global::new()).expect("store is created")
+}
+
+#[test]
+fn unit_crud() {
+    crud_impl(unit_store());
+}
+
+#[test]
+fn split_crud() {
+    crud_impl(split_store());
+}
+
+#[test]
+fn unit_conflicts() {
+    conflicts_impl(unit_store().with_duplicates(Duplicates::Reject));
+}
+
+#[test]
+fn split_conflicts() {
+    conflicts_impl(split_store().with_duplicates(Duplicates::Reject));
+}
+
+#[test]
+fn read_only_store() {
+    let store = unit::Store::<usize, _>::read_only(Global::new()).expect("store is opened");
+    let links = format!("{}/links", serve(store));
+    assert_eq!(call("POST", &links, None).0, 403);
+    assert_eq!(call("GET", &links, None).0, 200);
+}
+
+#[test]
+fn unit_query() {
+    query_impl(unit_store());
+}
+
+#[test]
+fn split_query() {
+    query_impl(split_store());
+}
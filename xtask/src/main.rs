@@ -0,0 +1,110 @@
+//! Development tasks run by `cargo xtask <task>`:
+//!
+//! - `ffi-install <prefix>` builds `doublets-ffi` in release mode and installs
+//!   its libraries into `<prefix>/lib`, `doublets.h` into `<prefix>/include`
+//!   and `doublets.pc` into `<prefix>/lib/pkgconfig`
+
+use std::{
+    collections::HashMap,
+    env,
+    error::Error,
+    fs,
+    io::{BufRead, BufReader},
+    path::{Path, PathBuf},
+    process::{exit, Command, Stdio},
+};
+
+use serde_json::Value;
+
+type Result<T> = std::result::Result<T, Box<dyn Error>>;
+
+// libraries of `doublets-ffi` and the output directory of its build script
+struct Artifacts {
+    libraries: Vec<PathBuf>,
+    out_dir: PathBuf,
+}
+
+fn build_ffi() -> Result<Artifacts> {
+    let cargo = env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+    let mut child = Command::new(cargo)
+        .args(["build", "--release", "--package", "doublets-ffi"])
+        .arg("--message-format=json-render-diagnostics")
+        .stdout(Stdio::piped())
+        .spawn()?;
+    let stdout = child.stdout.take().expect("stdout is piped");
+
+    let mut out_dirs = HashMap::new();
+    let mut artifacts = None;
+    for line in BufReader::new(stdout).lines() {
+        let message: Value = serde_json::from_str(&line?)?;
+        let package = message["package_id"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string();
+        match message["reason"].as_str() {
+            Some("build-script-executed") => {
+                out_dirs.insert(package, message["out_dir"].clone());
+            }
+            Some("compiler-artifact") if message["target"]["name"] == "doublets_ffi" => {
+                let libraries = message["filenames"]
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(Value::as_str)
+                    .map(PathBuf::from)
+                    .collect();
+                artifacts = Some((package, libraries));
+            }
+            _ => {}
+        }
+    }
+    if !child.wait()?.success() {
+        return Err("unable to build doublets-ffi".into());
+    }
+
+    let (package, libraries) = artifacts.ok_or("doublets-ffi libraries are not built")?;
+    let out_dir = out_dirs
+        .get(&package)
+        .and_then(Value::as_str)
+        .ok_or("doublets-ffi build script is not run")?;
+    Ok(Artifacts {
+        libraries,
+        out_dir: PathBuf::from(out_dir),
+    })
+}
+
+fn ffi_install(prefix: &Path) -> Result<()> {
+    let Artifacts { libraries, out_dir } = build_ffi()?;
+    let include = prefix.join("include");
+    let lib = prefix.join("lib");
+    let pkgconfig = lib.join("pkgconfig");
+    fs::create_dir_all(&include)?;
+    fs::create_dir_all(&pkgconfig)?;
+
+    let mut installed = vec![
+        (out_dir.join("doublets.h"), include.join("doublets.h")),
+        (out_dir.join("doublets.pc"), pkgconfig.join("doublets.pc")),
+    ];
+    for library in libraries {
+        let name = library.file_name().ok_or("library path is not a file")?;
+        let to = lib.join(name);
+        installed.push((library, to));
+    }
+    for (from, to) in installed {
+        fs::copy(&from, &to)?;
+        println!("installed {}", to.display());
+    }
+    Ok(())
+}
+
+fn main() {
+    let args: Vec<_> = env::args().skip(1).collect();
+    let result = match &args.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+        ["ffi-install", prefix] => ffi_install(Path::new(prefix)),
+        _ => Err("usage: cargo xtask ffi-install <prefix>".into()),
+    };
+    if let Err(err) = result {
+        eprintln!("{err}");
+        exit(1);
+    }
+}